@@ -1,7 +1,8 @@
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
+use crate::reconcile::Reconciliation;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BalanceType {
   Debit,
   Credit,
@@ -16,20 +17,84 @@ impl BalanceType {
   }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AccountType {
   Liability,
   Equity,
   Asset,
+  Revenue,
+  Expense,
+}
+
+impl AccountType {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      AccountType::Liability => "Liability",
+      AccountType::Equity => "Equity",
+      AccountType::Asset => "Asset",
+      AccountType::Revenue => "Revenue",
+      AccountType::Expense => "Expense",
+    }
+  }
+
+  pub fn normal_balance(self) -> BalanceType {
+    match self {
+      AccountType::Asset | AccountType::Expense => BalanceType::Debit,
+      AccountType::Liability 
+      | AccountType::Equity 
+      | AccountType::Revenue => BalanceType::Credit,
+    }
+  }
+}
+
+/// Section of the cash flow statement an account's cash movements belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CashFlowActivity {
+  Operating,
+  Investing,
+  Financing,
+}
+
+impl CashFlowActivity {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      CashFlowActivity::Operating => "Operating",
+      CashFlowActivity::Investing => "Investing",
+      CashFlowActivity::Financing => "Financing",
+    }
+  }
+
+  /// Classification used for accounts without an explicit activity. Assets
+  /// and liabilities are taken to be current, such as receivables and
+  /// credit cards; long-term ones are set to investing or financing by hand.
+  pub fn default_for(account_type: AccountType) -> CashFlowActivity {
+    match account_type {
+      AccountType::Asset
+      | AccountType::Liability
+      | AccountType::Revenue
+      | AccountType::Expense => CashFlowActivity::Operating,
+      AccountType::Equity => CashFlowActivity::Financing,
+    }
+  }
 }
 
 pub type AccountId = String;
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
   pub id: AccountId,
   pub name: String,
   pub balance_type: BalanceType,
   pub account_type: AccountType,
+  #[serde(default)]
+  pub parent_id: Option<AccountId>,
+  /// Cash or cash equivalent, tracked by the cash flow statement.
+  #[serde(default)]
+  pub is_cash: bool,
+  #[serde(default)]
+  pub activity: Option<CashFlowActivity>,
+  /// Finished reconciliations, oldest first.
+  #[serde(default)]
+  pub reconciliations: Vec<Reconciliation>,
   reference_count: u32
 }
 
@@ -40,10 +105,18 @@ impl Account {
       name,
       balance_type,
       account_type,
+      parent_id: None,
+      is_cash: false,
+      activity: None,
+      reconciliations: Vec::new(),
       reference_count: 0
     }
   }
 
+  pub fn cash_flow_activity(&self) -> CashFlowActivity {
+    self.activity.unwrap_or_else(|| CashFlowActivity::default_for(self.account_type))
+  }
+
   pub fn increment_reference(mut self) {
     self.reference_count += 1;
   }
@@ -17,13 +17,39 @@ use serde::{Serialize, Deserialize};
 
 pub type AccountChangeId = String;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How far a change has been checked against the account's statements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ClearedStatus {
+  #[default]
+  Uncleared,
+  /// Ticked off during a reconciliation that is not finished yet.
+  Cleared,
+  /// Part of a finished reconciliation.
+  Reconciled,
+}
+
+impl ClearedStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ClearedStatus::Uncleared => "Uncleared",
+      ClearedStatus::Cleared => "Cleared",
+      ClearedStatus::Reconciled => "Reconciled",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountChange {
   pub id: AccountChangeId,
   pub account_id: AccountId,
   pub currency_id: CurrencyId,
   pub credit_or_debit: BalanceType,
   pub amount: f64,
+  #[serde(default)]
+  pub status: ClearedStatus,
+  /// Note on this posting alone, such as a QIF split memo.
+  #[serde(default)]
+  pub memo: String,
 }
 
 impl AccountChange {
@@ -38,7 +64,23 @@ impl AccountChange {
       account_id, 
       currency_id,
       credit_or_debit, 
-      amount
+      amount,
+      status: ClearedStatus::Uncleared,
+      memo: String::new(),
+    }
+  }
+
+  /// Reconciled postings agree with a bank statement, so changing them
+  /// needs confirming.
+  pub fn is_reconciled(&self) -> bool {
+    self.status == ClearedStatus::Reconciled
+  }
+
+  /// Amount of the change, positive when it is posted on `positive_side`.
+  pub fn signed_amount(&self, positive_side: BalanceType) -> f64 {
+    match self.credit_or_debit == positive_side {
+      true => self.amount,
+      false => -self.amount,
     }
   }
 
@@ -64,4 +106,18 @@ impl AccountChange {
 
     format!("{} - {}: {}", c_or_d, account_name, self.amount)
   }
-}
\ No newline at end of file
+}
+/// Debit positive totals of the currencies that do not balance, in the
+/// order the currencies first appear.
+pub fn imbalances<'a>(changes: impl IntoIterator<Item = &'a AccountChange>) -> Vec<(CurrencyId, f64)> {
+  let mut totals: Vec<(CurrencyId, f64)> = Vec::new();
+  for change in changes {
+    let amount = change.signed_amount(BalanceType::Debit);
+    match totals.iter_mut().find(|(id, _)| id == &change.currency_id) {
+      Some((_, total)) => *total += amount,
+      None => totals.push((change.currency_id.clone(), amount)),
+    }
+  }
+  totals.retain(|(_, total)| total.abs() >= 0.005);
+  totals
+}
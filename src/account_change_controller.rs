@@ -5,7 +5,7 @@ use crate::{
   transaction::{
     TransactionId
   },
-  page::Page, 
+  page::{Navigation, Page},
   utility::*, 
   journal::{
     JournalId, 
@@ -13,7 +13,10 @@ use crate::{
   }, 
   unwrapper::*, 
   account::BalanceType, 
-  currency::CurrencyId, 
+  budget_controller::warn_over_budget,
+  display::account_change_details,
+  reconcile_controller::confirm_reconciled,
+  currency::{Currency, CurrencyId}, 
   account_change::{
     AccountChange, 
     AccountChangeId
@@ -21,10 +24,11 @@ use crate::{
 };
 
 pub fn new_account_change<'a>(
+  currencies: &HashMap<CurrencyId, Currency>,
   currency_id: CurrencyId,
   journal_id: &Option<JournalId>,
   journals: &'a mut HashMap<JournalId, Journal>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
   transaction_id: &Option<TransactionId>
 ) {
   let credit_or_debit = { 
@@ -34,32 +38,37 @@ pub fn new_account_change<'a>(
     ];
 
     match select_with_labels("Credit Or Debit:".to_string(), &options) {
-      Err(error) => return on_error(page, error),
-      Ok(credit_or_debit) => credit_or_debit.clone(),
+      Err(error) => return on_error(navigation, error),
+      Ok(credit_or_debit) => *credit_or_debit,
     }
   };
 
   let account_id = {
     let accounts = match get_accounts(journals, journal_id) {
-      None => return on_not_found(page),
+      None => return on_not_found(navigation),
       Some(accounts) => accounts,
     };
 
-    let prompt = format!("Account:");
+    let prompt = "Account:".to_string();
     let options = accounts.iter().map(
       |(id, a)| LabeledItem::from(a.name.clone(), id)
     ).collect::<Vec<_>>();
     match fuzzy_input_with_labels(prompt, &options) {
-      Err(error) => return on_error(page, error),
-      Ok(account_id) => *account_id,
+      Err(error) => return on_error(navigation, error),
+      Ok(account_id) => (*account_id).clone(),
     }
   };
 
+  let currency_id = match input_currency(currencies, &currency_id) {
+    Err(error) => return on_error(navigation, error),
+    Ok(currency_id) => currency_id,
+  };
+
   let amount = {
     let prompt = format!("Enter {} Amount:", credit_or_debit.as_str());
-    let default = format!(""); 
+    let default = String::new(); 
     match input_until_parsed(prompt, default) {
-      Err(error) => return on_error(page, error),
+      Err(error) => return on_error(navigation, error),
       Ok(amount) => amount,
     }
   };
@@ -73,20 +82,24 @@ pub fn new_account_change<'a>(
 
   let transaction = {
     match get_transaction_mut(journals, journal_id, transaction_id) {
-      None => return on_not_found(page),
+      None => return on_not_found(navigation),
       Some(transaction) => transaction,
     }
   };
 
-  transaction.account_changes.insert(account_change.id.clone(), account_change);
-  *page = Page::SelectAccountChange;
+  let date = transaction.parsed_date();
+  transaction.account_changes.insert(account_change.id.clone(), account_change.clone());
+  navigation.back();
+
+  if let (Some(journal), Some(date)) = (get_journal(journals, journal_id), date) {
+    warn_over_budget(currencies, journal, &[&account_change], date);
+  }
 }
 
 pub fn select_account_change<'a>(
-  account_change_id: &mut Option<AccountChangeId>,
   journal_id: &Option<JournalId>,
   journals: &'a mut HashMap<JournalId, Journal>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
   transaction_id: &Option<TransactionId>,
 ) {
   enum Selection {
@@ -97,7 +110,7 @@ pub fn select_account_change<'a>(
 
   let account_changes = {
     match get_account_changes(journals, journal_id, transaction_id) {
-      None => return on_not_found(page),
+      None => return on_not_found(navigation),
       Some(account_changes) => account_changes,
     }
   };
@@ -127,7 +140,7 @@ pub fn select_account_change<'a>(
       "Select Change".to_string(), 
       &options
     ) {
-      Err(error) => return on_error(page, error),
+      Err(error) => return on_error(navigation, error),
       Ok(selection) => selection,
     }
   };
@@ -135,27 +148,25 @@ pub fn select_account_change<'a>(
   match selection {
     Selection::AccountChange(id) => {
       match account_changes.get(id) {
-        None => return on_not_found(page),
-        Some(account_change) => {
-          *account_change_id = Some(account_change.id.clone());
-          *page = Page::ViewAccountChange;
-        },
-      };    
+        None => on_not_found(navigation),
+        Some(account_change) => navigation.open_account_change(account_change.id.clone()),
+      }
     },
     Selection::NewAccountChange => {
-      *page = Page::NewAccountChange;
+      navigation.go(Page::NewAccountChange);
     },
     Selection::Back => {
-      *page = Page::ViewTransaction;
+      navigation.back();
     },
-  };
+  }
 }
 
 pub fn view_account_change<'a>(
-  account_change_id: &mut Option<AccountChangeId>,
+  currencies: &HashMap<CurrencyId, Currency>,
+  account_change_id: &Option<AccountChangeId>,
   journal_id: &Option<JournalId>,
   journals: &'a mut HashMap<JournalId, Journal>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
   transaction_id: &Option<TransactionId>,
 ) {
   enum Selection {
@@ -176,7 +187,7 @@ pub fn view_account_change<'a>(
     transaction_id, 
     account_change_id
   ) {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(account_change) => account_change,
   };
 
@@ -185,18 +196,28 @@ pub fn view_account_change<'a>(
       account_change.lookup_name(journals, journal_id), 
       &options
     ) {
-      Err(error) => return on_error(page, error),
+      Err(error) => return on_error(navigation, error),
       Ok(selection) => selection,
     }
   };
 
   match *selection {
-    Selection::Display => println!("{:#?}", account_change),
+    Selection::Display => {
+      let details = get_journal(journals, journal_id)
+        .zip(get_transaction(journals, journal_id, transaction_id))
+        .map(|(journal, transaction)| {
+          account_change_details(journal, currencies, transaction, account_change)
+        });
+      match details {
+        None => on_not_found(navigation),
+        Some(details) => println!("{}", details),
+      }
+    },
     Selection::Back => {
-      *page = Page::SelectTransaction
+      navigation.back()
     },
     Selection::Delete => {
-      *page = Page::DeleteAccountChange
+      navigation.go(Page::DeleteAccountChange)
     },
   }
 }
@@ -205,36 +226,37 @@ pub fn delete_account_change<'a>(
   account_change_id: &Option<AccountChangeId>,
   journal_id: &Option<JournalId>,
   journals: &'a mut HashMap<JournalId, Journal>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
   transaction_id: &Option<TransactionId>,
 ) {
-  let name = {
+  let (name, is_reconciled) = {
     match get_account_changes(
       journals, 
       journal_id, 
       transaction_id, 
     )
-    .map(|account_changes| account_changes
-      .get_optional(account_change_id.clone())
-    )
-    .flatten()
-    .map(|a| a.lookup_name(journals, journal_id))
+    .and_then(|account_changes| account_changes
+      .get_optional(account_change_id.clone()))
+    .map(|a| (a.lookup_name(journals, journal_id), a.is_reconciled()))
     {
-      None => return on_not_found(page),
-      Some(name) => name,
+      None => return on_not_found(navigation),
+      Some(found) => found,
     }
   };
 
   let prompt = format!("Are you sure you want to delete \"{}\"?", name);
 
-  let should_delete = match confirm_default(prompt) {
-    Err(error) => return on_error(page, error),
+  let should_delete = match confirm_default(prompt).and_then(|confirmed| match confirmed {
+    true => confirm_reconciled(is_reconciled as usize, "Delete"),
+    false => Ok(false),
+  }) {
+    Err(error) => return on_error(navigation, error),
     Ok(should_delete) => should_delete,
   };
   
   match should_delete {
     false => {
-      *page = Page::ViewTransaction
+      navigation.back()
     },
     true => {
       let account_changes = get_account_changes_mut(
@@ -250,7 +272,7 @@ pub fn delete_account_change<'a>(
           a.remove(&id)
         )
       );
-      *page = Page::SelectAccountChange
+      navigation.back_to(Page::SelectAccountChange)
     },
   }
 }
\ No newline at end of file
@@ -3,9 +3,11 @@ use std::collections::HashMap;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input};
 
 use crate::{
-  account::{Account, AccountId, AccountType, BalanceType},
+  account::{Account, AccountId, AccountType, BalanceType, CashFlowActivity},
+  currency::{Currency, CurrencyId},
+  display::account_details,
   journal::Journal,
-  page::Page,
+  page::{Navigation, Page},
   utility::{
     on_error, 
     on_not_found,
@@ -14,9 +16,9 @@ use crate::{
   },
 };
 
-pub fn new_account<'a>(accounts: Option<&'a mut HashMap<AccountId, Account>>, page: &'a mut Page) {
+pub fn new_account<'a>(accounts: Option<&'a mut HashMap<AccountId, Account>>, navigation: &'a mut Navigation) {
   let accounts = match accounts {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(accounts) => accounts,
   };
 
@@ -25,7 +27,7 @@ pub fn new_account<'a>(accounts: Option<&'a mut HashMap<AccountId, Account>>, pa
     .with_initial_text(format!("Account {}", accounts.len()))
     .interact()
   {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(name) => name,
   };
 
@@ -34,33 +36,32 @@ pub fn new_account<'a>(accounts: Option<&'a mut HashMap<AccountId, Account>>, pa
     LabeledItem::from("Credit".to_string(), BalanceType::Credit),
   ];
   let balance_type = match select_with_labels("Balance Type:".to_string(), &options) {
-    Err(error) => return on_error(page, error),
-    Ok(balance_type) => balance_type.clone(),
+    Err(error) => return on_error(navigation, error),
+    Ok(balance_type) => *balance_type,
   };
 
   let options = vec![
     LabeledItem::from("Liability".to_string(), AccountType::Liability),
     LabeledItem::from("Asset".to_string(), AccountType::Asset),
     LabeledItem::from("Equity".to_string(), AccountType::Equity),
+    LabeledItem::from("Revenue".to_string(), AccountType::Revenue),
+    LabeledItem::from("Expense".to_string(), AccountType::Expense),
   ];
   let account_type = match select_with_labels("Account Type:".to_string(), &options) {
-    Err(error) => return on_error(page, error),
-    Ok(account_type) => account_type.clone(),
+    Err(error) => return on_error(navigation, error),
+    Ok(account_type) => *account_type,
   };
 
   let account = Account::new(name, balance_type, account_type);
   accounts.insert(account.id.clone(), account);
-  *page = Page::SelectAccount;
+  navigation.back();
 }
 
 // Todo change $journal dep into $accounts dep
 pub fn select_account<'a>(
-  account_id: &'a mut Option<AccountId>,
   journal: Option<&'a mut Journal>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
 ) {
-  *account_id = None;
-
   enum Selection<'a> {
     Account(&'a AccountId),
     NewAccount,
@@ -68,7 +69,7 @@ pub fn select_account<'a>(
   }
 
   let journal = match journal {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(journal) => journal,
   };
 
@@ -85,76 +86,122 @@ pub fn select_account<'a>(
   let options = options;
 
   let selection = match select_with_labels("Select Account".to_string(), &options) {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(selection) => selection,
   };
 
   match *selection {
     Selection::Account(id) => {
       match accounts.get(id) {
-        None => return on_not_found(page),
-        Some(account) => {
-          *account_id = Some(account.id.clone());
-          *page = Page::ViewAccount;
-        }
-      };
+        None => on_not_found(navigation),
+        Some(account) => navigation.open_account(account.id.clone()),
+      }
     }
     Selection::NewAccount => {
-      *page = Page::NewAccount;
+      navigation.go(Page::NewAccount);
     }
     Selection::Back => {
-      *page = Page::ViewJournal;
+      navigation.back();
     }
-  };
+  }
 }
 
-pub fn view_account(account: Option<&mut Account>, page: &mut Page) {
+pub fn view_account(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  account_id: &Option<AccountId>,
+  navigation: &mut Navigation,
+) {
   enum Selection {
     Back,
     Display,
+    Chart,
+    CashFlow,
+    Reconcile,
     Delete,
   }
 
   let options = vec![
     LabeledItem::from("[Back]".to_string(), Selection::Back),
     LabeledItem::from("[Display]".to_string(), Selection::Display),
+    LabeledItem::from("[Chart]".to_string(), Selection::Chart),
+    LabeledItem::from("[Cash Flow]".to_string(), Selection::CashFlow),
+    LabeledItem::from("[Reconcile]".to_string(), Selection::Reconcile),
     LabeledItem::from("[Delete]".to_string(), Selection::Delete),
   ];
 
-  let account = match account {
-    None => return on_not_found(page),
-    Some(account) => account,
+  let (journal, account_id) = match (journal, account_id) {
+    (Some(journal), Some(account_id)) => (journal, account_id),
+    _ => return on_not_found(navigation),
+  };
+  let name = match journal.accounts.get(account_id) {
+    None => return on_not_found(navigation),
+    Some(account) => account.name.clone(),
   };
 
-  let selection = match select_with_labels(account.name.to_string(), &options) {
-    Err(error) => return on_error(page, error),
+  let selection = match select_with_labels(name, &options) {
+    Err(error) => return on_error(navigation, error),
     Ok(selection) => selection,
   };
 
   match *selection {
-    Selection::Display => println!("{:#?}", account),
-    Selection::Back => *page = Page::SelectAccount,
-    Selection::Delete => *page = Page::DeleteAccount,
+    Selection::Display => println!("{}", account_details(journal, currencies, account_id)),
+    Selection::Chart => navigation.go(Page::AccountChart),
+    Selection::CashFlow => match journal.accounts.get_mut(account_id) {
+      None => on_not_found(navigation),
+      Some(account) => edit_cash_flow(account, navigation),
+    },
+    Selection::Reconcile => navigation.go(Page::Reconcile),
+    Selection::Back => navigation.back(),
+    Selection::Delete => navigation.go(Page::DeleteAccount),
   }
 }
 
+/// Marks the account as cash and picks the cash flow statement section its
+/// movements against cash are reported in.
+fn edit_cash_flow(account: &mut Account, navigation: &mut Navigation) {
+  let is_cash = match Confirm::with_theme(&ColorfulTheme::default())
+    .with_prompt("Cash or cash equivalent?")
+    .default(account.is_cash)
+    .interact()
+  {
+    Err(error) => return on_error(navigation, error),
+    Ok(is_cash) => is_cash,
+  };
+
+  let default = CashFlowActivity::default_for(account.account_type);
+  let options = vec![
+    LabeledItem::from(format!("Default ({})", default.as_str()), None),
+    LabeledItem::from("Operating".to_string(), Some(CashFlowActivity::Operating)),
+    LabeledItem::from("Investing".to_string(), Some(CashFlowActivity::Investing)),
+    LabeledItem::from("Financing".to_string(), Some(CashFlowActivity::Financing)),
+  ];
+  let activity = match select_with_labels("Cash Flow Activity".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(activity) => *activity,
+  };
+
+  account.is_cash = is_cash;
+  account.activity = activity;
+}
+
 pub fn delete_account<'a>(
   accounts: Option<&'a mut HashMap<AccountId, Account>>,
   account_id: &Option<AccountId>,
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
 ) {
   let accounts = match accounts {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(accounts) => accounts,
   };
 
   let account_id = match account_id {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(id) => id,
   };
 
   let name = match accounts.get(account_id) {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(account) => account.name.clone(),
   };
 
@@ -163,15 +210,15 @@ pub fn delete_account<'a>(
     .default(false)
     .interact()
   {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(should_delete) => should_delete,
   };
 
   match should_delete {
-    false => *page = Page::ViewAccount,
+    false => navigation.back(),
     true => {
       accounts.remove(account_id);
-      *page = Page::SelectAccount
+      navigation.back_to(Page::SelectAccount)
     }
   }
 }
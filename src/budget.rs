@@ -0,0 +1,435 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+use chrono::{Datelike, NaiveDate};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  report::{account_label, currency_label, Cell, ReportOptions, Table},
+};
+
+pub type BudgetId = String;
+
+/// Debit positive posting totals per account, currency and month.
+pub type MonthlyTotals = HashMap<(AccountId, CurrencyId, Month), f64>;
+
+/// A calendar month, the period budgets are planned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Month {
+  pub year: i32,
+  pub month: u32,
+}
+
+impl Month {
+  pub fn of(date: NaiveDate) -> Month {
+    Month { year: date.year(), month: date.month() }
+  }
+
+  pub fn first_day(self) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(self.year, self.month, 1)
+  }
+
+  pub fn next(self) -> Month {
+    match self.month {
+      12 => Month { year: self.year + 1, month: 1 },
+      month => Month { year: self.year, month: month + 1 },
+    }
+  }
+}
+
+impl FromStr for Month {
+  type Err = ();
+
+  /// Parses `YYYY-MM`.
+  fn from_str(text: &str) -> Result<Month, ()> {
+    let (year, month) = text.trim().split_once('-').ok_or(())?;
+    let month = Month {
+      year: year.parse().map_err(|_| ())?,
+      month: month.parse().map_err(|_| ())?,
+    };
+    month.first_day().map(|_| month).ok_or(())
+  }
+}
+
+impl fmt::Display for Month {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}-{:02}", self.year, self.month)
+  }
+}
+
+/// What happens to the difference between planned and actual at the end of
+/// a month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Rollover {
+  /// Every month starts fresh.
+  #[default]
+  None,
+  /// Unspent amounts carry into the next month, overspending does not.
+  Unspent,
+  /// Unspent amounts and overspending both carry into the next month.
+  Balance,
+}
+
+impl Rollover {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Rollover::None => "No Rollover",
+      Rollover::Unspent => "Roll Over Unspent",
+      Rollover::Balance => "Roll Over Balance",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthAmount {
+  pub month: Month,
+  pub amount: f64,
+}
+
+/// Planned monthly amounts for an expense or revenue account and its
+/// sub-accounts. The monthly amount recurs from `start` until `end`, with
+/// individual months overridden by `overrides`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+  pub id: BudgetId,
+  pub account_id: AccountId,
+  pub currency_id: CurrencyId,
+  pub monthly_amount: f64,
+  pub start: Month,
+  pub end: Option<Month>,
+  pub overrides: Vec<MonthAmount>,
+  pub rollover: Rollover,
+}
+
+/// A budget's figures for one month, all positive on the account's normal
+/// balance.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BudgetStatus {
+  pub planned: f64,
+  /// Carried over from earlier months.
+  pub rollover: f64,
+  pub actual: f64,
+}
+
+impl BudgetStatus {
+  pub fn available(&self) -> f64 {
+    self.planned + self.rollover
+  }
+
+  /// Positive while under budget.
+  pub fn variance(&self) -> f64 {
+    self.available() - self.actual
+  }
+
+  pub fn percent_used(&self) -> Option<f64> {
+    match self.available().abs() < 0.005 {
+      true => None,
+      false => Some(self.actual / self.available() * 100.0),
+    }
+  }
+}
+
+impl Budget {
+  pub fn new(
+    account_id: AccountId,
+    currency_id: CurrencyId,
+    monthly_amount: f64,
+    start: Month,
+  ) -> Budget {
+    Budget {
+      id: Uuid::new_v4().to_string(),
+      account_id,
+      currency_id,
+      monthly_amount,
+      start,
+      end: None,
+      overrides: Vec::new(),
+      rollover: Rollover::None,
+    }
+  }
+
+  pub fn is_active(&self, month: Month) -> bool {
+    month >= self.start && self.end.is_none_or(|end| month <= end)
+  }
+
+  /// Amount planned for `month` before any rollover.
+  pub fn planned(&self, month: Month) -> f64 {
+    if !self.is_active(month) {
+      return 0.0;
+    }
+    self.overrides
+      .iter()
+      .find(|o| o.month == month)
+      .map(|o| o.amount)
+      .unwrap_or(self.monthly_amount)
+  }
+
+  pub fn set_override(&mut self, month: Month, amount: f64) {
+    self.overrides.retain(|o| o.month != month);
+    self.overrides.push(MonthAmount { month, amount });
+    self.overrides.sort_by_key(|o| o.month);
+  }
+
+  /// Figures for `month`, carrying differences forward from `start`
+  /// according to the rollover option. `totals` comes from
+  /// `monthly_totals` and can be shared between budgets.
+  pub fn status(&self, journal: &Journal, totals: &MonthlyTotals, month: Month) -> BudgetStatus {
+    let actuals = self.actuals(journal, totals);
+    match self.statuses(&actuals, month).pop() {
+      Some((_, status)) => status,
+      // Before the first month
+      None => BudgetStatus {
+        planned: 0.0,
+        rollover: 0.0,
+        actual: actuals.get(&month).copied().unwrap_or(0.0),
+      },
+    }
+  }
+
+  /// Figures for each month from `start` through `last`, carrying the
+  /// rollover from one month to the next.
+  fn statuses(&self, actuals: &HashMap<Month, f64>, last: Month) -> Vec<(Month, BudgetStatus)> {
+    let mut statuses = Vec::new();
+    let mut rollover = 0.0;
+    let mut current = self.start;
+    while current <= last {
+      let status = BudgetStatus {
+        planned: self.planned(current),
+        rollover: match self.is_active(current) {
+          true => rollover,
+          false => 0.0,
+        },
+        actual: actuals.get(&current).copied().unwrap_or(0.0),
+      };
+      let leftover = rollover + status.planned - status.actual;
+      rollover = match self.rollover {
+        Rollover::None => 0.0,
+        Rollover::Unspent => leftover.max(0.0),
+        Rollover::Balance => leftover,
+      };
+      statuses.push((current, status));
+      current = current.next();
+    }
+    statuses
+  }
+
+  /// Actuals per month in the budget's currency from the journal's
+  /// `monthly_totals`, positive on the account's normal balance.
+  fn actuals(&self, journal: &Journal, totals: &MonthlyTotals) -> HashMap<Month, f64> {
+    let balance_type = journal.accounts
+      .get(&self.account_id)
+      .map(|a| a.balance_type)
+      .unwrap_or(BalanceType::Debit);
+    let sign = match balance_type {
+      BalanceType::Debit => 1.0,
+      BalanceType::Credit => -1.0,
+    };
+    let mut actuals: HashMap<Month, f64> = HashMap::new();
+    for ((account_id, currency_id, month), total) in totals {
+      if currency_id == &self.currency_id && journal.is_within(account_id, &self.account_id) {
+        *actuals.entry(*month).or_insert(0.0) += sign * total;
+      }
+    }
+    actuals
+  }
+}
+
+/// Debit positive totals of the dated postings per account, currency and
+/// month, gathered in one pass over the journal.
+pub fn monthly_totals(journal: &Journal) -> MonthlyTotals {
+  let mut totals = HashMap::new();
+  for posting in journal.postings() {
+    if let Some(date) = posting.date {
+      let change = posting.change;
+      let key = (change.account_id.clone(), change.currency_id.clone(), Month::of(date));
+      *totals.entry(key).or_insert(0.0) += change.signed_amount(BalanceType::Debit);
+    }
+  }
+  totals
+}
+
+/// Expense budgets that `changes`, just posted on `date`, took over budget
+/// for the month. Budgets that were already over are left out.
+pub fn exceeded_budgets<'a>(
+  journal: &'a Journal,
+  changes: &[&AccountChange],
+  date: NaiveDate,
+) -> Vec<(&'a Budget, BudgetStatus)> {
+  let month = Month::of(date);
+  let totals = monthly_totals(journal);
+  let mut budgets = journal.budgets
+    .values()
+    .filter(|b| b.is_active(month))
+    .filter_map(|b| {
+      let account = journal.accounts.get(&b.account_id).filter(|a| a.account_type == AccountType::Expense)?;
+      let posted = changes
+        .iter()
+        .filter(|c| c.currency_id == b.currency_id && journal.is_within(&c.account_id, &b.account_id))
+        .map(|c| c.signed_amount(account.balance_type))
+        .fold(None, |total: Option<f64>, amount| Some(total.unwrap_or(0.0) + amount))?;
+      let after = b.status(journal, &totals, month);
+      let before = BudgetStatus { actual: after.actual - posted, ..after };
+      match before.variance() >= -0.005 && after.variance() < -0.005 {
+        true => Some((b, after)),
+        false => None,
+      }
+    })
+    .collect::<Vec<_>>();
+  budgets.sort_by_key(|(b, _)| journal.account_full_name(&b.account_id));
+  budgets
+}
+
+/// Planned against actual amounts for every budget and month in the range.
+/// Open ends of the range default to the budget's first month and the month
+/// of the last dated posting.
+pub fn budget_report(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - Budget vs Actual ({})", journal.name, options.range.describe()),
+    &["Account", "Month", "Currency", "Planned", "Rollover", "Actual", "Variance", "Used"],
+  );
+
+  let last_posting = journal.postings().iter().filter_map(|p| p.date).max().map(Month::of);
+  let mut budgets = journal.budgets.values().collect::<Vec<_>>();
+  budgets.sort_by_cached_key(|b| (
+    account_label(journal, &b.account_id),
+    currency_label(currencies, &b.currency_id),
+  ));
+
+  let totals = monthly_totals(journal);
+  for budget in budgets {
+    let first = options.range.start.map(Month::of).unwrap_or(budget.start).max(budget.start);
+    let last = options.range.end
+      .map(Month::of)
+      .or(last_posting)
+      .unwrap_or(budget.start);
+    let last = budget.end.map_or(last, |end| last.min(end));
+
+    let actuals = budget.actuals(journal, &totals);
+    for (month, status) in budget.statuses(&actuals, last).into_iter().filter(|(m, _)| *m >= first) {
+      let currency_id = &budget.currency_id;
+      table.rows.push(vec![
+        Cell::Text(account_label(journal, &budget.account_id)),
+        Cell::Text(month.to_string()),
+        Cell::Text(currency_label(currencies, currency_id)),
+        Cell::Amount(status.planned, currency_id.clone()),
+        Cell::Amount(status.rollover, currency_id.clone()),
+        Cell::Amount(status.actual, currency_id.clone()),
+        Cell::Amount(status.variance(), currency_id.clone()),
+        match status.percent_used() {
+          None => Cell::Empty,
+          Some(percent) => Cell::Text(format!("{:.1}%", percent)),
+        },
+      ]);
+    }
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::transaction::Transaction;
+
+  const USD: &str = "usd";
+
+  /// A journal spending `amounts` on food from checking, one posting per
+  /// (month, amount) in 2024.
+  fn journal(amounts: &[(u32, f64)]) -> (Journal, AccountId) {
+    let mut journal = Journal::new("Test".to_string());
+    let food = journal.find_or_create_account("Food", AccountType::Expense);
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    for (month, amount) in amounts {
+      let date = NaiveDate::from_ymd_opt(2024, *month, 10).unwrap();
+      add(&mut journal, &food, &checking, date, *amount);
+    }
+    (journal, food)
+  }
+
+  fn add(
+    journal: &mut Journal,
+    food: &AccountId,
+    checking: &AccountId,
+    date: NaiveDate,
+    amount: f64,
+  ) -> Vec<AccountChange> {
+    let mut transaction = Transaction::new(Transaction::format_date(date), "Food".to_string(), String::new());
+    let changes = vec![
+      AccountChange::new(food.clone(), USD.to_string(), BalanceType::Debit, amount),
+      AccountChange::new(checking.clone(), USD.to_string(), BalanceType::Credit, amount),
+    ];
+    for change in &changes {
+      transaction.add_account_change(change.clone());
+    }
+    journal.transactions.insert(transaction.id.clone(), transaction);
+    changes
+  }
+
+  fn month(month: u32) -> Month {
+    Month { year: 2024, month }
+  }
+
+  /// Rollover carried into and variance of each of the first three months.
+  fn carried(rollover: Rollover) -> Vec<(f64, f64)> {
+    let (journal, food) = journal(&[(1, 60.0), (2, 150.0), (3, 50.0)]);
+    let budget = Budget { rollover, ..Budget::new(food, USD.to_string(), 100.0, month(1)) };
+    let totals = monthly_totals(&journal);
+    (1..=3)
+      .map(|m| budget.status(&journal, &totals, month(m)))
+      .map(|status| (status.rollover, status.variance()))
+      .collect()
+  }
+
+  #[test]
+  fn no_rollover_starts_each_month_fresh() {
+    assert_eq!(carried(Rollover::None), vec![(0.0, 40.0), (0.0, -50.0), (0.0, 50.0)]);
+  }
+
+  #[test]
+  fn unspent_rollover_carries_only_savings() {
+    assert_eq!(carried(Rollover::Unspent), vec![(0.0, 40.0), (40.0, -10.0), (0.0, 50.0)]);
+  }
+
+  #[test]
+  fn balance_rollover_carries_overspending() {
+    assert_eq!(carried(Rollover::Balance), vec![(0.0, 40.0), (40.0, -10.0), (-10.0, 40.0)]);
+  }
+
+  #[test]
+  fn rollover_stops_after_the_last_month() {
+    let (journal, food) = journal(&[(1, 60.0), (2, 30.0)]);
+    let budget = Budget {
+      rollover: Rollover::Balance,
+      end: Some(month(1)),
+      ..Budget::new(food, USD.to_string(), 100.0, month(1))
+    };
+    let status = budget.status(&journal, &monthly_totals(&journal), month(2));
+    assert_eq!((status.planned, status.rollover, status.actual), (0.0, 0.0, 30.0));
+  }
+
+  #[test]
+  fn exceeded_budgets_only_reports_crossing_the_limit() {
+    let (mut journal, food) = journal(&[(1, 80.0)]);
+    let checking = journal.find_account("Checking").unwrap().clone();
+    let budget = Budget::new(food.clone(), USD.to_string(), 100.0, month(1));
+    journal.budgets.insert(budget.id.clone(), budget);
+    let date = NaiveDate::from_ymd_opt(2024, 1, 20).unwrap();
+
+    let within = add(&mut journal, &food, &checking, date, 15.0);
+    assert!(exceeded_budgets(&journal, &within.iter().collect::<Vec<_>>(), date).is_empty());
+
+    let over = add(&mut journal, &food, &checking, date, 10.0);
+    let exceeded = exceeded_budgets(&journal, &over.iter().collect::<Vec<_>>(), date);
+    assert_eq!(exceeded.len(), 1);
+    assert!((exceeded[0].1.variance() + 5.0).abs() < 0.001);
+
+    // Already over, so a further posting is not reported again
+    let again = add(&mut journal, &food, &checking, date, 10.0);
+    assert!(exceeded_budgets(&journal, &again.iter().collect::<Vec<_>>(), date).is_empty());
+  }
+}
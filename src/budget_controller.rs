@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use chrono::{NaiveDate, Utc};
+use crate::{
+  account::AccountType,
+  account_change::AccountChange,
+  budget::{exceeded_budgets, monthly_totals, Budget, BudgetId, Month, Rollover},
+  currency::{format_amount, Currency, CurrencyId},
+  journal::Journal,
+  page::Navigation,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_input_with_labels,
+    input_currency,
+    input_optional_parsed,
+    input_until_parsed,
+    select_with_labels,
+  },
+};
+
+pub fn view_budgets(
+  currencies: &HashMap<CurrencyId, Currency>,
+  currency_id: &CurrencyId,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Selection {
+    Budget(BudgetId),
+    NewBudget,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = journal.budgets
+    .values()
+    .map(|b| LabeledItem::from(budget_label(journal, currencies, b), Selection::Budget(b.id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.extend(vec![
+    LabeledItem::from("[New Budget]".to_string(), Selection::NewBudget),
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+  ]);
+
+  let selection = match select_with_labels("Select Budget".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let result = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::NewBudget => new_budget(currencies, currency_id, journal),
+    Selection::Budget(id) => edit_budget(currencies, journal, id),
+  };
+  if let Err(error) = result {
+    on_error(navigation, error)
+  }
+}
+
+fn new_budget(
+  currencies: &HashMap<CurrencyId, Currency>,
+  currency_id: &CurrencyId,
+  journal: &mut Journal,
+) -> Result<(), std::io::Error> {
+  let mut options = journal.accounts
+    .values()
+    .filter(|a| matches!(a.account_type, AccountType::Expense | AccountType::Revenue))
+    .map(|a| LabeledItem::from(journal.account_full_name(&a.id).unwrap_or_default(), a.id.clone()))
+    .collect::<Vec<_>>();
+  if options.is_empty() {
+    println!("Budgets need an expense or revenue account");
+    return Ok(());
+  }
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  let account_id = fuzzy_input_with_labels("Account:".to_string(), &options)?.clone();
+  let currency_id = input_currency(currencies, currency_id)?;
+
+  let amount = input_until_parsed("Monthly Amount:".to_string(), String::new())?;
+  let start = input_until_parsed(
+    "First Month (YYYY-MM):".to_string(),
+    Month::of(Utc::now().date_naive()).to_string()
+  )?;
+
+  let mut budget = Budget::new(account_id, currency_id, amount, start);
+  budget.rollover = select_rollover(budget.rollover)?;
+  journal.budgets.insert(budget.id.clone(), budget);
+  Ok(())
+}
+
+fn edit_budget(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &mut Journal,
+  budget_id: &BudgetId,
+) -> Result<(), std::io::Error> {
+  enum Selection {
+    Back,
+    Status,
+    MonthlyAmount,
+    MonthAmount,
+    Rollover,
+    End,
+    Delete,
+  }
+
+  let label = match journal.budgets.get(budget_id) {
+    None => return Ok(()),
+    Some(budget) => budget_label(journal, currencies, budget),
+  };
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+    LabeledItem::from("[This Month]".to_string(), Selection::Status),
+    LabeledItem::from("[Monthly Amount]".to_string(), Selection::MonthlyAmount),
+    LabeledItem::from("[Amount for a Month]".to_string(), Selection::MonthAmount),
+    LabeledItem::from("[Rollover]".to_string(), Selection::Rollover),
+    LabeledItem::from("[Last Month]".to_string(), Selection::End),
+    LabeledItem::from("[Delete]".to_string(), Selection::Delete),
+  ];
+  let selection = select_with_labels(label.clone(), &options)?;
+
+  if let Selection::Status = selection {
+    if let Some(budget) = journal.budgets.get(budget_id) {
+      let totals = monthly_totals(journal);
+      let status = budget.status(journal, &totals, Month::of(Utc::now().date_naive()));
+      let format = |amount: f64| format_amount(currencies, &budget.currency_id, amount);
+      println!(
+        "Planned {}, Rollover {}, Actual {}, Remaining {}",
+        format(status.planned),
+        format(status.rollover),
+        format(status.actual),
+        format(status.variance()),
+      );
+    }
+    return Ok(());
+  }
+  if let Selection::Delete = selection {
+    if confirm_default(format!("Are you sure you want to delete \"{}\"?", label))? {
+      journal.budgets.remove(budget_id);
+    }
+    return Ok(());
+  }
+
+  let budget = match journal.budgets.get_mut(budget_id) {
+    None => return Ok(()),
+    Some(budget) => budget,
+  };
+  match selection {
+    Selection::Back | Selection::Status | Selection::Delete => {},
+    Selection::MonthlyAmount => {
+      budget.monthly_amount = input_until_parsed(
+        "Monthly Amount:".to_string(),
+        budget.monthly_amount.to_string()
+      )?;
+    },
+    Selection::MonthAmount => {
+      let month: Month = input_until_parsed("Month (YYYY-MM):".to_string(), budget.start.to_string())?;
+      let amount = input_until_parsed("Amount:".to_string(), budget.planned(month).to_string())?;
+      budget.set_override(month, amount);
+    },
+    Selection::Rollover => budget.rollover = select_rollover(budget.rollover)?,
+    Selection::End => budget.end = input_optional_parsed::<Month>(
+      "Last Month (YYYY-MM, empty for none):".to_string(),
+      budget.end
+    )?,
+  }
+  Ok(())
+}
+
+fn select_rollover(current: Rollover) -> Result<Rollover, std::io::Error> {
+  let mut options = [Rollover::None, Rollover::Unspent, Rollover::Balance]
+    .into_iter()
+    .map(|rollover| LabeledItem::from(rollover.as_str().to_string(), rollover))
+    .collect::<Vec<_>>();
+  options.sort_by_key(|o| o.item != current);
+  Ok(*select_with_labels("Rollover".to_string(), &options)?)
+}
+
+fn budget_label(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  budget: &Budget,
+) -> String {
+  let amount = format_amount(currencies, &budget.currency_id, budget.monthly_amount);
+  format!(
+    "{} {}/month from {}",
+    journal.account_full_name(&budget.account_id).unwrap_or_default(),
+    amount,
+    budget.start,
+  )
+}
+
+/// Prints a warning for each budget that `changes`, just posted on `date`,
+/// took over budget.
+pub fn warn_over_budget(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &Journal,
+  changes: &[&AccountChange],
+  date: NaiveDate,
+) {
+  for (budget, status) in exceeded_budgets(journal, changes, date) {
+    let format = |amount: f64| format_amount(currencies, &budget.currency_id, amount);
+    println!(
+      "Warning: {} is over budget for {} by {} ({} of {})",
+      journal.account_full_name(&budget.account_id).unwrap_or_default(),
+      Month::of(date),
+      format(-status.variance()),
+      format(status.actual),
+      format(status.available()),
+    );
+  }
+}
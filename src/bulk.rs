@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use crate::{
+  account::AccountId,
+  currency::{Currency, CurrencyId},
+  delimited::{to_delimited, Delimiter},
+  journal::Journal,
+  report::{ReportKind, ReportOptions},
+  transaction::{Transaction, TransactionId},
+};
+
+/// Points the postings of `from` in the selected transactions at `to`,
+/// returning how many were moved.
+pub fn move_postings(
+  journal: &mut Journal,
+  transaction_ids: &[TransactionId],
+  from: &AccountId,
+  to: &AccountId,
+) -> usize {
+  let mut moved = 0;
+  for id in transaction_ids {
+    let transaction = match journal.transactions.get_mut(id) {
+      None => continue,
+      Some(transaction) => transaction,
+    };
+    for change in transaction.account_changes.values_mut().filter(|c| &c.account_id == from) {
+      change.account_id = to.clone();
+      moved += 1;
+    }
+  }
+  moved
+}
+
+pub fn delete(journal: &mut Journal, transaction_ids: &[TransactionId]) -> usize {
+  transaction_ids
+    .iter()
+    .filter(|id| journal.transactions.remove(*id).is_some())
+    .count()
+}
+
+pub fn tag(journal: &mut Journal, transaction_ids: &[TransactionId], tag: &str) {
+  for id in transaction_ids {
+    if let Some(transaction) = journal.transactions.get_mut(id) {
+      transaction.add_tag(tag);
+    }
+  }
+}
+
+pub fn untag(journal: &mut Journal, transaction_ids: &[TransactionId], tag: &str) {
+  for id in transaction_ids {
+    if let Some(transaction) = journal.transactions.get_mut(id) {
+      transaction.tags.retain(|t| t != tag);
+    }
+  }
+}
+
+pub fn set_date(journal: &mut Journal, transaction_ids: &[TransactionId], date: NaiveDate) {
+  for id in transaction_ids {
+    if let Some(transaction) = journal.transactions.get_mut(id) {
+      transaction.date = Transaction::format_date(date);
+    }
+  }
+}
+
+/// Adds a copy of each selected transaction, dated `date` or its original
+/// date, returning the ids of the copies.
+pub fn duplicate(
+  journal: &mut Journal,
+  transaction_ids: &[TransactionId],
+  date: Option<NaiveDate>,
+) -> Vec<TransactionId> {
+  let copies = transaction_ids
+    .iter()
+    .filter_map(|id| journal.transactions.get(id))
+    .map(|t| t.duplicate(date))
+    .collect::<Vec<_>>();
+  copies
+    .into_iter()
+    .map(|copy| {
+      let id = copy.id.clone();
+      journal.transactions.insert(id.clone(), copy);
+      id
+    })
+    .collect()
+}
+
+/// The selected transactions as a delimited transactions report.
+pub fn export(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  transaction_ids: &[TransactionId],
+  delimiter: Delimiter,
+) -> String {
+  let mut selected = journal.clone();
+  selected.transactions.retain(|id, _| transaction_ids.contains(id));
+  let table = ReportKind::Transactions.build(&selected, currencies, &ReportOptions::default());
+  to_delimited(&table, currencies, delimiter)
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, fs};
+use chrono::{NaiveDate, Utc};
+use crate::{
+  bulk,
+  currency::{Currency, CurrencyId},
+  delimited::Delimiter,
+  journal::Journal,
+  page::Navigation,
+  reconcile_controller::confirm_reconciled,
+  report::{account_label, date_label},
+  transaction::TransactionId,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_input_with_labels,
+    input_default,
+    input_optional_parsed,
+    input_until_parsed,
+    multi_select_with_labels,
+    select_with_labels,
+  },
+};
+
+/// Transactions shown at a time while marking.
+const PAGE_SIZE: usize = 15;
+
+/// Marks transactions from the filtered list and applies one action to all
+/// of them.
+pub fn bulk_transactions(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let filter = navigation.context().filter;
+  let options = filter.apply(journal, currencies)
+    .into_iter()
+    .map(|t| LabeledItem::from(
+      format!("{}: {}", date_label(t.parsed_date(), &t.date), t.name),
+      t.id.clone()
+    ))
+    .collect::<Vec<_>>();
+  if options.is_empty() {
+    println!("No transactions to select");
+    return navigation.back();
+  }
+
+  let prompt = "Mark Transactions (space to mark, enter when done)".to_string();
+  let transaction_ids = match multi_select_with_labels(prompt, &options, PAGE_SIZE) {
+    Err(error) => return on_error(navigation, error),
+    Ok(ids) => ids.into_iter().cloned().collect::<Vec<_>>(),
+  };
+  if transaction_ids.is_empty() {
+    return navigation.back();
+  }
+
+  match apply_action(currencies, journal, &transaction_ids) {
+    Err(error) => on_error(navigation, error),
+    Ok(()) => navigation.back(),
+  }
+}
+
+fn apply_action(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &mut Journal,
+  transaction_ids: &[TransactionId],
+) -> Result<(), std::io::Error> {
+  enum Selection {
+    Move,
+    Delete,
+    Tag,
+    Untag,
+    Date,
+    Duplicate,
+    Export,
+    Cancel,
+  }
+
+  let options = vec![
+    LabeledItem::from("[Move Postings]".to_string(), Selection::Move),
+    LabeledItem::from("[Add Tag]".to_string(), Selection::Tag),
+    LabeledItem::from("[Remove Tag]".to_string(), Selection::Untag),
+    LabeledItem::from("[Change Date]".to_string(), Selection::Date),
+    LabeledItem::from("[Duplicate]".to_string(), Selection::Duplicate),
+    LabeledItem::from("[Export]".to_string(), Selection::Export),
+    LabeledItem::from("[Delete]".to_string(), Selection::Delete),
+    LabeledItem::from("[Cancel]".to_string(), Selection::Cancel),
+  ];
+  let prompt = format!("{} Transactions", transaction_ids.len());
+
+  match select_with_labels(prompt, &options)? {
+    Selection::Cancel => {},
+    Selection::Move => {
+      let mut used = transaction_ids
+        .iter()
+        .filter_map(|id| journal.transactions.get(id))
+        .flat_map(|t| t.account_changes.values().map(|c| c.account_id.clone()))
+        .collect::<Vec<_>>();
+      used.sort();
+      used.dedup();
+      let mut from_options = used
+        .into_iter()
+        .map(|id| LabeledItem::from(account_label(journal, &id), id))
+        .collect::<Vec<_>>();
+      from_options.sort_by(|a, b| a.label.cmp(&b.label));
+      let from = fuzzy_input_with_labels("Move Postings From:".to_string(), &from_options)?.clone();
+
+      let mut to_options = journal.accounts
+        .keys()
+        .filter(|id| **id != from)
+        .map(|id| LabeledItem::from(account_label(journal, id), id.clone()))
+        .collect::<Vec<_>>();
+      to_options.sort_by(|a, b| a.label.cmp(&b.label));
+      if to_options.is_empty() {
+        return Ok(());
+      }
+      let to = fuzzy_input_with_labels("To:".to_string(), &to_options)?.clone();
+
+      if !confirm_reconciled(journal.count_reconciled(transaction_ids, Some(&from)), "Move")? {
+        return Ok(());
+      }
+      let moved = bulk::move_postings(journal, transaction_ids, &from, &to);
+      println!("Moved {} postings", moved);
+    },
+    Selection::Tag => {
+      let tag = input_default("Tag:".to_string(), String::new())?;
+      let tag = tag.trim();
+      if tag.is_empty() {
+        println!("Tags cannot be empty");
+        return Ok(());
+      }
+      bulk::tag(journal, transaction_ids, tag);
+    },
+    Selection::Untag => {
+      let mut tags = transaction_ids
+        .iter()
+        .filter_map(|id| journal.transactions.get(id))
+        .flat_map(|t| t.tags.iter().cloned())
+        .collect::<Vec<_>>();
+      tags.sort();
+      tags.dedup();
+      if tags.is_empty() {
+        println!("The marked transactions have no tags");
+        return Ok(());
+      }
+      let options = tags
+        .into_iter()
+        .map(|tag| LabeledItem::from(tag.clone(), tag))
+        .collect::<Vec<_>>();
+      let tag = select_with_labels("Remove Tag".to_string(), &options)?;
+      bulk::untag(journal, transaction_ids, tag);
+    },
+    Selection::Date => {
+      let date: NaiveDate = input_until_parsed(
+        "New Date (YYYY-MM-DD):".to_string(),
+        Utc::now().date_naive().to_string()
+      )?;
+      if !confirm_reconciled(journal.count_reconciled(transaction_ids, None), "Redate")? {
+        return Ok(());
+      }
+      bulk::set_date(journal, transaction_ids, date);
+    },
+    Selection::Duplicate => {
+      let date = input_optional_parsed::<NaiveDate>(
+        "Date of the Copies (YYYY-MM-DD, empty to keep):".to_string(),
+        None
+      )?;
+      let copies = bulk::duplicate(journal, transaction_ids, date);
+      println!("Added {} transactions", copies.len());
+    },
+    Selection::Export => {
+      let options = vec![
+        LabeledItem::from("CSV".to_string(), (Delimiter::Comma, "csv")),
+        LabeledItem::from("TSV".to_string(), (Delimiter::Tab, "tsv")),
+      ];
+      let (delimiter, extension) = *select_with_labels("Format".to_string(), &options)?;
+      let path = input_default("File Path:".to_string(), format!("transactions.{}", extension))?;
+      fs::write(&path, bulk::export(journal, currencies, transaction_ids, delimiter))?;
+      println!("Exported {} transactions to {}", transaction_ids.len(), path);
+    },
+    Selection::Delete => {
+      let prompt = format!("Are you sure you want to delete {} transactions?", transaction_ids.len());
+      if confirm_default(prompt)? && confirm_reconciled(journal.count_reconciled(transaction_ids, None), "Delete")? {
+        let deleted = bulk::delete(journal, transaction_ids);
+        println!("Deleted {} transactions", deleted);
+      }
+    },
+  }
+  Ok(())
+}
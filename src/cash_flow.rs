@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::{
+  account::{AccountId, BalanceType, CashFlowActivity},
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  report::{
+    account_label,
+    account_totals,
+    currency_label,
+    total_rows,
+    Cell,
+    DateRange,
+    ReportOptions,
+    Table,
+  },
+};
+
+/// Movement in cash accounts within the range, grouped into operating,
+/// investing and financing activities by the accounts on the other side of
+/// each transaction. Transfers between cash accounts cancel out and are left
+/// out.
+pub fn cash_flow(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let range = options.range;
+  let mut table = Table::new(
+    format!("{} - Cash Flow Statement ({})", journal.name, range.describe()),
+    &["Section", "Account", "Currency", "Amount"],
+  );
+  let is_cash = |account_id: &AccountId| journal.accounts
+    .get(account_id)
+    .is_some_and(|a| a.is_cash);
+
+  // Cash inflow per counter account and currency, positive when cash grows
+  let mut flows: HashMap<(AccountId, CurrencyId), f64> = HashMap::new();
+  for transaction in journal.transactions.values() {
+    if !range.contains(transaction.parsed_date()) {
+      continue;
+    }
+    let changes = transaction.account_changes.values();
+    if !changes.clone().any(|c| is_cash(&c.account_id)) {
+      continue;
+    }
+    for change in changes.filter(|c| !is_cash(&c.account_id)) {
+      *flows
+        .entry((change.account_id.clone(), change.currency_id.clone()))
+        .or_insert(0.0) -= change.signed_amount(BalanceType::Debit);
+    }
+  }
+
+  let mut flows = flows.into_iter().collect::<Vec<_>>();
+  flows.sort_by_cached_key(|((account_id, currency_id), _)| (
+    account_label(journal, account_id),
+    currency_label(currencies, currency_id),
+  ));
+
+  let mut net_change: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for activity in [
+    CashFlowActivity::Operating,
+    CashFlowActivity::Investing,
+    CashFlowActivity::Financing,
+  ] {
+    let section = format!("{} Activities", activity.as_str());
+    let mut section_totals: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+    for ((account_id, currency_id), amount) in &flows {
+      let account_activity = journal.accounts.get(account_id).map(|a| a.cash_flow_activity());
+      if account_activity != Some(activity) || amount.abs() < 0.005 {
+        continue;
+      }
+      let label = currency_label(currencies, currency_id);
+      table.rows.push(vec![
+        Cell::Text(section.clone()),
+        Cell::Text(account_label(journal, account_id)),
+        Cell::Text(label.clone()),
+        Cell::Amount(*amount, currency_id.clone()),
+      ]);
+      section_totals.entry(label).or_insert((currency_id.clone(), 0.0)).1 += amount;
+    }
+    total_rows(&mut table, &format!("Net Cash from {}", section), &section_totals);
+    for (label, (currency_id, amount)) in section_totals {
+      net_change.entry(label).or_insert((currency_id, 0.0)).1 += amount;
+    }
+  }
+  total_rows(&mut table, "Net Change in Cash", &net_change);
+
+  let mut opening: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  if let Some(start) = range.start.and_then(|start| start.pred_opt()) {
+    for ((account_id, currency_id), total) in account_totals(journal, DateRange::new(None, Some(start))) {
+      if is_cash(&account_id) {
+        opening
+          .entry(currency_label(currencies, &currency_id))
+          .or_insert((currency_id, 0.0))
+          .1 += total;
+      }
+    }
+  }
+  let mut closing = opening.clone();
+  for (label, (currency_id, amount)) in &net_change {
+    closing.entry(label.clone()).or_insert((currency_id.clone(), 0.0)).1 += amount;
+  }
+  total_rows(&mut table, "Opening Cash", &opening);
+  total_rows(&mut table, "Closing Cash", &closing);
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+  use crate::{
+    account::AccountType,
+    account_change::AccountChange,
+    transaction::Transaction,
+  };
+
+  fn journal() -> Journal {
+    let mut journal = Journal::new("Home".to_string());
+    let mut account = |name: &str, account_type, activity| {
+      let id = journal.find_or_create_account(name, account_type);
+      let account = journal.accounts.get_mut(&id).unwrap();
+      account.is_cash = name == "Cash" || name == "Savings";
+      account.activity = activity;
+      id
+    };
+    let cash = account("Cash", AccountType::Asset, None);
+    let savings = account("Savings", AccountType::Asset, None);
+    let salary = account("Salary", AccountType::Revenue, None);
+    let food = account("Food", AccountType::Expense, None);
+    let card = account("Card", AccountType::Liability, None);
+    let owner = account("Owner", AccountType::Equity, None);
+    let equipment = account("Equipment", AccountType::Asset, Some(CashFlowActivity::Investing));
+    let loan = account("Loan", AccountType::Liability, Some(CashFlowActivity::Financing));
+    let entries = [
+      ("2024/1/2", &cash, &owner, 500.0),
+      ("2024/2/1", &cash, &salary, 100.0),
+      ("2024/2/2", &food, &cash, 30.0),
+      ("2024/2/3", &equipment, &cash, 50.0),
+      ("2024/2/4", &cash, &loan, 200.0),
+      ("2024/2/5", &cash, &owner, 10.0),
+      ("2024/2/6", &card, &cash, 20.0),
+      ("2024/2/7", &savings, &cash, 40.0),
+      ("2024/3/1", &food, &cash, 99.0),
+    ];
+    for (date, debit, credit, amount) in entries {
+      let mut transaction = Transaction::new(date.to_string(), String::new(), String::new());
+      transaction.add_account_change(AccountChange::new(debit.clone(), "usd".to_string(), BalanceType::Debit, amount));
+      transaction.add_account_change(AccountChange::new(credit.clone(), "usd".to_string(), BalanceType::Credit, amount));
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+    journal
+  }
+
+  /// Section or total, account and amount of each row.
+  fn rows(table: &Table) -> Vec<(String, String, f64)> {
+    table.rows
+      .iter()
+      .map(|row| {
+        let text = |cell: &Cell| match cell {
+          Cell::Text(text) => text.clone(),
+          _ => String::new(),
+        };
+        let amount = match &row[3] {
+          Cell::Amount(amount, _) => *amount,
+          _ => 0.0,
+        };
+        (text(&row[0]), text(&row[1]), amount)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn classifies_counter_accounts_by_activity() {
+    let dollars = Currency { id: "usd".to_string(), ..Currency::new("Dollars".to_string(), "$".to_string()) };
+    let currencies = HashMap::from([(dollars.id.clone(), dollars)]);
+    let options = ReportOptions {
+      range: DateRange::new(NaiveDate::from_ymd_opt(2024, 2, 1), NaiveDate::from_ymd_opt(2024, 2, 29)),
+      ..ReportOptions::default()
+    };
+    let row = |section: &str, account: &str, amount: f64| (section.to_string(), account.to_string(), amount);
+    assert_eq!(rows(&cash_flow(&journal(), &currencies, &options)), vec![
+      // Current assets and liabilities default to operating, equity to financing
+      row("Operating Activities", "Card", -20.0),
+      row("Operating Activities", "Food", -30.0),
+      row("Operating Activities", "Salary", 100.0),
+      row("Net Cash from Operating Activities", "", 50.0),
+      row("Investing Activities", "Equipment", -50.0),
+      row("Net Cash from Investing Activities", "", -50.0),
+      row("Financing Activities", "Loan", 200.0),
+      row("Financing Activities", "Owner", 10.0),
+      row("Net Cash from Financing Activities", "", 210.0),
+      row("Net Change in Cash", "", 210.0),
+      row("Opening Cash", "", 500.0),
+      row("Closing Cash", "", 710.0),
+    ]);
+  }
+
+  #[test]
+  fn default_activities() {
+    let activity = CashFlowActivity::default_for;
+    assert_eq!(activity(AccountType::Asset), CashFlowActivity::Operating);
+    assert_eq!(activity(AccountType::Liability), CashFlowActivity::Operating);
+    assert_eq!(activity(AccountType::Revenue), CashFlowActivity::Operating);
+    assert_eq!(activity(AccountType::Expense), CashFlowActivity::Operating);
+    assert_eq!(activity(AccountType::Equity), CashFlowActivity::Financing);
+  }
+}
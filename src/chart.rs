@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  currency::CurrencyId,
+  journal::Journal,
+  periodic::{periods, Interval},
+  report::{account_label, account_totals, DateRange},
+};
+
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Horizontal bars from a shared zero line, labels on the left and
+/// formatted values on the right. Negative values are drawn shaded.
+pub fn bar_chart(bars: &[(String, f64)], width: usize, format: &dyn Fn(f64) -> String) -> String {
+  if bars.is_empty() {
+    return "No data\n".to_string();
+  }
+  let label_width = bars.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+  let largest = bars.iter().map(|(_, value)| value.abs()).fold(0.0_f64, f64::max);
+  let scale = match largest == 0.0 {
+    true => 0.0,
+    false => width as f64 / largest,
+  };
+
+  let mut output = String::new();
+  for (label, value) in bars {
+    let eighths = (value.abs() * scale * 8.0).round() as usize;
+    let bar = match *value < 0.0 {
+      true => "▒".repeat(eighths.div_ceil(8)),
+      false => {
+        let mut bar = "█".repeat(eighths / 8);
+        let remainder = eighths % 8;
+        if remainder > 0 {
+          bar.push(EIGHTHS[remainder]);
+        }
+        bar
+      },
+    };
+    output.push_str(&format!(
+      "{:<label_width$} │{:<width$} {}\n",
+      label,
+      bar,
+      format(*value),
+      label_width = label_width,
+      width = width + 1,
+    ));
+  }
+  output
+}
+
+/// Points plotted left to right on a grid `height` rows tall, joined by
+/// vertical strokes, with the range on the y axis and the first and last
+/// labels under the x axis. A dotted line marks zero when it is in range.
+/// Series with more points than `width` columns are sampled down to fit.
+pub fn line_chart(
+  points: &[(String, f64)],
+  width: usize,
+  height: usize,
+  format: &dyn Fn(f64) -> String,
+) -> String {
+  if points.is_empty() {
+    return "No data\n".to_string();
+  }
+  let sampled = sample(points, width.max(2));
+  let points = sampled.as_slice();
+  let height = height.max(2);
+  let step = (width / points.len()).clamp(1, 8);
+  let columns = step * (points.len() - 1) + 1;
+
+  let min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min).min(0.0);
+  let max = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max).max(0.0);
+  let span = if max - min == 0.0 { 1.0 } else { max - min };
+  let row_of = |value: f64| ((max - value) / span * (height - 1) as f64).round() as usize;
+
+  let mut grid = vec![vec![' '; columns]; height];
+  let zero = row_of(0.0);
+  for cell in grid[zero].iter_mut() {
+    *cell = '┈';
+  }
+  let mut previous: Option<usize> = None;
+  for (i, (_, value)) in points.iter().enumerate() {
+    let column = i * step;
+    let row = row_of(*value);
+    if let Some(previous) = previous {
+      for line in grid.iter_mut().take(previous.max(row)).skip(previous.min(row) + 1) {
+        line[column] = '│';
+      }
+      for cell in &mut grid[previous][column - step + 1..column] {
+        *cell = '─';
+      }
+      if row != previous {
+        grid[previous][column] = if row < previous { '╯' } else { '╮' };
+      }
+    }
+    grid[row][column] = '●';
+    previous = Some(row);
+  }
+
+  let axis_labels = [format(max), format(min), format(0.0)];
+  let axis_width = axis_labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+  let mut output = String::new();
+  for (i, row) in grid.iter().enumerate() {
+    let label = match i {
+      0 => axis_labels[0].clone(),
+      i if i == height - 1 => axis_labels[1].clone(),
+      i if i == zero => axis_labels[2].clone(),
+      _ => String::new(),
+    };
+    output.push_str(&format!(
+      "{:>axis_width$} ┤{}\n",
+      label,
+      row.iter().collect::<String>().trim_end(),
+      axis_width = axis_width,
+    ));
+  }
+  output.push_str(&format!("{:>axis_width$} └{}\n", "", "─".repeat(columns), axis_width = axis_width));
+  let first = &points[0].0;
+  let last = &points[points.len() - 1].0;
+  let gap = (columns + 1).saturating_sub(first.chars().count() + last.chars().count()).max(1);
+  output.push_str(&format!(
+    "{:>axis_width$}  {}{}{}\n",
+    "",
+    first,
+    " ".repeat(gap),
+    if points.len() > 1 { last.as_str() } else { "" },
+    axis_width = axis_width,
+  ));
+  output
+}
+
+/// At most `count` evenly spaced points, always keeping the first and last.
+fn sample(points: &[(String, f64)], count: usize) -> Vec<(String, f64)> {
+  match points.len() > count {
+    true => (0..count)
+      .map(|i| points[i * (points.len() - 1) / (count - 1)].clone())
+      .collect(),
+    false => points.to_vec(),
+  }
+}
+
+/// Balance of an account and its sub-accounts at the end of every period,
+/// positive on the account's balance type.
+pub fn account_balance_series(
+  journal: &Journal,
+  account_id: &AccountId,
+  currency_id: &CurrencyId,
+  interval: Interval,
+) -> Vec<(String, f64)> {
+  let sign = match journal.accounts.get(account_id).map(|a| a.balance_type) {
+    None => return Vec::new(),
+    Some(BalanceType::Debit) => 1.0,
+    Some(BalanceType::Credit) => -1.0,
+  };
+  balance_series(journal, currency_id, interval, |id| {
+    journal.is_within(id, account_id).then_some(sign)
+  })
+}
+
+/// Assets less liabilities at the end of every period.
+pub fn net_worth_series(
+  journal: &Journal,
+  currency_id: &CurrencyId,
+  interval: Interval,
+) -> Vec<(String, f64)> {
+  balance_series(journal, currency_id, interval, |id| {
+    match journal.accounts.get(id).map(|a| a.account_type) {
+      Some(AccountType::Asset) | Some(AccountType::Liability) => Some(1.0),
+      _ => None,
+    }
+  })
+}
+
+/// Expense accounts with activity in the range, largest first.
+pub fn spending_by_account(
+  journal: &Journal,
+  currency_id: &CurrencyId,
+  range: DateRange,
+) -> Vec<(String, f64)> {
+  let mut spending = account_totals(journal, range)
+    .into_iter()
+    .filter(|((account_id, currency), total)| {
+      currency == currency_id
+        && total.abs() >= 0.005
+        && journal.accounts.get(account_id).map(|a| a.account_type) == Some(AccountType::Expense)
+    })
+    .map(|((account_id, _), total)| (account_label(journal, &account_id), total))
+    .collect::<Vec<_>>();
+  spending.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+  spending
+}
+
+/// Running totals of dated postings in `currency_id` at every period end,
+/// from the first dated posting to the last. `weight` picks the accounts to
+/// include and the sign their debits count with.
+fn balance_series(
+  journal: &Journal,
+  currency_id: &CurrencyId,
+  interval: Interval,
+  weight: impl Fn(&AccountId) -> Option<f64>,
+) -> Vec<(String, f64)> {
+  let postings = journal.postings()
+    .into_iter()
+    .filter(|p| &p.change.currency_id == currency_id)
+    .filter_map(|p| Some((p.date?, weight(&p.change.account_id)?, p.change)))
+    .collect::<Vec<_>>();
+  let (Some(first), Some(last)) = (postings.first(), postings.last()) else {
+    return Vec::new();
+  };
+
+  let starts = periods(interval, first.0, last.0);
+  let mut changes: HashMap<usize, f64> = HashMap::new();
+  for (date, sign, change) in &postings {
+    let index = starts.partition_point(|start| start <= date).saturating_sub(1);
+    *changes.entry(index).or_insert(0.0) += sign * change.signed_amount(BalanceType::Debit);
+  }
+
+  let mut balance = 0.0;
+  starts
+    .iter()
+    .enumerate()
+    .map(|(i, start)| {
+      balance += changes.get(&i).copied().unwrap_or(0.0);
+      (interval.label(*start), balance)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+  use crate::{account_change::AccountChange, transaction::Transaction};
+
+  fn journal() -> Journal {
+    let mut journal = Journal::new("Home".to_string());
+    let bank = journal.find_or_create_account("Assets:Bank", AccountType::Asset);
+    let savings = journal.find_or_create_account("Assets:Bank:Savings", AccountType::Asset);
+    let card = journal.find_or_create_account("Liabilities:Card", AccountType::Liability);
+    let salary = journal.find_or_create_account("Income:Salary", AccountType::Revenue);
+    let food = journal.find_or_create_account("Expenses:Food", AccountType::Expense);
+    let rent = journal.find_or_create_account("Expenses:Rent", AccountType::Expense);
+    let entries = [
+      ("2024/1/10", &bank, &salary, 100.0, "usd"),
+      ("2024/1/20", &food, &card, 30.0, "usd"),
+      ("2024/3/5", &savings, &bank, 40.0, "usd"),
+      ("2024/3/6", &rent, &bank, 50.0, "usd"),
+      ("2024/3/7", &food, &bank, 5.0, "eur"),
+      ("someday", &bank, &salary, 1000.0, "usd"),
+    ];
+    for (date, debit, credit, amount, currency_id) in entries {
+      let mut transaction = Transaction::new(date.to_string(), String::new(), String::new());
+      transaction.add_account_change(AccountChange::new(debit.clone(), currency_id.to_string(), BalanceType::Debit, amount));
+      transaction.add_account_change(AccountChange::new(credit.clone(), currency_id.to_string(), BalanceType::Credit, amount));
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+    journal
+  }
+
+  fn series(points: &[(&str, f64)]) -> Vec<(String, f64)> {
+    points.iter().map(|(label, value)| (label.to_string(), *value)).collect()
+  }
+
+  #[test]
+  fn account_series_include_sub_accounts_in_the_balance_sign() {
+    let journal = journal();
+    let usd = "usd".to_string();
+    let bank = journal.find_account("Assets:Bank").unwrap();
+    assert_eq!(
+      account_balance_series(&journal, bank, &usd, Interval::Month),
+      series(&[("2024-01", 100.0), ("2024-02", 100.0), ("2024-03", 50.0)])
+    );
+    let card = journal.find_account("Liabilities:Card").unwrap();
+    assert_eq!(account_balance_series(&journal, card, &usd, Interval::Month), series(&[("2024-01", 30.0)]));
+    assert_eq!(account_balance_series(&journal, &"missing".to_string(), &usd, Interval::Month), vec![]);
+  }
+
+  #[test]
+  fn net_worth_subtracts_liabilities() {
+    let journal = journal();
+    assert_eq!(
+      net_worth_series(&journal, &"usd".to_string(), Interval::Month),
+      series(&[("2024-01", 70.0), ("2024-02", 70.0), ("2024-03", 20.0)])
+    );
+    assert_eq!(net_worth_series(&journal, &"eur".to_string(), Interval::Year), series(&[("2024", -5.0)]));
+  }
+
+  #[test]
+  fn spending_lists_expenses_in_the_currency_largest_first() {
+    let journal = journal();
+    let usd = "usd".to_string();
+    assert_eq!(
+      spending_by_account(&journal, &usd, DateRange::default()),
+      series(&[("Expenses:Rent", 50.0), ("Expenses:Food", 30.0)])
+    );
+    let march = DateRange::new(NaiveDate::from_ymd_opt(2024, 3, 1), NaiveDate::from_ymd_opt(2024, 3, 31));
+    assert_eq!(spending_by_account(&journal, &usd, march), series(&[("Expenses:Rent", 50.0)]));
+  }
+
+  #[test]
+  fn line_charts_fit_long_series_in_the_width() {
+    let points = (0..100).map(|i| (format!("p{}", i), i as f64)).collect::<Vec<_>>();
+    let chart = line_chart(&points, 20, 5, &|value| format!("{:.0}", value));
+    let lines = chart.lines().collect::<Vec<_>>();
+    for line in &lines[..lines.len() - 1] {
+      assert!(line.chars().count() <= "99 ┤".chars().count() + 20, "{}", line);
+    }
+    assert_eq!(lines.last().unwrap().trim(), format!("p0{}p99", " ".repeat(16)));
+    assert_eq!(sample(&points, 3), series(&[("p0", 0.0), ("p49", 49.0), ("p99", 99.0)]));
+  }
+
+  #[test]
+  fn line_charts_plot_short_series_in_steps() {
+    let chart = line_chart(&series(&[("a", 0.0), ("b", 2.0)]), 4, 3, &|value| format!("{:.0}", value));
+    assert_eq!(chart, concat!(
+      "2 ┤  ●\n",
+      "  ┤  │\n",
+      "0 ┤●─╯\n",
+      "  └───\n",
+      "   a  b\n",
+    ));
+  }
+}
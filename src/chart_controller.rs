@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use crate::{
+  account::AccountId,
+  chart::{account_balance_series, bar_chart, line_chart, net_worth_series, spending_by_account},
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  page::Navigation,
+  periodic::Interval,
+  report_controller::input_report_options,
+  utility::{on_error, on_not_found, LabeledItem, select_with_labels},
+};
+
+const WIDTH: usize = 60;
+const HEIGHT: usize = 12;
+
+pub fn view_journal_charts(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Selection {
+    Back,
+    NetWorth,
+    Spending,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+    LabeledItem::from("Net Worth".to_string(), Selection::NetWorth),
+    LabeledItem::from("Spending by Account".to_string(), Selection::Spending),
+  ];
+  let selection = match select_with_labels("Select Chart".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+  if let Selection::Back = selection {
+    return navigation.back();
+  }
+
+  let currency = match select_currency(currencies, journal, None) {
+    Err(error) => return on_error(navigation, error),
+    Ok(None) => return println!("No transactions to chart"),
+    Ok(Some(currency)) => currency,
+  };
+  let format = |amount: f64| currency.format_amount(amount);
+
+  match selection {
+    Selection::Back => {},
+    Selection::NetWorth => {
+      let interval = match select_interval() {
+        Err(error) => return on_error(navigation, error),
+        Ok(interval) => interval,
+      };
+      let points = net_worth_series(journal, &currency.id, interval);
+      println!("Net Worth ({})", currency.symbol);
+      println!("{}", line_chart(&points, WIDTH, HEIGHT, &format));
+    },
+    Selection::Spending => {
+      let options = match input_report_options() {
+        Err(error) => return on_error(navigation, error),
+        Ok(options) => options,
+      };
+      let bars = spending_by_account(journal, &currency.id, options.range);
+      println!("Spending by Account ({}, {})", currency.symbol, options.range.describe());
+      println!("{}", bar_chart(&bars, WIDTH, &format));
+    },
+  }
+}
+
+pub fn view_account_chart(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  account_id: &Option<AccountId>,
+  navigation: &mut Navigation,
+) {
+  let (journal, account_id) = match (journal, account_id) {
+    (Some(journal), Some(account_id)) => (journal, account_id),
+    _ => return on_not_found(navigation),
+  };
+
+  let currency = match select_currency(currencies, journal, Some(account_id)) {
+    Err(error) => return on_error(navigation, error),
+    Ok(None) => {
+      println!("No postings to chart");
+      return navigation.back();
+    },
+    Ok(Some(currency)) => currency,
+  };
+  let interval = match select_interval() {
+    Err(error) => return on_error(navigation, error),
+    Ok(interval) => interval,
+  };
+
+  let points = account_balance_series(journal, account_id, &currency.id, interval);
+  println!(
+    "{} Balance ({})",
+    journal.account_full_name(account_id).unwrap_or_default(),
+    currency.symbol
+  );
+  println!("{}", line_chart(&points, WIDTH, HEIGHT, &|amount| currency.format_amount(amount)));
+  navigation.back();
+}
+
+/// Currencies with postings in the journal, or in the account and its
+/// sub-accounts, asking only when there is more than one.
+fn select_currency<'a>(
+  currencies: &'a HashMap<CurrencyId, Currency>,
+  journal: &Journal,
+  account_id: Option<&AccountId>,
+) -> Result<Option<&'a Currency>, std::io::Error> {
+  let mut used = journal.transactions
+    .values()
+    .flat_map(|t| t.account_changes.values())
+    .filter(|c| account_id.is_none_or(|id| journal.is_within(&c.account_id, id)))
+    .filter_map(|c| currencies.get(&c.currency_id))
+    .collect::<Vec<_>>();
+  used.sort_by(|a, b| (&a.symbol, &a.id).cmp(&(&b.symbol, &b.id)));
+  used.dedup_by(|a, b| a.id == b.id);
+
+  match used.len() {
+    0 => Ok(None),
+    1 => Ok(used.pop()),
+    _ => {
+      let options = used
+        .into_iter()
+        .map(|currency| LabeledItem::from(
+          format!("{} ({})", currency.name, currency.symbol),
+          currency
+        ))
+        .collect::<Vec<_>>();
+      Ok(Some(*select_with_labels("Currency".to_string(), &options)?))
+    },
+  }
+}
+
+fn select_interval() -> Result<Interval, std::io::Error> {
+  let options = Interval::all()
+    .into_iter()
+    .map(|interval| LabeledItem::from(interval.as_str().to_string(), interval))
+    .collect::<Vec<_>>();
+  Ok(*select_with_labels("Period".to_string(), &options)?)
+}
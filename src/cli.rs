@@ -0,0 +1,180 @@
+use std::{borrow::Cow, collections::HashMap, fs, path::Path};
+use chrono::NaiveDate;
+use crate::{
+  data::Data,
+  delimited::{to_delimited, Delimiter},
+  journal::Journal,
+  periodic::Interval,
+  query::Query,
+  report::{DateRange, ReportKind, ReportOptions},
+  xlsx::export_xlsx,
+  html::{export_html, write_site},
+};
+
+pub fn usage() -> String {
+  let reports = ReportKind::all()
+    .into_iter()
+    .map(ReportKind::cli_name)
+    .collect::<Vec<_>>()
+    .join("|");
+  [
+    "Usage:".to_string(),
+    "  bin                                   Start the interactive tool".to_string(),
+    "  bin tui                               Start the full-screen interface".to_string(),
+    format!("  bin report <journal> <{}> [options]", reports),
+    format!("  bin export <journal> <{}> [options]", reports),
+    "  bin workbook <journal> --output PATH [options]".to_string(),
+    "  bin site <journal> --output DIRECTORY [options]".to_string(),
+    "  bin query <journal> <terms>...        List the postings the terms select".to_string(),
+    "".to_string(),
+    "Options:".to_string(),
+    "  --from YYYY-MM-DD      First date included".to_string(),
+    "  --to YYYY-MM-DD        Last date included".to_string(),
+    "  --period week|month|quarter|year".to_string(),
+    "                         Period length of the periodic report, month by default".to_string(),
+    "  --show change|balance  Periodic changes or cumulative balances, change by default".to_string(),
+    "  --format csv|tsv       Export format, csv by default".to_string(),
+    "  --output PATH          Export file, stdout by default".to_string(),
+    "  --query TERMS          Only include postings the terms select, e.g.".to_string(),
+    "                         \"account:Expenses date:2024-01.. amount:>50\"".to_string(),
+  ].join("\n")
+}
+
+/// Runs a single command line subcommand. `args` excludes the program name.
+pub fn run(args: &[String], data: &Data) -> Result<(), String> {
+  let args = Arguments::parse(args)?;
+  match args.positional.first().map(String::as_str) {
+    Some("report") => {
+      let (journal, kind) = journal_and_report(&args, data)?;
+      let table = kind.build(&journal, &data.currencies, &args.report_options()?);
+      print!("{}", table.render(&data.currencies));
+      Ok(())
+    },
+    Some("export") => {
+      let (journal, kind) = journal_and_report(&args, data)?;
+      let delimiter = match args.flag("format").unwrap_or("csv") {
+        "csv" => Delimiter::Comma,
+        "tsv" => Delimiter::Tab,
+        other => return Err(format!("Unknown format \"{}\"", other)),
+      };
+      let table = kind.build(&journal, &data.currencies, &args.report_options()?);
+      write_output(args.flag("output"), &to_delimited(&table, &data.currencies, delimiter))
+    },
+    Some("workbook") => {
+      let journal = find_journal(&args, data)?;
+      let path = args.flag("output").ok_or("Workbooks need an --output path")?;
+      let contents = export_xlsx(&journal, &data.currencies, &args.report_options()?)
+        .map_err(|error| error.to_string())?;
+      fs::write(path, contents).map_err(|error| error.to_string())
+    },
+    Some("site") => {
+      let journal = find_journal(&args, data)?;
+      let directory = args.flag("output").ok_or("Sites need an --output directory")?;
+      let pages = export_html(&journal, &data.currencies, &args.report_options()?);
+      write_site(Path::new(directory), &pages).map_err(|error| error.to_string())
+    },
+    Some("query") => {
+      let journal = find_journal(&args, data)?;
+      let query = Query::parse(&args.positional[2..].join(" "))?;
+      let selected = query.select(&journal, &data.currencies);
+      let table = ReportKind::Transactions.build(&selected, &data.currencies, &args.report_options()?);
+      print!("{}", table.render(&data.currencies));
+      Ok(())
+    },
+    Some("help") | Some("--help") | Some("-h") => {
+      println!("{}", usage());
+      Ok(())
+    },
+    Some(other) => Err(format!("Unknown command \"{}\"\n\n{}", other, usage())),
+    None => Err(usage()),
+  }
+}
+
+struct Arguments {
+  positional: Vec<String>,
+  flags: HashMap<String, String>,
+}
+
+impl Arguments {
+  fn parse(args: &[String]) -> Result<Arguments, String> {
+    let mut positional = Vec::new();
+    let mut flags = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+      match arg.strip_prefix("--") {
+        Some(name) if !name.is_empty() => {
+          let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for --{}", name))?;
+          flags.insert(name.to_string(), value.clone());
+        },
+        _ => positional.push(arg.clone()),
+      }
+    }
+    Ok(Arguments { positional, flags })
+  }
+
+  fn flag(&self, name: &str) -> Option<&str> {
+    self.flags.get(name).map(String::as_str)
+  }
+
+  fn date(&self, name: &str) -> Result<Option<NaiveDate>, String> {
+    self.flag(name)
+      .map(|text| NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date for --{}: \"{}\"", name, text)))
+      .transpose()
+  }
+
+  fn report_options(&self) -> Result<ReportOptions, String> {
+    let interval = match self.flag("period") {
+      None => Interval::default(),
+      Some(name) => Interval::from_cli_name(name)
+        .ok_or_else(|| format!("Unknown period \"{}\"", name))?,
+    };
+    let cumulative = match self.flag("show").unwrap_or("change") {
+      "change" => false,
+      "balance" => true,
+      other => return Err(format!("Unknown value for --show: \"{}\"", other)),
+    };
+    Ok(ReportOptions {
+      range: DateRange::new(self.date("from")?, self.date("to")?),
+      interval,
+      cumulative,
+    })
+  }
+}
+
+fn journal_and_report<'a>(
+  args: &Arguments,
+  data: &'a Data,
+) -> Result<(Cow<'a, Journal>, ReportKind), String> {
+  let journal = find_journal(args, data)?;
+  let name = args.positional.get(2).ok_or_else(usage)?;
+  let kind = ReportKind::from_cli_name(name)
+    .ok_or_else(|| format!("Unknown report \"{}\"\n\n{}", name, usage()))?;
+  Ok((journal, kind))
+}
+
+/// Finds a journal by id or by name, keeping only the postings selected by
+/// `--query` when given.
+fn find_journal<'a>(args: &Arguments, data: &'a Data) -> Result<Cow<'a, Journal>, String> {
+  let name = args.positional.get(1).ok_or_else(usage)?;
+  let journal = data.journals
+    .get(name)
+    .or_else(|| data.journals.values().find(|j| &j.name == name))
+    .ok_or_else(|| format!("No journal named \"{}\"", name))?;
+  match args.flag("query") {
+    None => Ok(Cow::Borrowed(journal)),
+    Some(text) => Ok(Cow::Owned(Query::parse(text)?.select(journal, &data.currencies))),
+  }
+}
+
+fn write_output(path: Option<&str>, contents: &str) -> Result<(), String> {
+  match path {
+    None => {
+      print!("{}", contents);
+      Ok(())
+    },
+    Some(path) => fs::write(path, contents).map_err(|error| error.to_string()),
+  }
+}
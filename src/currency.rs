@@ -1,13 +1,21 @@
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub type CurrencyId = String;
 
+fn default_precision() -> usize {
+  2
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Currency {
   pub id: CurrencyId,
   pub name: String,
   pub symbol: String,
+  /// Number of decimal places amounts are shown with.
+  #[serde(default = "default_precision")]
+  pub precision: usize,
 }
 
 impl Currency {
@@ -16,6 +24,71 @@ impl Currency {
       id: Uuid::new_v4().to_string(),
       name,
       symbol,
+      precision: default_precision(),
+    }
+  }
+
+  /// Formats an amount for display, prefixing single character symbols
+  /// such as `$` and suffixing codes such as `EUR`.
+  pub fn format_amount(&self, amount: f64) -> String {
+    let (sign, number) = self.sign_and_number(amount);
+    match self.symbol.chars().count() == 1 {
+      true => format!("{}{}{}", sign, self.symbol, number),
+      false => format!("{}{} {}", sign, number, self.symbol),
     }
   }
-}
\ No newline at end of file
+
+  /// Formats an amount at the currency's precision without its symbol.
+  /// Amounts that round to zero are never shown as negative.
+  pub fn format_number(&self, amount: f64) -> String {
+    let (sign, number) = self.sign_and_number(amount);
+    format!("{}{}", sign, number)
+  }
+
+  /// Whether the currency stands for the ISO 4217 `code`, either by using
+  /// it as its symbol or name or by using the code's usual symbol.
+  pub fn matches_code(&self, code: &str) -> bool {
+    self.symbol == code
+      || self.name.eq_ignore_ascii_case(code)
+      || iso_symbol(code) == Some(self.symbol.as_str())
+  }
+
+  fn sign_and_number(&self, amount: f64) -> (&'static str, String) {
+    let number = format!("{:.*}", self.precision, amount.abs());
+    let sign = match amount < 0.0 && number.chars().any(|c| c.is_ascii_digit() && c != '0') {
+      true => "-",
+      false => "",
+    };
+    (sign, number)
+  }
+}
+
+/// The symbol usually written for a common ISO 4217 code.
+fn iso_symbol(code: &str) -> Option<&'static str> {
+  match code {
+    "USD" => Some("$"),
+    "EUR" => Some("€"),
+    "GBP" => Some("£"),
+    "JPY" => Some("¥"),
+    "INR" => Some("₹"),
+    "KRW" => Some("₩"),
+    "ILS" => Some("₪"),
+    "NGN" => Some("₦"),
+    "PHP" => Some("₱"),
+    "UAH" => Some("₴"),
+    "VND" => Some("₫"),
+    "THB" => Some("฿"),
+    "RUB" => Some("₽"),
+    "TRY" => Some("₺"),
+    _ => None,
+  }
+}
+
+/// Formats an amount in the currency, or as a plain number when the
+/// currency no longer exists.
+pub fn format_amount(currencies: &HashMap<CurrencyId, Currency>, currency_id: &CurrencyId, amount: f64) -> String {
+  match currencies.get(currency_id) {
+    Some(currency) => currency.format_amount(amount),
+    None => format!("{:.2}", amount),
+  }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use crate::{
+  currency::{Currency, CurrencyId},
+  page::Navigation,
+  utility::{
+    on_error,
+    LabeledItem,
+    input_default,
+    input_until_number_with_validation,
+    select_with_labels,
+  },
+};
+
+/// Most decimal places a currency may be shown with.
+const MAX_PRECISION: usize = 8;
+
+/// Lists the currencies to add or edit.
+pub fn view_currencies(currencies: &mut HashMap<CurrencyId, Currency>, navigation: &mut Navigation) {
+  enum Selection {
+    Currency(CurrencyId),
+    NewCurrency,
+    Back,
+  }
+
+  let mut options = currencies
+    .values()
+    .map(|c| LabeledItem::from(label(c), Selection::Currency(c.id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.insert(0, LabeledItem::from("[Back]".to_string(), Selection::Back));
+  options.insert(1, LabeledItem::from("[New Currency]".to_string(), Selection::NewCurrency));
+
+  let selection = match select_with_labels("Currencies".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let result = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::NewCurrency => new_currency(currencies),
+    Selection::Currency(id) => edit_currency(currencies, id),
+  };
+  if let Err(error) = result {
+    on_error(navigation, error)
+  }
+}
+
+/// Name, symbol and an example amount, e.g. `Dollars ($1,234.57)`.
+fn label(currency: &Currency) -> String {
+  format!("{} ({})", currency.name, currency.format_amount(1234.5678))
+}
+
+fn new_currency(currencies: &mut HashMap<CurrencyId, Currency>) -> Result<(), std::io::Error> {
+  let name = input_default("Currency Name:".to_string(), String::new())?;
+  let symbol = input_default("Symbol:".to_string(), String::new())?;
+  let mut currency = Currency::new(name.trim().to_string(), symbol.trim().to_string());
+  currency.precision = input_precision(currency.precision)?;
+  currencies.insert(currency.id.clone(), currency);
+  Ok(())
+}
+
+fn edit_currency(currencies: &mut HashMap<CurrencyId, Currency>, currency_id: &CurrencyId) -> Result<(), std::io::Error> {
+  enum Selection {
+    Back,
+    Rename,
+    Symbol,
+    Precision,
+  }
+
+  let currency = match currencies.get_mut(currency_id) {
+    None => return Ok(()),
+    Some(currency) => currency,
+  };
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+    LabeledItem::from(format!("[Rename] {}", currency.name), Selection::Rename),
+    LabeledItem::from(format!("[Symbol] {}", currency.symbol), Selection::Symbol),
+    LabeledItem::from(format!("[Decimal Places] {}", currency.precision), Selection::Precision),
+  ];
+  let selection = select_with_labels(label(currency), &options)?;
+
+  match selection {
+    Selection::Back => {},
+    Selection::Rename => {
+      let name = input_default("Currency Name:".to_string(), currency.name.clone())?;
+      currency.name = name.trim().to_string();
+    },
+    Selection::Symbol => {
+      let symbol = input_default("Symbol:".to_string(), currency.symbol.clone())?;
+      currency.symbol = symbol.trim().to_string();
+    },
+    Selection::Precision => {
+      currency.precision = input_precision(currency.precision)?;
+    },
+  }
+  Ok(())
+}
+
+fn input_precision(current: usize) -> Result<usize, std::io::Error> {
+  input_until_number_with_validation(
+    format!("Decimal Places (0-{}):", MAX_PRECISION),
+    current.to_string(),
+    &|places: usize| places <= MAX_PRECISION,
+  )
+}
@@ -7,28 +7,46 @@ use crate::{journal::{JournalId, Journal}, currency::{CurrencyId, Currency}};
 #[derive(Serialize, Deserialize)]
 pub struct Data {
   pub journals: HashMap<JournalId, Journal>,
-  pub currencies: HashMap<CurrencyId, Currency>
+  pub currencies: HashMap<CurrencyId, Currency>,
+  /// Currency offered first when a journal has no postings to go by.
+  #[serde(default)]
+  pub default_currency_id: Option<CurrencyId>,
 }
 
 impl Data {
   pub fn load() -> Data {
     let path = Path::new("db.json");
-    if let Ok(file) = File::open(&path) {
+    if let Ok(file) = File::open(path) {
       serde_json::from_reader(file).unwrap()
     } else {
       let mut data = Data { 
         journals: HashMap::new(), 
-        currencies: HashMap::new()
+        currencies: HashMap::new(),
+        default_currency_id: None,
       };
       let dollars = Currency::new("Dollars".to_string(), "$".to_string());
+      data.default_currency_id = Some(dollars.id.clone());
       data.currencies.insert(dollars.id.clone(), dollars);
       data
     }
   }
 
+  /// The stored default currency, or while none is stored, the first
+  /// currency by name so that imports adding currencies don't change it.
+  pub fn default_currency_id(&self) -> CurrencyId {
+    if let Some(id) = self.default_currency_id.as_ref().filter(|id| self.currencies.contains_key(*id)) {
+      return id.clone();
+    }
+    self.currencies
+      .values()
+      .min_by(|a, b| (&a.name, &a.symbol, &a.id).cmp(&(&b.name, &b.symbol, &b.id)))
+      .map(|c| c.id.clone())
+      .unwrap_or_default()
+  }
+
   pub fn save(self) {
     let path = Path::new("db.json");
-    if let Ok(file) = File::create(&path) {
+    if let Ok(file) = File::create(path) {
       _ = serde_json::to_writer(file, &self);
     }
   }
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use crate::{
+  currency::{Currency, CurrencyId},
+  report::{Cell, Table},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+  Comma,
+  Tab,
+}
+
+impl Delimiter {
+  pub fn as_char(self) -> char {
+    match self {
+      Delimiter::Comma => ',',
+      Delimiter::Tab => '\t',
+    }
+  }
+
+  pub fn extension(self) -> &'static str {
+    match self {
+      Delimiter::Comma => "csv",
+      Delimiter::Tab => "tsv",
+    }
+  }
+}
+
+/// Writes a table as CSV or TSV with a single header row. Fields containing
+/// the delimiter, quotes or line breaks are quoted with doubled quotes, and
+/// amounts are written as plain numbers, at their currency's precision, so
+/// spreadsheets treat them as such.
+pub fn to_delimited(
+  table: &Table,
+  currencies: &HashMap<CurrencyId, Currency>,
+  delimiter: Delimiter,
+) -> String {
+  let mut output = String::new();
+  write_row(&mut output, table.headers.iter().map(String::as_str), delimiter);
+  for row in &table.rows {
+    let fields = row
+      .iter()
+      .map(|cell| match cell {
+        Cell::Text(text) => text.clone(),
+        Cell::Amount(amount, currency_id) => format!(
+          "{:.*}", 
+          currencies.get(currency_id).map_or(2, |c| c.precision), 
+          amount
+        ),
+        Cell::Empty => String::new(),
+      })
+      .collect::<Vec<_>>();
+    write_row(&mut output, fields.iter().map(String::as_str), delimiter);
+  }
+  output
+}
+
+fn write_row<'a>(
+  output: &mut String,
+  fields: impl Iterator<Item = &'a str>,
+  delimiter: Delimiter,
+) {
+  let fields = fields.map(|field| quote(field, delimiter)).collect::<Vec<_>>();
+  output.push_str(&fields.join(&delimiter.as_char().to_string()));
+  output.push_str("\r\n");
+}
+
+fn quote(field: &str, delimiter: Delimiter) -> String {
+  let needs_quotes = field.contains(delimiter.as_char())
+    || field.contains(['"', '\n', '\r'])
+    || field.starts_with(' ')
+    || field.ends_with(' ');
+  match needs_quotes {
+    true => format!("\"{}\"", field.replace('"', "\"\"")),
+    false => field.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn table(rows: Vec<Vec<Cell>>) -> Table {
+    Table { rows, ..Table::new("Test".to_string(), &["Name", "Amount"]) }
+  }
+
+  #[test]
+  fn quotes_fields_with_the_delimiter_quotes_or_line_breaks() {
+    let text = |text: &str| Cell::Text(text.to_string());
+    let table = table(vec![
+      vec![text("Smith, John"), text("tab\there")],
+      vec![text("Say \"hi\""), text("two\nlines")],
+      vec![text(" padded"), text("cr\r")],
+      vec![text("plain"), Cell::Empty],
+    ]);
+    let currencies = HashMap::new();
+    assert_eq!(
+      to_delimited(&table, &currencies, Delimiter::Comma),
+      "Name,Amount\r\n\
+      \"Smith, John\",tab\there\r\n\
+      \"Say \"\"hi\"\"\",\"two\nlines\"\r\n\
+      \" padded\",\"cr\r\"\r\n\
+      plain,\r\n"
+    );
+    assert_eq!(
+      to_delimited(&table, &currencies, Delimiter::Tab),
+      "Name\tAmount\r\n\
+      Smith, John\t\"tab\there\"\r\n\
+      \"Say \"\"hi\"\"\"\t\"two\nlines\"\r\n\
+      \" padded\"\t\"cr\r\"\r\n\
+      plain\t\r\n"
+    );
+  }
+
+  #[test]
+  fn writes_amounts_at_their_currency_precision() {
+    let yen = Currency { precision: 0, ..Currency::new("Yen".to_string(), "¥".to_string()) };
+    let currencies = HashMap::from([(yen.id.clone(), yen.clone())]);
+    let table = table(vec![
+      vec![Cell::Text("Yen".to_string()), Cell::Amount(-1234.6, yen.id.clone())],
+      vec![Cell::Text("Unknown".to_string()), Cell::Amount(1234.5, "gone".to_string())],
+    ]);
+    assert_eq!(
+      to_delimited(&table, &currencies, Delimiter::Comma),
+      "Name,Amount\r\nYen,-1235\r\nUnknown,1234.50\r\n"
+    );
+  }
+}
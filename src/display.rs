@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, HashMap};
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  report::{account_label, account_totals, currency_label, date_label, Cell, DateRange, Table},
+  transaction::Transaction,
+};
+
+/// Counts, date span and balances per account type of a journal.
+pub fn journal_details(journal: &Journal, currencies: &HashMap<CurrencyId, Currency>) -> String {
+  let mut summary = Table::new(journal.name.clone(), &["Detail", "Value"]);
+  let dates = journal.transactions
+    .values()
+    .filter_map(|t| t.parsed_date())
+    .collect::<Vec<_>>();
+  let span = match (dates.iter().min(), dates.iter().max()) {
+    (Some(first), Some(last)) => format!("{} to {}", first, last),
+    _ => "None".to_string(),
+  };
+  for (label, value) in [
+    ("Accounts", journal.accounts.len().to_string()),
+    ("Transactions", journal.transactions.len().to_string()),
+    ("Dates", span),
+    ("Budgets", journal.budgets.len().to_string()),
+    ("Recurring", journal.recurring.len().to_string()),
+    ("Templates", journal.templates.len().to_string()),
+    ("Payees", journal.payees.len().to_string()),
+    ("Import Rules", journal.import_rules.len().to_string()),
+  ] {
+    summary.rows.push(vec![Cell::Text(label.to_string()), Cell::Text(value)]);
+  }
+
+  let account_types = [
+    AccountType::Asset,
+    AccountType::Liability,
+    AccountType::Equity,
+    AccountType::Revenue,
+    AccountType::Expense,
+  ];
+  let mut balances: BTreeMap<String, (CurrencyId, [f64; 5])> = BTreeMap::new();
+  for ((account_id, currency_id), total) in account_totals(journal, DateRange::default()) {
+    let account_type = match journal.accounts.get(&account_id) {
+      None => continue,
+      Some(account) => account.account_type,
+    };
+    let index = account_types.iter().position(|t| *t == account_type).unwrap_or_default();
+    let entry = balances
+      .entry(currency_label(currencies, &currency_id))
+      .or_insert((currency_id, [0.0; 5]));
+    entry.1[index] += match account_type.normal_balance() {
+      BalanceType::Debit => total,
+      BalanceType::Credit => -total,
+    };
+  }
+
+  let mut headers = vec!["Currency"];
+  headers.extend(account_types.iter().map(|t| t.as_str()));
+  headers.push("Net Income");
+  let mut totals = Table::new("Balances".to_string(), &headers);
+  for (label, (currency_id, amounts)) in balances {
+    let mut row = vec![Cell::Text(label)];
+    row.extend(amounts.iter().map(|amount| Cell::Amount(*amount, currency_id.clone())));
+    row.push(Cell::Amount(amounts[3] - amounts[4], currency_id));
+    totals.rows.push(row);
+  }
+
+  format!("{}\n{}", summary.render(currencies), totals.render(currencies))
+}
+
+/// Type, normal balance, settings and current balance of an account,
+/// including its sub-accounts.
+pub fn account_details(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  account_id: &AccountId,
+) -> String {
+  let account = match journal.accounts.get(account_id) {
+    None => return String::new(),
+    Some(account) => account,
+  };
+
+  let postings = journal.postings()
+    .into_iter()
+    .filter(|p| journal.is_within(&p.change.account_id, account_id))
+    .collect::<Vec<_>>();
+  let parent = account.parent_id
+    .as_ref()
+    .map_or("None".to_string(), |id| account_label(journal, id));
+  let cash = match account.is_cash {
+    true => "Yes",
+    false => "No",
+  };
+  let reconciled = account.reconciliations
+    .iter()
+    .max_by_key(|r| (r.statement_date, r.reconciled_on))
+    .map_or("Never".to_string(), |r| r.statement_date.to_string());
+  let mut summary = Table::new(account_label(journal, account_id), &["Detail", "Value"]);
+  for (label, value) in [
+    ("Type", account.account_type.as_str().to_string()),
+    ("Normal Balance", account.balance_type.as_str().to_string()),
+    ("Parent", parent),
+    ("Cash", cash.to_string()),
+    ("Cash Flow Activity", account.cash_flow_activity().as_str().to_string()),
+    ("Postings", postings.len().to_string()),
+    ("Last Reconciled", reconciled),
+  ] {
+    summary.rows.push(vec![Cell::Text(label.to_string()), Cell::Text(value)]);
+  }
+
+  let mut balances: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for posting in &postings {
+    let currency_id = &posting.change.currency_id;
+    balances
+      .entry(currency_label(currencies, currency_id))
+      .or_insert((currency_id.clone(), 0.0))
+      .1 += posting.change.signed_amount(account.balance_type);
+  }
+  let mut balance = Table::new("Balance".to_string(), &["Currency", "Balance"]);
+  for (label, (currency_id, amount)) in balances {
+    balance.rows.push(vec![Cell::Text(label), Cell::Amount(amount, currency_id)]);
+  }
+
+  format!("{}\n{}", summary.render(currencies), balance.render(currencies))
+}
+
+/// The postings of a transaction with debit and credit columns and totals
+/// per currency.
+pub fn transaction_details(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  transaction: &Transaction,
+) -> String {
+  let title = format!(
+    "{} {}",
+    date_label(transaction.parsed_date(), &transaction.date),
+    transaction.name
+  );
+  let mut table = Table::new(title, &["Account", "Currency", "Debit", "Credit"]);
+
+  let mut changes = transaction.account_changes.values().collect::<Vec<_>>();
+  changes.sort_by_cached_key(|c| (
+    c.credit_or_debit == BalanceType::Credit,
+    account_label(journal, &c.account_id),
+  ));
+  let mut totals: BTreeMap<String, (CurrencyId, f64, f64)> = BTreeMap::new();
+  for change in changes {
+    table.rows.push(posting_row(journal, currencies, change));
+    let total = totals
+      .entry(currency_label(currencies, &change.currency_id))
+      .or_insert((change.currency_id.clone(), 0.0, 0.0));
+    match change.credit_or_debit {
+      BalanceType::Debit => total.1 += change.amount,
+      BalanceType::Credit => total.2 += change.amount,
+    }
+  }
+  for (label, (currency_id, debit, credit)) in totals {
+    table.rows.push(vec![
+      Cell::Text("Total".to_string()),
+      Cell::Text(label),
+      Cell::Amount(debit, currency_id.clone()),
+      Cell::Amount(credit, currency_id),
+    ]);
+  }
+
+  let mut output = table.render(currencies);
+  if !transaction.tags.is_empty() {
+    output = format!("Tags: {}\n{}", transaction.tags.join(", "), output);
+  }
+  if !transaction.description.is_empty() {
+    output = format!("{}\n{}", transaction.description, output);
+  }
+  output
+}
+
+/// A single posting in the layout of `transaction_details`.
+pub fn account_change_details(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  transaction: &Transaction,
+  account_change: &AccountChange,
+) -> String {
+  let title = format!(
+    "{} {}",
+    date_label(transaction.parsed_date(), &transaction.date),
+    transaction.name
+  );
+  let mut table = Table::new(title, &["Account", "Currency", "Debit", "Credit"]);
+  table.rows.push(posting_row(journal, currencies, account_change));
+  format!("{}Status: {}", table.render(currencies), account_change.status.as_str())
+}
+
+fn posting_row(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  change: &AccountChange,
+) -> Vec<Cell> {
+  let amount = Cell::Amount(change.amount, change.currency_id.clone());
+  let (debit, credit) = match change.credit_or_debit {
+    BalanceType::Debit => (amount, Cell::Empty),
+    BalanceType::Credit => (Cell::Empty, amount),
+  };
+  let account = match change.memo.is_empty() {
+    true => account_label(journal, &change.account_id),
+    false => format!("{} ({})", account_label(journal, &change.account_id), change.memo),
+  };
+  vec![
+    Cell::Text(account),
+    Cell::Text(currency_label(currencies, &change.currency_id)),
+    debit,
+    credit,
+  ]
+}
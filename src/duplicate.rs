@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use crate::{
+  account::{AccountId, BalanceType},
+  account_change::AccountChange,
+  currency::CurrencyId,
+  journal::Journal,
+  transaction::{Transaction, TransactionId},
+};
+
+pub fn default_window_days() -> u32 {
+  3
+}
+
+/// Accounts, currencies, sides and amounts in cents of every posting,
+/// sorted, so that transactions posting the same amounts compare equal.
+type Signature = Vec<(AccountId, CurrencyId, bool, i64)>;
+
+fn posting_key(change: &AccountChange) -> (AccountId, CurrencyId, bool, i64) {
+  (
+    change.account_id.clone(),
+    change.currency_id.clone(),
+    change.credit_or_debit == BalanceType::Debit,
+    (change.amount * 100.0).round() as i64,
+  )
+}
+
+fn signature(transaction: &Transaction) -> Signature {
+  let mut signature = transaction.account_changes
+    .values()
+    .map(posting_key)
+    .collect::<Vec<_>>();
+  signature.sort();
+  signature
+}
+
+impl Journal {
+  /// Pairs of transactions with the same postings dated at most
+  /// `window_days` apart, older first, leaving out pairs already marked as
+  /// distinct. With `among`, only pairs involving one of those transactions
+  /// are returned.
+  pub fn find_duplicates(
+    &self,
+    window_days: u32,
+    among: Option<&[TransactionId]>,
+  ) -> Vec<(TransactionId, TransactionId)> {
+    let mut groups: HashMap<Signature, Vec<&Transaction>> = HashMap::new();
+    for transaction in self.transactions.values() {
+      if transaction.parsed_date().is_some() && !transaction.account_changes.is_empty() {
+        groups.entry(signature(transaction)).or_default().push(transaction);
+      }
+    }
+
+    let mut pairs = Vec::new();
+    for group in groups.values_mut() {
+      group.sort_by_key(|t| (t.parsed_date(), t.id.clone()));
+      for (index, first) in group.iter().enumerate() {
+        for second in &group[index + 1..] {
+          let days = (second.parsed_date().unwrap_or_default() - first.parsed_date().unwrap_or_default())
+            .num_days();
+          if days > window_days as i64 {
+            break;
+          }
+          let involved = among.is_none_or(|ids| ids.contains(&first.id) || ids.contains(&second.id));
+          if involved && !self.are_distinct(&first.id, &second.id) {
+            pairs.push((first.parsed_date(), first.id.clone(), second.id.clone()));
+          }
+        }
+      }
+    }
+    pairs.sort();
+    pairs.into_iter().map(|(_, first, second)| (first, second)).collect()
+  }
+
+  fn are_distinct(&self, first: &TransactionId, second: &TransactionId) -> bool {
+    self.distinct_pairs
+      .iter()
+      .any(|(a, b)| (a == first && b == second) || (a == second && b == first))
+  }
+
+  /// Remembers that two transactions are not duplicates of each other.
+  pub fn mark_distinct(&mut self, first: &TransactionId, second: &TransactionId) {
+    if !self.are_distinct(first, second) {
+      self.distinct_pairs.push((first.clone(), second.clone()));
+    }
+  }
+
+  /// Folds `discard` into `keep` and removes it. Tags are combined, an
+  /// empty description or missing payee is filled in, and each posting keeps
+  /// the further cleared status of the two.
+  pub fn merge_duplicates(&mut self, keep: &TransactionId, discard: &TransactionId) {
+    let discarded = match self.transactions.remove(discard) {
+      None => return,
+      Some(transaction) => transaction,
+    };
+    self.distinct_pairs.retain(|(a, b)| a != discard && b != discard);
+    let kept = match self.transactions.get_mut(keep) {
+      None => return,
+      Some(transaction) => transaction,
+    };
+
+    for tag in &discarded.tags {
+      kept.add_tag(tag);
+    }
+    if kept.description.is_empty() {
+      kept.description = discarded.description;
+    }
+    if kept.payee_id.is_none() {
+      kept.payee_id = discarded.payee_id;
+    }
+    let mut unmatched = kept.account_changes.values_mut().collect::<Vec<_>>();
+    for change in discarded.account_changes.values() {
+      let key = posting_key(change);
+      if let Some(index) = unmatched.iter().position(|c| posting_key(c) == key) {
+        let matched = unmatched.swap_remove(index);
+        matched.status = matched.status.max(change.status);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{account::AccountType, account_change::ClearedStatus};
+
+  /// Adds food bought with cash, returning its id.
+  fn add(journal: &mut Journal, name: &str, date: &str, amount: f64) -> TransactionId {
+    let food = journal.find_or_create_account("Food", AccountType::Expense);
+    let cash = journal.find_or_create_account("Cash", AccountType::Asset);
+    let mut transaction = Transaction::new(date.to_string(), name.to_string(), String::new());
+    transaction.add_account_change(AccountChange::new(food, "usd".to_string(), BalanceType::Debit, amount));
+    transaction.add_account_change(AccountChange::new(cash, "usd".to_string(), BalanceType::Credit, amount));
+    let id = transaction.id.clone();
+    journal.transactions.insert(id.clone(), transaction);
+    id
+  }
+
+  fn names(journal: &Journal, pairs: Vec<(TransactionId, TransactionId)>) -> Vec<(String, String)> {
+    let name = |id: &TransactionId| journal.transactions[id].name.clone();
+    pairs.iter().map(|(first, second)| (name(first), name(second))).collect()
+  }
+
+  fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+  }
+
+  #[test]
+  fn finds_pairs_within_the_window() {
+    let mut journal = Journal::new("Home".to_string());
+    add(&mut journal, "A", "2024/1/1", 10.0);
+    add(&mut journal, "B", "2024/1/4", 10.0);
+    add(&mut journal, "C", "2024/1/8", 10.0);
+    add(&mut journal, "Other amount", "2024/1/2", 11.0);
+    add(&mut journal, "Undated", "someday", 10.0);
+
+    assert_eq!(names(&journal, journal.find_duplicates(0, None)), pairs(&[]));
+    assert_eq!(names(&journal, journal.find_duplicates(3, None)), pairs(&[("A", "B")]));
+    assert_eq!(names(&journal, journal.find_duplicates(4, None)), pairs(&[("A", "B"), ("B", "C")]));
+    let mut all = names(&journal, journal.find_duplicates(7, None));
+    all.sort();
+    assert_eq!(all, pairs(&[("A", "B"), ("A", "C"), ("B", "C")]));
+  }
+
+  #[test]
+  fn skips_distinct_pairs_and_pairs_outside_among() {
+    let mut journal = Journal::new("Home".to_string());
+    let a = add(&mut journal, "A", "2024/1/1", 10.0);
+    let b = add(&mut journal, "B", "2024/1/2", 10.0);
+    let c = add(&mut journal, "C", "2024/1/3", 10.0);
+
+    journal.mark_distinct(&b, &a);
+    journal.mark_distinct(&a, &b);
+    assert_eq!(journal.distinct_pairs.len(), 1);
+    let mut found = names(&journal, journal.find_duplicates(3, None));
+    found.sort();
+    assert_eq!(found, pairs(&[("A", "C"), ("B", "C")]));
+
+    let among = [c.clone()];
+    assert_eq!(names(&journal, journal.find_duplicates(3, Some(&among))).len(), 2);
+    let among = [a.clone()];
+    assert_eq!(names(&journal, journal.find_duplicates(3, Some(&among))), pairs(&[("A", "C")]));
+    assert_eq!(journal.find_duplicates(3, Some(&[])), vec![]);
+  }
+
+  #[test]
+  fn merging_keeps_the_further_status_and_fills_in_details() {
+    let mut journal = Journal::new("Home".to_string());
+    let keep = add(&mut journal, "Keep", "2024/1/1", 10.0);
+    let discard = add(&mut journal, "Discard", "2024/1/2", 10.0);
+    let other = add(&mut journal, "Other", "2024/1/3", 10.0);
+    let food = journal.find_account("Food").unwrap().clone();
+    let set_status = |transaction: &mut Transaction, food_status, cash_status| {
+      for change in transaction.account_changes.values_mut() {
+        change.status = match change.account_id == food {
+          true => food_status,
+          false => cash_status,
+        };
+      }
+    };
+
+    let kept = journal.transactions.get_mut(&keep).unwrap();
+    set_status(kept, ClearedStatus::Uncleared, ClearedStatus::Cleared);
+    kept.add_tag("groceries");
+    let discarded = journal.transactions.get_mut(&discard).unwrap();
+    set_status(discarded, ClearedStatus::Reconciled, ClearedStatus::Uncleared);
+    discarded.add_tag("groceries");
+    discarded.add_tag("imported");
+    discarded.description = "From the bank".to_string();
+    discarded.payee_id = Some("payee".to_string());
+    journal.mark_distinct(&discard, &other);
+
+    journal.merge_duplicates(&keep, &discard);
+    assert!(!journal.transactions.contains_key(&discard));
+    assert!(journal.distinct_pairs.is_empty());
+    let kept = &journal.transactions[&keep];
+    assert_eq!(kept.tags, vec!["groceries", "imported"]);
+    assert_eq!(kept.description, "From the bank");
+    assert_eq!(kept.payee_id.as_deref(), Some("payee"));
+    let status = |account_id: &AccountId| kept.account_changes
+      .values()
+      .find(|c| &c.account_id == account_id)
+      .map(|c| c.status);
+    assert_eq!(status(&food), Some(ClearedStatus::Reconciled));
+    assert_eq!(status(journal.find_account("Cash").unwrap()), Some(ClearedStatus::Cleared));
+  }
+
+  #[test]
+  fn merging_keeps_existing_details() {
+    let mut journal = Journal::new("Home".to_string());
+    let keep = add(&mut journal, "Keep", "2024/1/1", 10.0);
+    let discard = add(&mut journal, "Discard", "2024/1/2", 10.0);
+    let kept = journal.transactions.get_mut(&keep).unwrap();
+    kept.description = "Mine".to_string();
+    kept.payee_id = Some("mine".to_string());
+    let discarded = journal.transactions.get_mut(&discard).unwrap();
+    discarded.description = "Theirs".to_string();
+    discarded.payee_id = Some("theirs".to_string());
+
+    journal.merge_duplicates(&keep, &discard);
+    let kept = &journal.transactions[&keep];
+    assert_eq!((kept.description.as_str(), kept.payee_id.as_deref()), ("Mine", Some("mine")));
+    assert_eq!(journal.transactions.len(), 1);
+  }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use crate::{
+  account::BalanceType,
+  account_change::ClearedStatus,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  page::Navigation,
+  report::{account_label, date_label, Cell, Table},
+  transaction::{Transaction, TransactionId},
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    input_until_parsed,
+    select_with_labels,
+  },
+};
+
+/// Looks for duplicates anywhere in the journal, within a window of days
+/// that is kept with the journal.
+pub fn find_duplicates(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let window_days = input_until_parsed(
+    "Days Apart (same postings within this many days are duplicates):".to_string(),
+    journal.duplicate_window_days.to_string()
+  );
+  let result = window_days.and_then(|days| {
+    journal.duplicate_window_days = days;
+    review_duplicates(currencies, journal, None)
+  });
+  match result {
+    Err(error) => on_error(navigation, error),
+    Ok(()) => navigation.back(),
+  }
+}
+
+/// Shows each pair of likely duplicates side by side to merge, discard one
+/// of, or keep. With `among`, only pairs involving those transactions are
+/// shown, e.g. the ones an import just added.
+pub fn review_duplicates(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &mut Journal,
+  among: Option<&[TransactionId]>,
+) -> Result<(), std::io::Error> {
+  enum Selection {
+    KeepBoth,
+    MergeIntoFirst,
+    MergeIntoSecond,
+    DiscardFirst,
+    DiscardSecond,
+    Skip,
+    Stop,
+  }
+
+  let pairs = journal.find_duplicates(journal.duplicate_window_days, among);
+  if pairs.is_empty() {
+    if among.is_none() {
+      println!("No duplicates found");
+    }
+    return Ok(());
+  }
+
+  let count = pairs.len();
+  for (index, (first, second)) in pairs.into_iter().enumerate() {
+    // An earlier merge or discard may have removed either one
+    let table = match (journal.transactions.get(&first), journal.transactions.get(&second)) {
+      (Some(a), Some(b)) => side_by_side(journal, a, b),
+      _ => continue,
+    };
+    println!("{}", table.render(currencies));
+
+    let options = vec![
+      LabeledItem::from("[Merge Into First]".to_string(), Selection::MergeIntoFirst),
+      LabeledItem::from("[Merge Into Second]".to_string(), Selection::MergeIntoSecond),
+      LabeledItem::from("[Discard First]".to_string(), Selection::DiscardFirst),
+      LabeledItem::from("[Discard Second]".to_string(), Selection::DiscardSecond),
+      LabeledItem::from("[Not Duplicates]".to_string(), Selection::KeepBoth),
+      LabeledItem::from("[Skip]".to_string(), Selection::Skip),
+      LabeledItem::from("[Stop]".to_string(), Selection::Stop),
+    ];
+    let prompt = format!("Possible Duplicate {} of {}", index + 1, count);
+    match select_with_labels(prompt, &options)? {
+      Selection::MergeIntoFirst => journal.merge_duplicates(&first, &second),
+      Selection::MergeIntoSecond => journal.merge_duplicates(&second, &first),
+      Selection::DiscardFirst => {
+        journal.transactions.remove(&first);
+      },
+      Selection::DiscardSecond => {
+        journal.transactions.remove(&second);
+      },
+      Selection::KeepBoth => journal.mark_distinct(&first, &second),
+      Selection::Skip => {},
+      Selection::Stop => return Ok(()),
+    }
+  }
+  Ok(())
+}
+
+fn side_by_side(journal: &Journal, first: &Transaction, second: &Transaction) -> Table {
+  let mut table = Table::new("Possible Duplicates".to_string(), &["", "First", "Second"]);
+  let text = |label: &str, value: &dyn Fn(&Transaction) -> String| vec![
+    Cell::Text(label.to_string()),
+    Cell::Text(value(first)),
+    Cell::Text(value(second)),
+  ];
+  table.rows.push(text("Date", &|t| date_label(t.parsed_date(), &t.date)));
+  table.rows.push(text("Name", &|t| t.name.clone()));
+  table.rows.push(text("Description", &|t| t.description.clone()));
+  table.rows.push(text("Tags", &|t| t.tags.join(", ")));
+  table.rows.push(text("Status", &|t| {
+    t.account_changes
+      .values()
+      .map(|c| c.status)
+      .max()
+      .unwrap_or(ClearedStatus::Uncleared)
+      .as_str()
+      .to_string()
+  }));
+
+  // Both have the same postings, so one list serves for the two
+  let mut changes = first.account_changes.values().collect::<Vec<_>>();
+  changes.sort_by_key(|c| (c.credit_or_debit == BalanceType::Credit, account_label(journal, &c.account_id)));
+  for change in changes {
+    table.rows.push(vec![
+      Cell::Text(format!("{} {}", change.credit_or_debit.as_str(), account_label(journal, &change.account_id))),
+      Cell::Amount(change.amount, change.currency_id.clone()),
+      Cell::Amount(change.amount, change.currency_id.clone()),
+    ]);
+  }
+  table
+}
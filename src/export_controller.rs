@@ -0,0 +1,120 @@
+use std::{fs, collections::HashMap, path::Path};
+use crate::{
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  delimited::{to_delimited, Delimiter},
+  ledger::export_ledger,
+  report::{transactions, ReportOptions},
+  xlsx::export_xlsx,
+  html::{export_html, write_site},
+  page::Navigation,
+  qif::export_qif,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    select_with_labels,
+    fuzzy_input_with_labels,
+    input_default,
+  },
+};
+
+pub fn export_journal(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Format {
+    Qif,
+    Ledger,
+    Delimited(Delimiter),
+    Workbook,
+    Site,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Format::Back),
+    LabeledItem::from("QIF".to_string(), Format::Qif),
+    LabeledItem::from("Ledger / hledger".to_string(), Format::Ledger),
+    LabeledItem::from("Transactions CSV".to_string(), Format::Delimited(Delimiter::Comma)),
+    LabeledItem::from("Transactions TSV".to_string(), Format::Delimited(Delimiter::Tab)),
+    LabeledItem::from("Excel Workbook".to_string(), Format::Workbook),
+    LabeledItem::from("HTML Site".to_string(), Format::Site),
+  ];
+  let format = match select_with_labels("Export Format".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(format) => format,
+  };
+
+  let contents = match format {
+    Format::Back => return navigation.back(),
+    Format::Qif => {
+      let mut options = journal.accounts
+        .keys()
+        .map(|id| LabeledItem::from(
+          journal.account_full_name(id).unwrap_or_default(), 
+          id
+        ))
+        .collect::<Vec<_>>();
+      options.sort_by(|a, b| a.label.cmp(&b.label));
+      let account_id = match fuzzy_input_with_labels(
+        "Export Account:".to_string(), 
+        &options
+      ) {
+        Err(error) => return on_error(navigation, error),
+        Ok(account_id) => *account_id,
+      };
+      match export_qif(journal, account_id) {
+        None => return on_not_found(navigation),
+        Some(contents) => (contents.into_bytes(), "export.qif".to_string()),
+      }
+    },
+    Format::Ledger => (export_ledger(journal, currencies).into_bytes(), "export.journal".to_string()),
+    Format::Delimited(delimiter) => {
+      let table = transactions(journal, currencies, &ReportOptions::default());
+      (
+        to_delimited(&table, currencies, *delimiter).into_bytes(), 
+        format!("transactions.{}", delimiter.extension())
+      )
+    },
+    Format::Workbook => match export_xlsx(journal, currencies, &ReportOptions::default()) {
+      Err(error) => {
+        println!("Export failed. {}", error);
+        return navigation.back();
+      },
+      Ok(contents) => (contents, format!("{}.xlsx", journal.name)),
+    },
+    Format::Site => {
+      let directory = match input_default(
+        "Directory:".to_string(), 
+        format!("{} site", journal.name)
+      ) {
+        Err(error) => return on_error(navigation, error),
+        Ok(directory) => directory,
+      };
+      let pages = export_html(journal, currencies, &ReportOptions::default());
+      match write_site(Path::new(&directory), &pages) {
+        Err(error) => println!("Export failed. {}", error),
+        Ok(()) => println!("Exported {} pages to {}", pages.len(), directory),
+      }
+      return navigation.back();
+    },
+  };
+
+  let (contents, default_path) = contents;
+  let path = match input_default("File Path:".to_string(), default_path) {
+    Err(error) => return on_error(navigation, error),
+    Ok(path) => path,
+  };
+  match fs::write(&path, contents) {
+    Err(error) => println!("Export failed. {}", error),
+    Ok(()) => println!("Exported to {}", path),
+  }
+  navigation.back();
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account::AccountId,
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  query::Query,
+  report::{account_label, currency_label, DateRange},
+  transaction::Transaction,
+};
+
+pub type FilterId = String;
+
+/// Criteria a transaction must all meet to be listed. Criteria left empty
+/// match every transaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionFilter {
+  /// Looked for in the name and description, ignoring case.
+  pub text: Option<String>,
+  pub range: DateRange,
+  /// Matches postings to the account or any of its sub-accounts.
+  pub account_id: Option<AccountId>,
+  pub currency_id: Option<CurrencyId>,
+  pub min_amount: Option<f64>,
+  pub max_amount: Option<f64>,
+  /// Query terms a posting of the transaction must meet.
+  #[serde(default)]
+  pub query: Query,
+}
+
+impl TransactionFilter {
+  pub fn is_empty(&self) -> bool {
+    self.text.is_none()
+      && self.range == DateRange::default()
+      && self.account_id.is_none()
+      && self.currency_id.is_none()
+      && self.min_amount.is_none()
+      && self.max_amount.is_none()
+      && self.query.is_empty()
+  }
+
+  /// Whether `transaction` meets every criterion. The account, currency and
+  /// amount criteria must all hold for the same posting.
+  pub fn matches(
+    &self,
+    journal: &Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+    transaction: &Transaction,
+  ) -> bool {
+    let has_text = self.text.as_ref().is_none_or(|text| {
+      let text = text.to_lowercase();
+      transaction.name.to_lowercase().contains(&text)
+        || transaction.description.to_lowercase().contains(&text)
+    });
+    let checks_postings = self.account_id.is_some()
+      || self.currency_id.is_some()
+      || self.min_amount.is_some()
+      || self.max_amount.is_some();
+    let has_posting = !checks_postings
+      || transaction.account_changes.values().any(|c| self.matches_change(journal, c));
+    let has_query = self.query.is_empty()
+      || self.query.matches_transaction(journal, currencies, transaction);
+    has_text && has_posting && has_query && self.range.contains(transaction.parsed_date())
+  }
+
+  fn matches_change(&self, journal: &Journal, change: &AccountChange) -> bool {
+    self.account_id.as_ref().is_none_or(|id| journal.is_within(&change.account_id, id))
+      && self.currency_id.as_ref().is_none_or(|id| &change.currency_id == id)
+      && self.min_amount.is_none_or(|min| change.amount >= min)
+      && self.max_amount.is_none_or(|max| change.amount <= max)
+  }
+
+  /// Transactions of `journal` that match, newest first.
+  pub fn apply<'a>(
+    &self,
+    journal: &'a Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+  ) -> Vec<&'a Transaction> {
+    let mut transactions = journal.transactions
+      .values()
+      .filter(|t| self.matches(journal, currencies, t))
+      .collect::<Vec<_>>();
+    transactions.sort_by_cached_key(|t| (t.parsed_date(), t.name.clone(), t.id.clone()));
+    transactions.reverse();
+    transactions
+  }
+
+  /// The criteria in use, e.g. `"rent", 2024-01-01 to 2024-12-31, Expenses`.
+  pub fn describe(&self, journal: &Journal, currencies: &HashMap<CurrencyId, Currency>) -> String {
+    let mut parts = Vec::new();
+    if let Some(text) = &self.text {
+      parts.push(format!("\"{}\"", text));
+    }
+    if self.range != DateRange::default() {
+      parts.push(self.range.describe());
+    }
+    if let Some(account_id) = &self.account_id {
+      parts.push(account_label(journal, account_id));
+    }
+    if let Some(currency_id) = &self.currency_id {
+      parts.push(currency_label(currencies, currency_id));
+    }
+    match (self.min_amount, self.max_amount) {
+      (None, None) => {},
+      (Some(min), None) => parts.push(format!("At least {}", min)),
+      (None, Some(max)) => parts.push(format!("At most {}", max)),
+      (Some(min), Some(max)) => parts.push(format!("{} to {}", min, max)),
+    }
+    if !self.query.is_empty() {
+      parts.push(self.query.text.clone());
+    }
+    match parts.is_empty() {
+      true => "All Transactions".to_string(),
+      false => parts.join(", "),
+    }
+  }
+}
+
+/// A filter kept with the journal under a name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedFilter {
+  pub id: FilterId,
+  pub label: String,
+  pub filter: TransactionFilter,
+}
+
+impl SavedFilter {
+  pub fn new(label: String, filter: TransactionFilter) -> SavedFilter {
+    SavedFilter {
+      id: Uuid::new_v4().to_string(),
+      label,
+      filter,
+    }
+  }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use crate::{
+  currency::{Currency, CurrencyId},
+  filter::{FilterId, SavedFilter, TransactionFilter},
+  journal::Journal,
+  page::Navigation,
+  query::Query,
+  report::{account_label, currency_label, DateRange},
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    fuzzy_input_with_labels,
+    input_default,
+    input_optional_parsed,
+    select_with_labels,
+  },
+};
+
+/// Edits the filter of the transaction list one criterion at a time.
+pub fn filter_transactions(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Selection {
+    Apply,
+    Text,
+    Start,
+    End,
+    Account,
+    Currency,
+    MinAmount,
+    MaxAmount,
+    Query,
+    Clear,
+    Cancel,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut filter = navigation.context().filter;
+  loop {
+    let any = || "Any".to_string();
+    let options = vec![
+      LabeledItem::from("[Apply]".to_string(), Selection::Apply),
+      LabeledItem::from(
+        format!("Text: {}", filter.text.clone().unwrap_or_else(any)),
+        Selection::Text
+      ),
+      LabeledItem::from(
+        format!("From: {}", filter.range.start.map_or_else(any, |d| d.to_string())),
+        Selection::Start
+      ),
+      LabeledItem::from(
+        format!("To: {}", filter.range.end.map_or_else(any, |d| d.to_string())),
+        Selection::End
+      ),
+      LabeledItem::from(
+        format!(
+          "Account: {}",
+          filter.account_id.as_ref().map_or_else(any, |id| account_label(journal, id))
+        ),
+        Selection::Account
+      ),
+      LabeledItem::from(
+        format!(
+          "Currency: {}",
+          filter.currency_id.as_ref().map_or_else(any, |id| currency_label(currencies, id))
+        ),
+        Selection::Currency
+      ),
+      LabeledItem::from(
+        format!("Minimum Amount: {}", filter.min_amount.map_or_else(any, |a| a.to_string())),
+        Selection::MinAmount
+      ),
+      LabeledItem::from(
+        format!("Maximum Amount: {}", filter.max_amount.map_or_else(any, |a| a.to_string())),
+        Selection::MaxAmount
+      ),
+      LabeledItem::from(
+        format!(
+          "Query: {}",
+          match filter.query.is_empty() {
+            true => any(),
+            false => filter.query.text.clone(),
+          }
+        ),
+        Selection::Query
+      ),
+      LabeledItem::from("[Clear All]".to_string(), Selection::Clear),
+      LabeledItem::from("[Cancel]".to_string(), Selection::Cancel),
+    ];
+
+    let selection = match select_with_labels("Filter Transactions".to_string(), &options) {
+      Err(error) => return on_error(navigation, error),
+      Ok(selection) => selection,
+    };
+
+    let result = match selection {
+      Selection::Apply => {
+        navigation.back();
+        return navigation.set_filter(filter);
+      },
+      Selection::Cancel => return navigation.back(),
+      Selection::Clear => {
+        filter = TransactionFilter::default();
+        Ok(())
+      },
+      Selection::Text => input_optional_parsed(
+        "Name or Description Contains (empty for any):".to_string(),
+        filter.text.clone()
+      ).map(|text| filter.text = text),
+      Selection::Start => input_optional_parsed(
+        "From (YYYY-MM-DD, empty for any):".to_string(),
+        filter.range.start
+      ).map(|start| filter.range = DateRange::new(start, filter.range.end)),
+      Selection::End => input_optional_parsed(
+        "To (YYYY-MM-DD, empty for any):".to_string(),
+        filter.range.end
+      ).map(|end| filter.range = DateRange::new(filter.range.start, end)),
+      Selection::Account => {
+        let mut options = journal.accounts
+          .keys()
+          .map(|id| LabeledItem::from(account_label(journal, id), Some(id.clone())))
+          .collect::<Vec<_>>();
+        options.sort_by(|a, b| a.label.cmp(&b.label));
+        options.insert(0, LabeledItem::from("[Any]".to_string(), None));
+        fuzzy_input_with_labels("Account:".to_string(), &options)
+          .map(|account_id| filter.account_id = account_id.clone())
+      },
+      Selection::Currency => {
+        let mut options = currencies
+          .values()
+          .map(|c| LabeledItem::from(format!("{} ({})", c.name, c.symbol), Some(c.id.clone())))
+          .collect::<Vec<_>>();
+        options.sort_by(|a, b| a.label.cmp(&b.label));
+        options.insert(0, LabeledItem::from("[Any]".to_string(), None));
+        select_with_labels("Currency:".to_string(), &options)
+          .map(|currency_id| filter.currency_id = currency_id.clone())
+      },
+      Selection::MinAmount => input_optional_parsed(
+        "Minimum Posting Amount (empty for any):".to_string(),
+        filter.min_amount
+      ).map(|amount| filter.min_amount = amount),
+      Selection::MaxAmount => input_optional_parsed(
+        "Maximum Posting Amount (empty for any):".to_string(),
+        filter.max_amount
+      ).map(|amount| filter.max_amount = amount),
+      Selection::Query => input_query(&filter.query).map(|query| filter.query = query),
+    };
+    if let Err(error) = result {
+      return on_error(navigation, error);
+    }
+  }
+}
+
+/// Lists the journal's saved filters to apply or delete.
+pub fn saved_filters(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Selection {
+    Filter(FilterId),
+    Delete,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = journal.filters
+    .values()
+    .map(|f| LabeledItem::from(
+      format!("{} ({})", f.label, f.filter.describe(journal, currencies)),
+      Selection::Filter(f.id.clone())
+    ))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.insert(0, LabeledItem::from("[Back]".to_string(), Selection::Back));
+  if !journal.filters.is_empty() {
+    options.push(LabeledItem::from("[Delete Filter]".to_string(), Selection::Delete));
+  }
+
+  let selection = match select_with_labels("Saved Filters".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  match selection {
+    Selection::Back => navigation.back(),
+    Selection::Filter(id) => {
+      let filter = match journal.filters.get(id) {
+        None => return on_not_found(navigation),
+        Some(saved) => saved.filter.clone(),
+      };
+      navigation.back();
+      navigation.set_filter(filter);
+    },
+    Selection::Delete => {
+      let mut options = journal.filters
+        .values()
+        .map(|f| LabeledItem::from(f.label.clone(), f.id.clone()))
+        .collect::<Vec<_>>();
+      options.sort_by(|a, b| a.label.cmp(&b.label));
+      match select_with_labels("Delete Filter".to_string(), &options) {
+        Err(error) => on_error(navigation, error),
+        Ok(filter_id) => {
+          journal.filters.remove(filter_id);
+        },
+      }
+    },
+  }
+}
+
+/// Asks for query terms until they parse. Empty input selects everything.
+pub fn input_query(current: &Query) -> Result<Query, std::io::Error> {
+  loop {
+    let current = Some(current.text.clone()).filter(|text| !text.is_empty());
+    let text = input_optional_parsed::<String>(
+      "Query (e.g. account:Expenses date:2024-01.. amount:>50, empty for all):".to_string(),
+      current
+    )?.unwrap_or_default();
+    match Query::parse(&text) {
+      Err(error) => println!("{}", error),
+      Ok(query) => return Ok(query),
+    }
+  }
+}
+
+/// Keeps `filter` with the journal under a name the user picks.
+pub fn save_filter(
+  journal: &mut Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  filter: &TransactionFilter,
+) -> Result<(), std::io::Error> {
+  let label = input_default("Filter Name:".to_string(), filter.describe(journal, currencies))?;
+  let saved = SavedFilter::new(label, filter.clone());
+  journal.filters.insert(saved.id.clone(), saved);
+  Ok(())
+}
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+use crate::{
+  account::{AccountId, BalanceType},
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  posting::Posting,
+  report::{account_label, currency_label, date_label, Cell, ReportOptions, Table},
+};
+
+/// One account's activity in a single currency.
+pub struct LedgerAccount<'a> {
+  pub account_id: AccountId,
+  pub name: String,
+  pub currency_id: CurrencyId,
+  pub opening_balance: f64,
+  pub lines: Vec<LedgerLine<'a>>,
+  pub closing_balance: f64,
+}
+
+pub struct LedgerLine<'a> {
+  pub posting: Posting<'a>,
+  /// Balance after this posting, positive on the account's balance type.
+  pub balance: f64,
+}
+
+/// Every account in each currency it has postings in, ordered by full name
+/// then currency. Accounts without postings are shown in the journal's last
+/// used currency. Opening balances cover everything dated before the range,
+/// undated postings included.
+pub fn general_ledger_accounts<'a>(
+  journal: &'a Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Vec<LedgerAccount<'a>> {
+  let range = options.range;
+  let postings = journal.postings();
+  let default_currency_id = journal.last_used_currency().or_else(|| {
+    let mut currencies = currencies.values().collect::<Vec<_>>();
+    currencies.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+    currencies.first().map(|c| c.id.clone())
+  });
+
+  // Seed every account so those without activity still show their balances
+  let mut accounts: HashMap<(AccountId, CurrencyId), LedgerAccount<'a>> = HashMap::new();
+  let mut seed = |account_id: &AccountId, currency_id: &CurrencyId| {
+    accounts
+      .entry((account_id.clone(), currency_id.clone()))
+      .or_insert_with(|| LedgerAccount {
+        account_id: account_id.clone(),
+        name: account_label(journal, account_id),
+        currency_id: currency_id.clone(),
+        opening_balance: 0.0,
+        lines: Vec::new(),
+        closing_balance: 0.0,
+      });
+  };
+  for posting in &postings {
+    seed(&posting.change.account_id, &posting.change.currency_id);
+  }
+  let posted = postings.iter().map(|p| &p.change.account_id).collect::<HashSet<_>>();
+  for account_id in journal.accounts.keys().filter(|id| !posted.contains(id)) {
+    if let Some(currency_id) = &default_currency_id {
+      seed(account_id, currency_id);
+    }
+  }
+
+  for posting in postings {
+    let change = posting.change;
+    let is_before = match (posting.date, range.start) {
+      (_, None) => false,
+      (None, Some(_)) => true,
+      (Some(date), Some(start)) => date < start,
+    };
+    if !is_before && !range.contains(posting.date) {
+      continue;
+    }
+
+    let balance_type = journal.accounts
+      .get(&change.account_id)
+      .map_or(BalanceType::Debit, |a| a.balance_type);
+    let account = match accounts.get_mut(&(change.account_id.clone(), change.currency_id.clone())) {
+      None => continue,
+      Some(account) => account,
+    };
+
+    account.closing_balance += change.signed_amount(balance_type);
+    match is_before {
+      true => account.opening_balance = account.closing_balance,
+      false => account.lines.push(LedgerLine { posting, balance: account.closing_balance }),
+    }
+  }
+
+  let mut accounts = accounts.into_values().collect::<Vec<_>>();
+  accounts.sort_by_cached_key(|a| (a.name.clone(), currency_label(currencies, &a.currency_id)));
+  accounts
+}
+
+/// Opening balance, postings with running balances and closing balance for
+/// every account.
+pub fn general_ledger(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - General Ledger ({})", journal.name, options.range.describe()),
+    &["Account", "Date", "Transaction", "Description", "Debit", "Credit", "Balance", "Currency"],
+  );
+
+  let start = options.range.start.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
+  let end = options.range.end.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
+  for account in general_ledger_accounts(journal, currencies, options) {
+    let currency = currency_label(currencies, &account.currency_id);
+    table.rows.push(vec![
+      Cell::Text(account.name.clone()),
+      Cell::Text(start.clone()),
+      Cell::Text("Opening Balance".to_string()),
+      Cell::Empty,
+      Cell::Empty,
+      Cell::Empty,
+      Cell::Amount(account.opening_balance, account.currency_id.clone()),
+      Cell::Text(currency.clone()),
+    ]);
+
+    for line in &account.lines {
+      let change = line.posting.change;
+      let amount = Cell::Amount(change.amount, change.currency_id.clone());
+      let (debit, credit) = match change.credit_or_debit {
+        BalanceType::Debit => (amount, Cell::Empty),
+        BalanceType::Credit => (Cell::Empty, amount),
+      };
+      table.rows.push(vec![
+        Cell::Text(account.name.clone()),
+        Cell::Text(date_label(line.posting.date, &line.posting.transaction.date)),
+        Cell::Text(line.posting.transaction.name.clone()),
+        Cell::Text(line.posting.transaction.description.clone()),
+        debit,
+        credit,
+        Cell::Amount(line.balance, change.currency_id.clone()),
+        Cell::Text(currency.clone()),
+      ]);
+    }
+
+    table.rows.push(vec![
+      Cell::Text(account.name.clone()),
+      Cell::Text(end.clone()),
+      Cell::Text("Closing Balance".to_string()),
+      Cell::Empty,
+      Cell::Empty,
+      Cell::Empty,
+      Cell::Amount(account.closing_balance, account.currency_id.clone()),
+      Cell::Text(currency),
+    ]);
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDate;
+  use crate::{
+    account::AccountType,
+    account_change::AccountChange,
+    report::DateRange,
+    transaction::Transaction,
+  };
+
+  /// Salary into checking in January and without a readable date, food in
+  /// February and March, and an expense account never posted to.
+  fn journal() -> Journal {
+    let mut journal = Journal::new("Home".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let salary = journal.find_or_create_account("Salary", AccountType::Revenue);
+    let food = journal.find_or_create_account("Food", AccountType::Expense);
+    journal.find_or_create_account("Unused", AccountType::Expense);
+    let entries = [
+      ("2024/1/5", "Pay", &salary, &checking, 100.0),
+      ("someday", "Bonus", &salary, &checking, 20.0),
+      ("2024/2/10", "Lunch", &checking, &food, 30.0),
+      ("2024/3/1", "Dinner", &checking, &food, 15.0),
+    ];
+    for (date, name, from, to, amount) in entries {
+      let mut transaction = Transaction::new(date.to_string(), name.to_string(), String::new());
+      transaction.add_account_change(AccountChange::new(to.clone(), "usd".to_string(), BalanceType::Debit, amount));
+      transaction.add_account_change(AccountChange::new(from.clone(), "usd".to_string(), BalanceType::Credit, amount));
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+    journal
+  }
+
+  fn february() -> ReportOptions {
+    ReportOptions {
+      range: DateRange::new(NaiveDate::from_ymd_opt(2024, 2, 1), NaiveDate::from_ymd_opt(2024, 2, 29)),
+      ..ReportOptions::default()
+    }
+  }
+
+  #[test]
+  fn opening_balances_cover_postings_before_the_range() {
+    let journal = journal();
+    let accounts = general_ledger_accounts(&journal, &HashMap::new(), &february());
+    let summary = accounts
+      .iter()
+      .map(|a| (
+        a.name.as_str(),
+        a.opening_balance,
+        a.lines.iter().map(|l| (l.posting.transaction.name.as_str(), l.balance)).collect::<Vec<_>>(),
+        a.closing_balance,
+      ))
+      .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+      ("Checking", 120.0, vec![("Lunch", 90.0)], 90.0),
+      ("Food", 0.0, vec![("Lunch", 30.0)], 30.0),
+      ("Salary", 120.0, vec![], 120.0),
+      ("Unused", 0.0, vec![], 0.0),
+    ]);
+  }
+
+  #[test]
+  fn unbounded_ranges_list_every_posting() {
+    let journal = journal();
+    let accounts = general_ledger_accounts(&journal, &HashMap::new(), &ReportOptions::default());
+    let checking = accounts.iter().find(|a| a.name == "Checking").unwrap();
+    assert_eq!(checking.opening_balance, 0.0);
+    let lines = checking.lines.iter().map(|l| l.balance).collect::<Vec<_>>();
+    assert_eq!(lines, vec![20.0, 120.0, 90.0, 75.0]);
+    assert_eq!(checking.closing_balance, 75.0);
+  }
+
+  #[test]
+  fn accounts_without_postings_use_the_last_used_currency() {
+    let journal = journal();
+    let table = general_ledger(&journal, &HashMap::new(), &february());
+    let unused = table.rows
+      .iter()
+      .filter(|row| matches!(&row[0], Cell::Text(name) if name == "Unused"))
+      .map(|row| match (&row[1], &row[2], &row[6]) {
+        (Cell::Text(date), Cell::Text(label), Cell::Amount(amount, currency_id)) => {
+          (date.as_str(), label.as_str(), *amount, currency_id.as_str())
+        },
+        _ => panic!("Unexpected row"),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(unused, vec![
+      ("2024-02-01", "Opening Balance", 0.0, "usd"),
+      ("2024-02-29", "Closing Balance", 0.0, "usd"),
+    ]);
+  }
+}
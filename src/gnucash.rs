@@ -0,0 +1,454 @@
+use std::{collections::HashMap, io::Read};
+use flate2::read::GzDecoder;
+use roxmltree::{Document, Node};
+use crate::{
+  account::{Account, AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  parse_error::ParseError,
+  transaction::Transaction,
+};
+
+/// Imports the first book of a GnuCash XML file, either plain or gzip
+/// compressed as GnuCash saves it by default. The account tree is kept,
+/// commodities become currencies (matched to existing ones by symbol, name
+/// or ISO code, with the precision of their smallest fraction) and every
+/// split becomes an account change worth its value in the transaction's
+/// currency.
+pub fn import_gnucash(
+  name: String,
+  bytes: &[u8],
+  currencies: &mut HashMap<CurrencyId, Currency>,
+) -> Result<Journal, ParseError> {
+  let source = decompress(bytes)?;
+  let document = Document::parse(&source)
+    .map_err(|error| ParseError::new(error.pos().row as usize, error.to_string()))?;
+  let line_of = |node: Node| document.text_pos_at(node.range().start).row as usize;
+
+  let book = document
+    .descendants()
+    .find(|n| is(n, "gnc", "book"))
+    .ok_or_else(|| ParseError::new(1, "File does not contain a GnuCash book"))?;
+
+  let mut new_currencies: HashMap<CurrencyId, Currency> = HashMap::new();
+  let mut journal = Journal::new(name);
+
+  for commodity in book.children().filter(|n| is(n, "gnc", "commodity")) {
+    commodity_id(commodity, currencies, &mut new_currencies)
+      .ok_or_else(|| ParseError::new(line_of(commodity), "Commodity has no id"))?;
+  }
+
+  // GnuCash guid to imported account id, with each account's parent guid
+  let mut account_ids: HashMap<&str, AccountId> = HashMap::new();
+  let mut parents: Vec<(AccountId, &str)> = Vec::new();
+  for node in book.children().filter(|n| is(n, "gnc", "account")) {
+    let guid = child_text(node, "act", "id")
+      .ok_or_else(|| ParseError::new(line_of(node), "Account has no id"))?;
+    let kind = child_text(node, "act", "type").unwrap_or_default();
+    if kind == "ROOT" {
+      continue;
+    }
+    let account_type = account_type(kind).ok_or_else(|| {
+      ParseError::new(line_of(node), format!("Unknown account type \"{}\"", kind))
+    })?;
+
+    let mut account = Account::new(
+      child_text(node, "act", "name").unwrap_or_default().to_string(),
+      account_type.normal_balance(),
+      account_type,
+    );
+    account.is_cash = kind == "BANK" || kind == "CASH";
+    if let Some(parent) = child_text(node, "act", "parent") {
+      parents.push((account.id.clone(), parent));
+    }
+    account_ids.insert(guid, account.id.clone());
+    journal.accounts.insert(account.id.clone(), account);
+  }
+  for (account_id, parent) in parents {
+    let parent_id = account_ids.get(parent).cloned();
+    if let Some(account) = journal.accounts.get_mut(&account_id) {
+      account.parent_id = parent_id;
+    }
+  }
+
+  for node in book.children().filter(|n| is(n, "gnc", "transaction")) {
+    let line = line_of(node);
+    let currency_id = child(node, "trn", "currency")
+      .and_then(|c| commodity_id(c, currencies, &mut new_currencies))
+      .ok_or_else(|| ParseError::new(line, "Transaction has no currency"))?;
+    let date = child(node, "trn", "date-posted")
+      .and_then(|d| child_text(d, "ts", "date"))
+      .and_then(|d| chrono::NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok())
+      .ok_or_else(|| ParseError::new(line, "Transaction has no valid posted date"))?;
+
+    let mut transaction = Transaction::new(
+      Transaction::format_date(date),
+      child_text(node, "trn", "description").unwrap_or_default().to_string(),
+      notes(node).unwrap_or_default().to_string(),
+    );
+
+    let splits = child(node, "trn", "splits").into_iter().flat_map(|s| s.children());
+    for split in splits.filter(|n| is(n, "trn", "split")) {
+      let line = line_of(split);
+      let account_id = child_text(split, "split", "account")
+        .and_then(|guid| account_ids.get(guid))
+        .ok_or_else(|| ParseError::new(line, "Split refers to an unknown account"))?;
+      let value = child_text(split, "split", "value")
+        .and_then(parse_rational)
+        .ok_or_else(|| ParseError::new(line, "Split has no valid value"))?;
+      if value == 0.0 {
+        continue;
+      }
+      let side = match value < 0.0 {
+        true => BalanceType::Credit,
+        false => BalanceType::Debit,
+      };
+      transaction.add_account_change(AccountChange::new(
+        account_id.clone(),
+        currency_id.clone(),
+        side,
+        value.abs()
+      ));
+    }
+
+    journal.transactions.insert(transaction.id.clone(), transaction);
+  }
+
+  currencies.extend(new_currencies);
+  Ok(journal)
+}
+
+fn decompress(bytes: &[u8]) -> Result<String, ParseError> {
+  if bytes.starts_with(&[0x1f, 0x8b]) {
+    let mut source = String::new();
+    GzDecoder::new(bytes)
+      .read_to_string(&mut source)
+      .map_err(|error| ParseError::new(1, format!("Could not decompress file. {}", error)))?;
+    Ok(source)
+  } else {
+    String::from_utf8(bytes.to_vec())
+      .map_err(|_| ParseError::new(1, "File is not valid UTF-8"))
+  }
+}
+
+fn account_type(kind: &str) -> Option<AccountType> {
+  match kind {
+    "ASSET" | "BANK" | "CASH" | "STOCK" | "MUTUAL" | "CURRENCY" | "RECEIVABLE" => {
+      Some(AccountType::Asset)
+    },
+    "LIABILITY" | "CREDIT" | "PAYABLE" => Some(AccountType::Liability),
+    "EQUITY" | "TRADING" => Some(AccountType::Equity),
+    "INCOME" => Some(AccountType::Revenue),
+    "EXPENSE" => Some(AccountType::Expense),
+    _ => None,
+  }
+}
+
+/// Finds or creates the currency for a `cmdty:id`/`cmdty:name` pair. ISO
+/// currencies also match an existing currency written with the code's
+/// symbol, e.g. `USD` matches `$`.
+fn commodity_id(
+  node: Node,
+  currencies: &HashMap<CurrencyId, Currency>,
+  new_currencies: &mut HashMap<CurrencyId, Currency>,
+) -> Option<CurrencyId> {
+  let symbol = child_text(node, "cmdty", "id")?;
+  let is_iso = matches!(child_text(node, "cmdty", "space"), Some("ISO4217" | "CURRENCY"));
+  if let Some(currency) = currencies
+    .values()
+    .chain(new_currencies.values())
+    .find(|c| match is_iso {
+      true => c.matches_code(symbol),
+      false => c.symbol == symbol || c.name == symbol,
+    })
+  {
+    return Some(currency.id.clone());
+  }
+  let name = child_text(node, "cmdty", "name").unwrap_or(symbol);
+  let mut currency = Currency::new(name.to_string(), symbol.to_string());
+  if let Some(precision) = child_text(node, "cmdty", "fraction").and_then(precision_of) {
+    currency.precision = precision;
+  }
+  let id = currency.id.clone();
+  new_currencies.insert(id.clone(), currency);
+  Some(id)
+}
+
+/// Decimal places of a smallest fraction such as `100` or `1000`.
+fn precision_of(fraction: &str) -> Option<usize> {
+  let mut fraction: u64 = fraction.parse().ok()?;
+  let mut precision = 0;
+  while fraction > 1 && fraction.is_multiple_of(10) {
+    fraction /= 10;
+    precision += 1;
+  }
+  match fraction {
+    1 => Some(precision),
+    _ => None,
+  }
+}
+
+/// The `notes` slot GnuCash uses for a transaction's long description.
+fn notes<'a>(transaction: Node<'a, '_>) -> Option<&'a str> {
+  child(transaction, "trn", "slots")?
+    .children()
+    .filter(|n| is(n, "", "slot"))
+    .find(|slot| child_text(*slot, "slot", "key") == Some("notes"))
+    .and_then(|slot| child_text(slot, "slot", "value"))
+}
+
+/// Parses GnuCash's `numerator/denominator` amounts.
+fn parse_rational(text: &str) -> Option<f64> {
+  let (numerator, denominator) = text.trim().split_once('/').unwrap_or((text.trim(), "1"));
+  let numerator: i64 = numerator.parse().ok()?;
+  let denominator: i64 = denominator.parse().ok()?;
+  if denominator == 0 {
+    return None;
+  }
+  Some(numerator as f64 / denominator as f64)
+}
+
+/// Matches an element by the prefix GnuCash binds to its namespace, e.g.
+/// `gnc` for `http://www.gnucash.org/XML/gnc`. An empty prefix matches the
+/// bare `slot` elements.
+fn is(node: &Node, prefix: &str, name: &str) -> bool {
+  let tag = node.tag_name();
+  if !node.is_element() || tag.name() != name {
+    return false;
+  }
+  match (prefix, tag.namespace()) {
+    ("", None) => true,
+    (_, Some(namespace)) => namespace.rsplit('/').next() == Some(prefix),
+    _ => false,
+  }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, prefix: &str, name: &str) -> Option<Node<'a, 'input>> {
+  node.children().find(|n| is(n, prefix, name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, prefix: &str, name: &str) -> Option<&'a str> {
+  child(node, prefix, name)?.text().map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+  use flate2::{write::GzEncoder, Compression};
+  use super::*;
+
+  const BOOK: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<gnc-v2
+     xmlns:gnc="http://www.gnucash.org/XML/gnc"
+     xmlns:act="http://www.gnucash.org/XML/act"
+     xmlns:book="http://www.gnucash.org/XML/book"
+     xmlns:cmdty="http://www.gnucash.org/XML/cmdty"
+     xmlns:trn="http://www.gnucash.org/XML/trn"
+     xmlns:split="http://www.gnucash.org/XML/split"
+     xmlns:ts="http://www.gnucash.org/XML/ts">
+<gnc:book version="2.0.0">
+<gnc:commodity version="2.0.0">
+  <cmdty:space>CURRENCY</cmdty:space>
+  <cmdty:id>USD</cmdty:id>
+</gnc:commodity>
+<gnc:commodity version="2.0.0">
+  <cmdty:space>FUND</cmdty:space>
+  <cmdty:id>VTSAX</cmdty:id>
+  <cmdty:name>Total Stock Market</cmdty:name>
+  <cmdty:fraction>1000</cmdty:fraction>
+</gnc:commodity>
+<gnc:account version="2.0.0">
+  <act:name>Root Account</act:name>
+  <act:id type="guid">root</act:id>
+  <act:type>ROOT</act:type>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Assets</act:name>
+  <act:id type="guid">assets</act:id>
+  <act:type>ASSET</act:type>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Checking</act:name>
+  <act:id type="guid">checking</act:id>
+  <act:type>BANK</act:type>
+  <act:parent type="guid">assets</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Visa</act:name>
+  <act:id type="guid">visa</act:id>
+  <act:type>CREDIT</act:type>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Salary</act:name>
+  <act:id type="guid">salary</act:id>
+  <act:type>INCOME</act:type>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:account version="2.0.0">
+  <act:name>Groceries</act:name>
+  <act:id type="guid">groceries</act:id>
+  <act:type>EXPENSE</act:type>
+  <act:parent type="guid">root</act:parent>
+</gnc:account>
+<gnc:transaction version="2.0.0">
+  <trn:id type="guid">t1</trn:id>
+  <trn:currency>
+    <cmdty:space>CURRENCY</cmdty:space>
+    <cmdty:id>USD</cmdty:id>
+  </trn:currency>
+  <trn:date-posted>
+    <ts:date>2024-01-31 10:59:00 +0000</ts:date>
+  </trn:date-posted>
+  <trn:description>Payday</trn:description>
+  <trn:splits>
+    <trn:split>
+      <split:value>250075/100</split:value>
+      <split:account type="guid">checking</split:account>
+    </trn:split>
+    <trn:split>
+      <split:value>-250075/100</split:value>
+      <split:account type="guid">salary</split:account>
+    </trn:split>
+  </trn:splits>
+</gnc:transaction>
+<gnc:transaction version="2.0.0">
+  <trn:id type="guid">t2</trn:id>
+  <trn:currency>
+    <cmdty:space>CURRENCY</cmdty:space>
+    <cmdty:id>USD</cmdty:id>
+  </trn:currency>
+  <trn:date-posted>
+    <ts:date>2024-02-02 10:59:00 +0000</ts:date>
+  </trn:date-posted>
+  <trn:description>Market</trn:description>
+  <trn:splits>
+    <trn:split>
+      <split:value>4250/100</split:value>
+      <split:account type="guid">groceries</split:account>
+    </trn:split>
+    <trn:split>
+      <split:value>-85/2</split:value>
+      <split:account type="guid">visa</split:account>
+    </trn:split>
+    <trn:split>
+      <split:value>0/100</split:value>
+      <split:account type="guid">checking</split:account>
+    </trn:split>
+  </trn:splits>
+</gnc:transaction>
+</gnc:book>
+</gnc-v2>
+"#;
+
+  fn dollars() -> HashMap<CurrencyId, Currency> {
+    let currency = Currency::new("Dollars".to_string(), "$".to_string());
+    HashMap::from([(currency.id.clone(), currency)])
+  }
+
+  /// Account, side and amount.
+  type Posting = (String, &'static str, String);
+
+  /// Each transaction's name and postings, sorted for comparing.
+  fn summary(journal: &Journal) -> Vec<(String, Vec<Posting>)> {
+    let mut transactions = journal.transactions
+      .values()
+      .map(|t| {
+        let mut postings = t.account_changes
+          .values()
+          .map(|c| (
+            journal.account_full_name(&c.account_id).unwrap_or_default(),
+            c.credit_or_debit.as_str(),
+            format!("{:.2}", c.amount),
+          ))
+          .collect::<Vec<_>>();
+        postings.sort();
+        (t.name.clone(), postings)
+      })
+      .collect::<Vec<_>>();
+    transactions.sort();
+    transactions
+  }
+
+  #[test]
+  fn import_keeps_the_account_tree_and_types() {
+    let mut currencies = dollars();
+    let journal = import_gnucash("Book".to_string(), BOOK.as_bytes(), &mut currencies).unwrap();
+    let account = |name: &str| &journal.accounts[journal.find_account(name).unwrap()];
+    assert_eq!(journal.accounts.len(), 5);
+    assert_eq!(account("Assets:Checking").account_type, AccountType::Asset);
+    assert!(account("Assets:Checking").is_cash);
+    assert!(!account("Assets").is_cash);
+    assert_eq!(account("Visa").account_type, AccountType::Liability);
+    assert_eq!(account("Visa").balance_type, BalanceType::Credit);
+    assert_eq!(account("Salary").account_type, AccountType::Revenue);
+    assert_eq!(account("Groceries").account_type, AccountType::Expense);
+  }
+
+  #[test]
+  fn import_converts_rational_values_to_postings() {
+    let mut currencies = dollars();
+    let journal = import_gnucash("Book".to_string(), BOOK.as_bytes(), &mut currencies).unwrap();
+    let posting = |account: &str, side, amount: &str| (account.to_string(), side, amount.to_string());
+    assert_eq!(summary(&journal), vec![
+      ("Market".to_string(), vec![
+        posting("Groceries", "Debit", "42.50"),
+        posting("Visa", "Credit", "42.50"),
+      ]),
+      ("Payday".to_string(), vec![
+        posting("Assets:Checking", "Debit", "2500.75"),
+        posting("Salary", "Credit", "2500.75"),
+      ]),
+    ]);
+  }
+
+  #[test]
+  fn import_matches_iso_codes_and_keeps_fractions() {
+    let mut currencies = dollars();
+    let journal = import_gnucash("Book".to_string(), BOOK.as_bytes(), &mut currencies).unwrap();
+    // USD is the existing "$" currency, only the fund is new
+    assert_eq!(currencies.len(), 2);
+    let dollar_id = currencies.values().find(|c| c.symbol == "$").unwrap().id.clone();
+    assert!(journal.transactions
+      .values()
+      .flat_map(|t| t.account_changes.values())
+      .all(|c| c.currency_id == dollar_id));
+    let fund = currencies.values().find(|c| c.symbol == "VTSAX").unwrap();
+    assert_eq!((fund.name.as_str(), fund.precision), ("Total Stock Market", 3));
+  }
+
+  #[test]
+  fn import_reads_gzip_compressed_books() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(BOOK.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let mut currencies = dollars();
+    let journal = import_gnucash("Book".to_string(), &compressed, &mut currencies).unwrap();
+    assert_eq!(journal.transactions.len(), 2);
+
+    let error = import_gnucash("Book".to_string(), &[0x1f, 0x8b, 0, 1], &mut dollars()).unwrap_err();
+    assert!(error.message.starts_with("Could not decompress"), "{}", error.message);
+  }
+
+  #[test]
+  fn parse_rationals_and_fractions() {
+    assert_eq!(parse_rational("250075/100"), Some(2500.75));
+    assert_eq!(parse_rational("-85/2"), Some(-42.5));
+    assert_eq!(parse_rational("7"), Some(7.0));
+    assert_eq!(parse_rational("1/0"), None);
+    assert_eq!(parse_rational("x/100"), None);
+    assert_eq!(precision_of("1"), Some(0));
+    assert_eq!(precision_of("100"), Some(2));
+    assert_eq!(precision_of("1000000"), Some(6));
+    assert_eq!(precision_of("250"), None);
+  }
+
+  #[test]
+  fn import_rejects_unknown_account_types() {
+    let source = BOOK.replace("<act:type>CREDIT</act:type>", "<act:type>MYSTERY</act:type>");
+    let error = import_gnucash("Book".to_string(), source.as_bytes(), &mut dollars()).unwrap_err();
+    assert!(error.message.contains("MYSTERY"), "{}", error.message);
+  }
+}
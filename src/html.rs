@@ -0,0 +1,514 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  io,
+  path::Path,
+};
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  cash_flow::cash_flow,
+  currency::{format_amount, Currency, CurrencyId},
+  journal::Journal,
+  report::{
+    account_label,
+    account_totals,
+    balance_sheet,
+    currency_label,
+    date_label,
+    income_statement,
+    register,
+    trial_balance,
+    Cell,
+    DateRange,
+    ReportOptions,
+    Table,
+  },
+  transaction::TransactionId,
+};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+nav a { margin-right: 1em; }
+table { border-collapse: collapse; width: 100%; margin: 1em 0 2em; }
+th, td { padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; text-align: left; }
+th { background: #f3f3f3; }
+td.amount, th.amount { text-align: right; font-variant-numeric: tabular-nums; }
+tr.total td { font-weight: bold; }
+.muted { color: #777; }
+svg { display: block; margin: 1em 0; }
+svg text { font-size: 11px; fill: #444; }
+";
+
+/// A file of the generated site, relative to its output directory.
+pub struct HtmlPage {
+  pub path: String,
+  pub contents: String,
+}
+
+/// Builds a self contained, read only HTML snapshot of `journal`: an index
+/// of accounts and transactions, a register page per account with running
+/// balances, a page per transaction and the financial statements for the
+/// given range. Every page is a flat file with inline CSS and SVG.
+pub fn export_html(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Vec<HtmlPage> {
+  let mut pages = vec![
+    HtmlPage { path: "index.html".to_string(), contents: index(journal, currencies) },
+    HtmlPage { path: "statements.html".to_string(), contents: statements(journal, currencies, options) },
+  ];
+  for account_id in journal.accounts.keys() {
+    pages.push(HtmlPage {
+      path: account_path(account_id),
+      contents: account_page(journal, currencies, account_id),
+    });
+  }
+  for transaction_id in journal.transactions.keys() {
+    pages.push(HtmlPage {
+      path: transaction_path(transaction_id),
+      contents: transaction_page(journal, currencies, transaction_id),
+    });
+  }
+  pages
+}
+
+/// Writes the pages produced by `export_html` into `directory`.
+pub fn write_site(directory: &Path, pages: &[HtmlPage]) -> Result<(), io::Error> {
+  fs::create_dir_all(directory)?;
+  for page in pages {
+    fs::write(directory.join(&page.path), &page.contents)?;
+  }
+  Ok(())
+}
+
+fn index(journal: &Journal, currencies: &HashMap<CurrencyId, Currency>) -> String {
+  let mut body = format!(
+    "<h1>{}</h1>\n<p class=\"muted\">{} accounts, {} transactions</p>\n",
+    escape(&journal.name),
+    journal.accounts.len(),
+    journal.transactions.len(),
+  );
+
+  let totals = account_totals(journal, DateRange::default());
+  let mut by_type: BTreeMap<String, Vec<(String, f64)>> = BTreeMap::new();
+  for account_type in [
+    AccountType::Asset,
+    AccountType::Liability,
+    AccountType::Equity,
+    AccountType::Revenue,
+    AccountType::Expense,
+  ] {
+    let mut sums: BTreeMap<String, f64> = BTreeMap::new();
+    for ((account_id, currency_id), total) in &totals {
+      if journal.accounts.get(account_id).map(|a| a.account_type) == Some(account_type) {
+        *sums.entry(currency_id.clone()).or_insert(0.0) += match account_type.normal_balance() {
+          BalanceType::Debit => *total,
+          BalanceType::Credit => -total,
+        };
+      }
+    }
+    for (currency_id, sum) in sums {
+      by_type
+        .entry(currency_id)
+        .or_default()
+        .push((account_type.as_str().to_string(), sum));
+    }
+  }
+  for (currency_id, bars) in &by_type {
+    body.push_str(&format!("<h2>Totals by Type ({})</h2>\n", escape(&currency_label(currencies, currency_id))));
+    body.push_str(&bar_chart(bars));
+  }
+
+  body.push_str("<h2>Accounts</h2>\n<table>\n<tr><th>Account</th><th>Type</th><th class=\"amount\">Balance</th></tr>\n");
+  let mut accounts = journal.accounts
+    .values()
+    .map(|a| (account_label(journal, &a.id), a))
+    .collect::<Vec<_>>();
+  accounts.sort_by(|a, b| a.0.cmp(&b.0));
+  for (name, account) in accounts {
+    let mut balances = totals
+      .iter()
+      .filter(|((id, _), _)| id == &account.id)
+      .map(|((_, currency_id), total)| format_amount(
+        currencies,
+        currency_id,
+        match account.balance_type {
+          BalanceType::Debit => *total,
+          BalanceType::Credit => -total,
+        }
+      ))
+      .collect::<Vec<_>>();
+    balances.sort();
+    body.push_str(&format!(
+      "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td class=\"amount\">{}</td></tr>\n",
+      account_path(&account.id),
+      escape(&name),
+      account.account_type.as_str(),
+      escape(&balances.join(", ")),
+    ));
+  }
+  body.push_str("</table>\n");
+
+  body.push_str("<h2>Transactions</h2>\n<table>\n<tr><th>Date</th><th>Transaction</th><th>Description</th></tr>\n");
+  let mut transactions = journal.transactions.values().collect::<Vec<_>>();
+  transactions.sort_by(|a, b| (a.parsed_date(), &a.name).cmp(&(b.parsed_date(), &b.name)));
+  for transaction in transactions {
+    body.push_str(&format!(
+      "<tr><td>{}</td><td><a href=\"{}\">{}</a></td><td>{}</td></tr>\n",
+      escape(&date_label(transaction.parsed_date(), &transaction.date)),
+      transaction_path(&transaction.id),
+      escape(&transaction.name),
+      escape(&transaction.description),
+    ));
+  }
+  body.push_str("</table>\n");
+
+  layout(&journal.name, &body)
+}
+
+fn statements(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> String {
+  let mut body = String::from("<h1>Financial Statements</h1>\n");
+  for table in [
+    balance_sheet(journal, currencies, options),
+    income_statement(journal, currencies, options),
+    cash_flow(journal, currencies, options),
+    trial_balance(journal, currencies, options),
+  ] {
+    body.push_str(&table_html(&table, currencies));
+  }
+  layout(&format!("{} - Financial Statements", journal.name), &body)
+}
+
+fn account_page(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  account_id: &AccountId,
+) -> String {
+  let name = account_label(journal, account_id);
+  let mut body = format!("<h1>{}</h1>\n", escape(&name));
+  if let Some(account) = journal.accounts.get(account_id) {
+    body.push_str(&format!(
+      "<p class=\"muted\">{} account, {} balance</p>\n",
+      account.account_type.as_str(),
+      account.balance_type.as_str(),
+    ));
+  }
+
+  let entries = register(journal, account_id);
+  let mut series: BTreeMap<&CurrencyId, Vec<f64>> = BTreeMap::new();
+  for entry in &entries {
+    series.entry(&entry.posting.change.currency_id).or_default().push(entry.balance);
+  }
+  for (currency_id, points) in series {
+    body.push_str(&format!("<h2>Balance ({})</h2>\n", escape(&currency_label(currencies, currency_id))));
+    body.push_str(&line_chart(&points));
+  }
+
+  body.push_str(concat!(
+    "<table>\n<tr><th>Date</th><th>Transaction</th><th>Description</th>",
+    "<th class=\"amount\">Debit</th><th class=\"amount\">Credit</th>",
+    "<th class=\"amount\">Balance</th></tr>\n"
+  ));
+  for entry in &entries {
+    let posting = entry.posting;
+    let change = posting.change;
+    let amount = format_amount(currencies, &change.currency_id, change.amount);
+    let (debit, credit) = match change.credit_or_debit {
+      BalanceType::Debit => (amount, String::new()),
+      BalanceType::Credit => (String::new(), amount),
+    };
+    body.push_str(&format!(
+      concat!(
+        "<tr><td>{}</td><td><a href=\"{}\">{}</a></td><td>{}</td>",
+        "<td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n"
+      ),
+      escape(&date_label(posting.date, &posting.transaction.date)),
+      transaction_path(&posting.transaction.id),
+      escape(&posting.transaction.name),
+      escape(&posting.transaction.description),
+      escape(&debit),
+      escape(&credit),
+      escape(&format_amount(currencies, &change.currency_id, entry.balance)),
+    ));
+  }
+  body.push_str("</table>\n");
+
+  layout(&name, &body)
+}
+
+fn transaction_page(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  transaction_id: &TransactionId,
+) -> String {
+  let transaction = match journal.transactions.get(transaction_id) {
+    None => return layout("Not Found", "<p>Not Found</p>"),
+    Some(transaction) => transaction,
+  };
+
+  let mut body = format!(
+    "<h1>{}</h1>\n<p>{}</p>\n<p>{}</p>\n",
+    escape(&transaction.name),
+    escape(&date_label(transaction.parsed_date(), &transaction.date)),
+    escape(&transaction.description),
+  );
+  body.push_str("<table>\n<tr><th>Account</th><th class=\"amount\">Debit</th><th class=\"amount\">Credit</th></tr>\n");
+
+  let mut changes = transaction.account_changes
+    .values()
+    .map(|c| (c.credit_or_debit == BalanceType::Credit, account_label(journal, &c.account_id), c))
+    .collect::<Vec<_>>();
+  changes.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+  let mut totals: BTreeMap<&CurrencyId, (f64, f64)> = BTreeMap::new();
+  for (_, name, change) in changes {
+    let amount = format_amount(currencies, &change.currency_id, change.amount);
+    let total = totals.entry(&change.currency_id).or_insert((0.0, 0.0));
+    let (debit, credit) = match change.credit_or_debit {
+      BalanceType::Debit => {
+        total.0 += change.amount;
+        (amount, String::new())
+      },
+      BalanceType::Credit => {
+        total.1 += change.amount;
+        (String::new(), amount)
+      },
+    };
+    body.push_str(&format!(
+      "<tr><td><a href=\"{}\">{}</a></td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+      account_path(&change.account_id),
+      escape(&name),
+      escape(&debit),
+      escape(&credit),
+    ));
+  }
+  for (currency_id, (debit, credit)) in totals {
+    body.push_str(&format!(
+      "<tr class=\"total\"><td>Total</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+      escape(&format_amount(currencies, currency_id, debit)),
+      escape(&format_amount(currencies, currency_id, credit)),
+    ));
+  }
+  body.push_str("</table>\n");
+
+  layout(&transaction.name, &body)
+}
+
+fn table_html(table: &Table, currencies: &HashMap<CurrencyId, Currency>) -> String {
+  let is_amount = |column: usize| table.rows
+    .iter()
+    .any(|row| matches!(row.get(column), Some(Cell::Amount(..))));
+
+  let mut html = format!("<h2>{}</h2>\n<table>\n<tr>", escape(&table.title));
+  for (column, header) in table.headers.iter().enumerate() {
+    match is_amount(column) {
+      true => html.push_str(&format!("<th class=\"amount\">{}</th>", escape(header))),
+      false => html.push_str(&format!("<th>{}</th>", escape(header))),
+    }
+  }
+  html.push_str("</tr>\n");
+
+  for row in &table.rows {
+    let is_total = matches!(row.first(), Some(Cell::Text(text)) if text.starts_with("Total") || text == "Net Income");
+    html.push_str(if is_total { "<tr class=\"total\">" } else { "<tr>" });
+    for cell in row {
+      match cell {
+        Cell::Text(text) => html.push_str(&format!("<td>{}</td>", escape(text))),
+        Cell::Amount(amount, currency_id) => html.push_str(&format!(
+          "<td class=\"amount\">{}</td>",
+          escape(&format_amount(currencies, currency_id, *amount))
+        )),
+        Cell::Empty => html.push_str("<td></td>"),
+      }
+    }
+    html.push_str("</tr>\n");
+  }
+  html.push_str("</table>\n");
+  html
+}
+
+/// Polyline of `points` in order, with a dashed zero line when the values
+/// cross it.
+fn line_chart(points: &[f64]) -> String {
+  const WIDTH: f64 = 600.0;
+  const HEIGHT: f64 = 160.0;
+  if points.is_empty() {
+    return String::new();
+  }
+
+  let min = points.iter().cloned().fold(0.0_f64, f64::min);
+  let max = points.iter().cloned().fold(0.0_f64, f64::max);
+  let span = if max - min == 0.0 { 1.0 } else { max - min };
+  let x = |i: usize| match points.len() {
+    1 => WIDTH / 2.0,
+    n => i as f64 * WIDTH / (n - 1) as f64,
+  };
+  let y = |value: f64| HEIGHT - (value - min) / span * HEIGHT;
+
+  let coordinates = points
+    .iter()
+    .enumerate()
+    .map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value)))
+    .collect::<Vec<_>>()
+    .join(" ");
+  format!(
+    concat!(
+      "<svg width=\"{w}\" height=\"{h}\" viewBox=\"-40 -10 {vw} {vh}\" role=\"img\">",
+      "<line x1=\"0\" y1=\"{zero:.1}\" x2=\"{width}\" y2=\"{zero:.1}\" stroke=\"#bbb\" stroke-dasharray=\"4\"/>",
+      "<polyline fill=\"none\" stroke=\"#2a6fdb\" stroke-width=\"2\" points=\"{points}\"/>",
+      "<text x=\"-38\" y=\"4\">{max:.0}</text><text x=\"-38\" y=\"{height}\">{min:.0}</text>",
+      "</svg>\n"
+    ),
+    w = WIDTH + 50.0,
+    h = HEIGHT + 20.0,
+    vw = WIDTH + 50.0,
+    vh = HEIGHT + 20.0,
+    zero = y(0.0),
+    width = WIDTH,
+    points = coordinates,
+    max = max,
+    min = min,
+    height = HEIGHT,
+  )
+}
+
+/// Horizontal bars drawn from a shared zero line.
+fn bar_chart(bars: &[(String, f64)]) -> String {
+  const LABEL_WIDTH: f64 = 90.0;
+  const WIDTH: f64 = 420.0;
+  const ROW: f64 = 22.0;
+
+  let min = bars.iter().map(|b| b.1).fold(0.0_f64, f64::min);
+  let max = bars.iter().map(|b| b.1).fold(0.0_f64, f64::max);
+  let span = if max - min == 0.0 { 1.0 } else { max - min };
+  let zero = LABEL_WIDTH + (-min) / span * WIDTH;
+
+  let mut svg = format!(
+    "<svg width=\"{}\" height=\"{}\" role=\"img\">",
+    LABEL_WIDTH + WIDTH + 90.0,
+    ROW * bars.len() as f64 + 4.0,
+  );
+  for (i, (label, value)) in bars.iter().enumerate() {
+    let top = i as f64 * ROW + 2.0;
+    let length = value.abs() / span * WIDTH;
+    let left = if *value < 0.0 { zero - length } else { zero };
+    svg.push_str(&format!(
+      concat!(
+        "<text x=\"0\" y=\"{ty:.1}\">{label}</text>",
+        "<rect x=\"{left:.1}\" y=\"{top:.1}\" width=\"{length:.1}\" height=\"{height:.1}\" fill=\"{fill}\"/>",
+        "<text x=\"{vx:.1}\" y=\"{ty:.1}\">{value:.2}</text>"
+      ),
+      ty = top + ROW * 0.65,
+      label = escape(label),
+      left = left,
+      top = top,
+      length = length.max(1.0),
+      height = ROW - 6.0,
+      fill = if *value < 0.0 { "#d9534f" } else { "#2a6fdb" },
+      vx = LABEL_WIDTH + WIDTH + 6.0,
+      value = value,
+    ));
+  }
+  svg.push_str("</svg>\n");
+  svg
+}
+
+fn layout(title: &str, body: &str) -> String {
+  format!(
+    concat!(
+      "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+      "<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+      "<nav><a href=\"index.html\">Index</a><a href=\"statements.html\">Statements</a></nav>\n",
+      "{}</body>\n</html>\n"
+    ),
+    escape(title),
+    STYLE,
+    body,
+  )
+}
+
+fn account_path(account_id: &AccountId) -> String {
+  format!("account-{}.html", file_safe(account_id))
+}
+
+fn transaction_path(transaction_id: &TransactionId) -> String {
+  format!("transaction-{}.html", file_safe(transaction_id))
+}
+
+fn file_safe(id: &str) -> String {
+  id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect()
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{account_change::AccountChange, transaction::Transaction};
+
+  fn dollars() -> HashMap<CurrencyId, Currency> {
+    let dollars = Currency { id: "usd".to_string(), ..Currency::new("Dollars".to_string(), "$".to_string()) };
+    HashMap::from([(dollars.id.clone(), dollars)])
+  }
+
+  #[test]
+  fn escapes_markup_characters() {
+    assert_eq!(escape("<a href=\"x\">Tom & Jerry's</a>"), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+    assert_eq!(escape("&amp;"), "&amp;amp;");
+  }
+
+  #[test]
+  fn tables_mark_amount_columns_and_total_rows() {
+    let mut table = Table::new("Cash <Flow>".to_string(), &["Account", "Note", "Amount"]);
+    table.rows.push(vec![
+      Cell::Text("Food & Drink".to_string()),
+      Cell::Empty,
+      Cell::Amount(-12.5, "usd".to_string()),
+    ]);
+    table.rows.push(vec![Cell::Text("Total".to_string()), Cell::Empty, Cell::Amount(-12.5, "usd".to_string())]);
+    let html = table_html(&table, &dollars());
+    assert_eq!(html, concat!(
+      "<h2>Cash &lt;Flow&gt;</h2>\n<table>\n",
+      "<tr><th>Account</th><th>Note</th><th class=\"amount\">Amount</th></tr>\n",
+      "<tr><td>Food &amp; Drink</td><td></td><td class=\"amount\">-$12.50</td></tr>\n",
+      "<tr class=\"total\"><td>Total</td><td></td><td class=\"amount\">-$12.50</td></tr>\n",
+      "</table>\n",
+    ));
+  }
+
+  #[test]
+  fn transaction_pages_escape_names_and_total_each_side() {
+    let mut journal = Journal::new("Home".to_string());
+    let food = journal.find_or_create_account("<Food>", AccountType::Expense);
+    let cash = journal.find_or_create_account("Cash", AccountType::Asset);
+    let mut transaction = Transaction::new("2024/1/2".to_string(), "<script>".to_string(), "A & B".to_string());
+    transaction.add_account_change(AccountChange::new(food.clone(), "usd".to_string(), BalanceType::Debit, 5.0));
+    transaction.add_account_change(AccountChange::new(cash, "usd".to_string(), BalanceType::Credit, 5.0));
+    let transaction_id = transaction.id.clone();
+    journal.transactions.insert(transaction_id.clone(), transaction);
+
+    let page = transaction_page(&journal, &dollars(), &transaction_id);
+    assert!(page.contains("<title>&lt;script&gt;</title>"));
+    assert!(page.contains("<h1>&lt;script&gt;</h1>\n<p>2024-01-02</p>\n<p>A &amp; B</p>"));
+    assert!(!page.contains("<script>"));
+    assert!(page.contains(&format!(
+      "<tr><td><a href=\"{}\">&lt;Food&gt;</a></td><td class=\"amount\">$5.00</td><td class=\"amount\"></td></tr>",
+      account_path(&food)
+    )));
+    assert!(page.contains(
+      "<tr class=\"total\"><td>Total</td><td class=\"amount\">$5.00</td><td class=\"amount\">$5.00</td></tr>"
+    ));
+  }
+}
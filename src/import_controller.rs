@@ -0,0 +1,171 @@
+use std::{fs, collections::HashMap, path::Path};
+use crate::{
+  currency::{Currency, CurrencyId},
+  duplicate_controller::review_duplicates,
+  journal::{Journal, JournalId},
+  gnucash::import_gnucash,
+  import_rule::pattern_errors,
+  ledger::import_ledger,
+  page::Navigation,
+  qif::import_qif,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    select_with_labels,
+    input_default,
+  },
+};
+
+pub fn import_new_journal(
+  currency_id: &CurrencyId,
+  currencies: &mut HashMap<CurrencyId, Currency>,
+  journals: &mut HashMap<JournalId, Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Format {
+    Ledger,
+    GnuCash,
+    Back,
+  }
+
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Format::Back),
+    LabeledItem::from("Ledger / hledger / beancount".to_string(), Format::Ledger),
+    LabeledItem::from("GnuCash XML".to_string(), Format::GnuCash),
+  ];
+  let format = match select_with_labels("Import Format".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(format) => format,
+  };
+  let default_path = match format {
+    Format::Back => return navigation.back(),
+    Format::Ledger => "import.journal",
+    Format::GnuCash => "import.gnucash",
+  };
+
+  let path = match input_default("File Path:".to_string(), default_path.to_string()) {
+    Err(error) => return on_error(navigation, error),
+    Ok(path) => path,
+  };
+  let bytes = match fs::read(&path) {
+    Err(error) => {
+      println!("Import failed. {}", error);
+      return navigation.back();
+    },
+    Ok(bytes) => bytes,
+  };
+
+  let name = {
+    let default_name = Path::new(&path)
+      .file_stem()
+      .map(|stem| stem.to_string_lossy().to_string())
+      .unwrap_or_else(|| format!("Journal {}", journals.len()));
+    match input_default("Journal Name:".to_string(), default_name) {
+      Err(error) => return on_error(navigation, error),
+      Ok(name) => name,
+    }
+  };
+
+  let result = match format {
+    Format::Back => return navigation.back(),
+    Format::Ledger => import_ledger(
+      name, 
+      &String::from_utf8_lossy(&bytes), 
+      currencies, 
+      currency_id
+    ),
+    Format::GnuCash => import_gnucash(name, &bytes, currencies),
+  };
+
+  match result {
+    Err(error) => println!("Import failed. {}", error),
+    Ok(mut journal) => {
+      println!(
+        "Imported {} accounts and {} transactions", 
+        journal.accounts.len(), 
+        journal.transactions.len()
+      );
+      let review = review_duplicates(currencies, &mut journal, None);
+      journals.insert(journal.id.clone(), journal);
+      if let Err(error) = review {
+        return on_error(navigation, error);
+      }
+    },
+  }
+  navigation.back();
+}
+
+pub fn import_into_journal(
+  currency_id: &CurrencyId,
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  enum Format {
+    Qif,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Format::Back),
+    LabeledItem::from("QIF".to_string(), Format::Qif),
+  ];
+  let format = match select_with_labels("Import Format".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(format) => format,
+  };
+
+  match format {
+    Format::Back => navigation.back(),
+    Format::Qif => {
+      let source = match read_source("import.qif") {
+        Err(error) => return on_error(navigation, error),
+        Ok(source) => source,
+      };
+
+      let account_id = {
+        let mut options = journal.accounts
+          .keys()
+          .map(|id| LabeledItem::from(
+            journal.account_full_name(id).unwrap_or_default(), 
+            Some(id.clone())
+          ))
+          .collect::<Vec<_>>();
+        options.sort_by(|a, b| a.label.cmp(&b.label));
+        options.insert(0, LabeledItem::from(
+          "[Accounts Named In File]".to_string(), 
+          None
+        ));
+        match select_with_labels("Import Into Account".to_string(), &options) {
+          Err(error) => return on_error(navigation, error),
+          Ok(account_id) => account_id.clone(),
+        }
+      };
+
+      match import_qif(journal, &source, account_id.as_ref(), currency_id) {
+        Err(error) => println!("Import failed. {}", error),
+        Ok(transaction_ids) => {
+          println!("Imported {} transactions", transaction_ids.len());
+          for error in pattern_errors(&journal.ordered_rules()) {
+            println!("{}", error);
+          }
+          if let Err(error) = review_duplicates(currencies, journal, Some(&transaction_ids)) {
+            return on_error(navigation, error);
+          }
+        },
+      }
+      navigation.back();
+    },
+  }
+}
+
+fn read_source(default_path: &str) -> Result<String, std::io::Error> {
+  let path = input_default("File Path:".to_string(), default_path.to_string())?;
+  fs::read_to_string(path)
+}
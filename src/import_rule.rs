@@ -0,0 +1,387 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::CurrencyId,
+  journal::Journal,
+  payee::PayeeId,
+  transaction::{Transaction, TransactionId},
+};
+
+pub type ImportRuleId = String;
+
+/// Share of the counter postings a rule moves to one account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSplit {
+  pub account_id: AccountId,
+  /// Percentage of the amount, the last split taking whatever remains.
+  pub percent: f64,
+}
+
+/// Categorizes imported transactions: when every condition that is set
+/// holds, the actions that are set are applied.
+///
+/// The counter postings are the ones not posted to the source account, or
+/// for rules without one, the postings to revenue, expense and equity
+/// accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRule {
+  pub id: ImportRuleId,
+  pub name: String,
+  /// Rules are tried from the lowest priority up; the first match applies.
+  pub priority: i32,
+
+  /// Regular expression searched for in the name and description,
+  /// ignoring case.
+  pub pattern: Option<String>,
+  /// Bounds on the transaction's amount, both included: the net amount
+  /// posted to the source account, or without one, the total debited.
+  pub min_amount: Option<f64>,
+  pub max_amount: Option<f64>,
+  pub payee_id: Option<PayeeId>,
+  /// Account the statement was imported into.
+  pub source_account_id: Option<AccountId>,
+
+  /// Counter-accounts replacing the counter postings. A single split
+  /// recategorizes, several divide the amount between them.
+  pub splits: Vec<RuleSplit>,
+  /// New transaction name, linking the transaction to the payee of that
+  /// name if there is one.
+  pub rename: Option<String>,
+  pub tags: Vec<String>,
+}
+
+impl ImportRule {
+  pub fn new(name: String) -> ImportRule {
+    ImportRule {
+      id: Uuid::new_v4().to_string(),
+      name,
+      priority: 0,
+      pattern: None,
+      min_amount: None,
+      max_amount: None,
+      payee_id: None,
+      source_account_id: None,
+      splits: Vec::new(),
+      rename: None,
+      tags: Vec::new(),
+    }
+  }
+
+  pub fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+  }
+
+  /// Why the stored pattern does not compile, if it does not. Such rules
+  /// never match.
+  pub fn pattern_error(&self) -> Option<String> {
+    let error = ImportRule::compile_pattern(self.pattern.as_deref()?).err()?;
+    Some(format!("Rule \"{}\" has an invalid pattern and was skipped. {}", self.name, error))
+  }
+
+  fn matches(&self, pattern: Option<&Regex>, journal: &Journal, transaction: &Transaction) -> bool {
+    if self.pattern.is_some() && pattern.is_none() {
+      return false;
+    }
+    let amount: f64 = match &self.source_account_id {
+      Some(source_id) => transaction.account_changes
+        .values()
+        .filter(|c| &c.account_id == source_id)
+        .fold(0.0, |total, c| total + c.signed_amount(BalanceType::Debit))
+        .abs(),
+      None => transaction.account_changes
+        .values()
+        .filter(|c| c.credit_or_debit == BalanceType::Debit)
+        .map(|c| c.amount)
+        .sum(),
+    };
+    let payee = self.payee_id.as_ref().map(|id| journal.payees.get(id));
+
+    pattern.is_none_or(|p| p.is_match(&transaction.name) || p.is_match(&transaction.description))
+      && self.min_amount.is_none_or(|min| amount >= min)
+      && self.max_amount.is_none_or(|max| amount <= max)
+      && payee.is_none_or(|payee| payee.is_some_and(|p| {
+        transaction.payee_id.as_ref() == Some(&p.id) || p.is_called(&transaction.name)
+      }))
+      && self.source_account_id.as_ref().is_none_or(|id| {
+        transaction.account_changes.values().any(|c| &c.account_id == id)
+      })
+  }
+
+  fn is_counter(&self, journal: &Journal, change: &AccountChange) -> bool {
+    match &self.source_account_id {
+      Some(source_id) => &change.account_id != source_id,
+      None => journal.accounts.get(&change.account_id).is_some_and(|a| matches!(
+        a.account_type,
+        AccountType::Revenue | AccountType::Expense | AccountType::Equity
+      )),
+    }
+  }
+
+  /// `transaction` with the actions applied.
+  fn apply(&self, journal: &Journal, transaction: &Transaction) -> Transaction {
+    let mut result = transaction.clone();
+
+    if let Some(name) = &self.rename {
+      match journal.find_payee(name) {
+        Some(payee) => {
+          result.name = payee.name.clone();
+          result.payee_id = Some(payee.id.clone());
+        },
+        None => result.name = name.clone(),
+      }
+    }
+    for tag in &self.tags {
+      result.add_tag(tag);
+    }
+
+    let splits = self.splits
+      .iter()
+      .filter(|s| journal.accounts.contains_key(&s.account_id))
+      .collect::<Vec<_>>();
+    if splits.is_empty() {
+      return result;
+    }
+    // Total the counter postings by currency and side, then divide each
+    // total between the split accounts
+    let mut totals: Vec<((CurrencyId, BalanceType), f64)> = Vec::new();
+    let mut counters = transaction.account_changes
+      .values()
+      .filter(|c| self.is_counter(journal, c))
+      .collect::<Vec<_>>();
+    counters.sort_by(|a, b| a.id.cmp(&b.id));
+    for change in counters {
+      result.account_changes.remove(&change.id);
+      let key = (change.currency_id.clone(), change.credit_or_debit);
+      match totals.iter_mut().find(|(k, _)| k == &key) {
+        Some((_, total)) => *total += change.amount,
+        None => totals.push((key, change.amount)),
+      }
+    }
+    for ((currency_id, side), total) in totals {
+      let mut remaining = total;
+      for (index, split) in splits.iter().enumerate() {
+        let amount = match index == splits.len() - 1 {
+          true => remaining,
+          false => (total * split.percent / 100.0 * 100.0).round() / 100.0,
+        };
+        remaining -= amount;
+        if amount.abs() >= 0.005 {
+          result.add_account_change(AccountChange::new(
+            split.account_id.clone(),
+            currency_id.clone(),
+            side,
+            amount
+          ));
+        }
+      }
+    }
+    result
+  }
+}
+
+/// Messages for the rules in `rules` whose patterns do not compile.
+pub fn pattern_errors(rules: &[&ImportRule]) -> Vec<String> {
+  rules.iter().filter_map(|r| r.pattern_error()).collect()
+}
+
+/// A transaction before and after the rule that matched it.
+pub struct RuleMatch {
+  pub rule_id: ImportRuleId,
+  pub before: Transaction,
+  pub after: Transaction,
+}
+
+impl Journal {
+  /// Rules in the order they are tried.
+  pub fn ordered_rules(&self) -> Vec<&ImportRule> {
+    let mut rules = self.import_rules.values().collect::<Vec<_>>();
+    rules.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.name.cmp(&b.name)));
+    rules
+  }
+
+  /// What the first matching rule of `rules` would do to each of
+  /// `transaction_ids`, without changing anything.
+  pub fn preview_rules(&self, rules: &[&ImportRule], transaction_ids: &[TransactionId]) -> Vec<RuleMatch> {
+    let compiled = rules
+      .iter()
+      .map(|r| (r, r.pattern.as_deref().and_then(|p| ImportRule::compile_pattern(p).ok())))
+      .collect::<Vec<_>>();
+    let mut matches = transaction_ids
+      .iter()
+      .filter_map(|id| self.transactions.get(id))
+      .filter_map(|t| {
+        let (rule, _) = compiled.iter().find(|(r, p)| r.matches(p.as_ref(), self, t))?;
+        Some(RuleMatch {
+          rule_id: rule.id.clone(),
+          before: t.clone(),
+          after: rule.apply(self, t),
+        })
+      })
+      .collect::<Vec<_>>();
+    matches.sort_by_key(|m| (m.before.parsed_date(), m.before.name.clone()));
+    matches
+  }
+
+  /// Applies the journal's rules to `transaction_ids`, returning how many
+  /// transactions a rule matched.
+  pub fn apply_rules(&mut self, transaction_ids: &[TransactionId]) -> usize {
+    let matches = self.preview_rules(&self.ordered_rules(), transaction_ids);
+    let count = matches.len();
+    self.apply_matches(matches);
+    count
+  }
+
+  pub fn apply_matches(&mut self, matches: Vec<RuleMatch>) {
+    for rule_match in matches {
+      self.transactions.insert(rule_match.after.id.clone(), rule_match.after);
+    }
+  }
+}
+
+/// Changes a rule made, e.g. `Uncategorized -> Expenses:Food, +coffee`.
+pub fn describe_match(journal: &Journal, rule_match: &RuleMatch) -> String {
+  let accounts = |t: &Transaction| {
+    let mut names = t.account_changes
+      .values()
+      .filter_map(|c| journal.account_full_name(&c.account_id))
+      .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names.join(", ")
+  };
+  let (before, after) = (&rule_match.before, &rule_match.after);
+  let mut parts = Vec::new();
+  if before.name != after.name {
+    parts.push(format!("\"{}\" -> \"{}\"", before.name, after.name));
+  }
+  let (before_accounts, after_accounts) = (accounts(before), accounts(after));
+  if before_accounts != after_accounts {
+    parts.push(format!("{} -> {}", before_accounts, after_accounts));
+  }
+  parts.extend(after.tags.iter().filter(|t| !before.tags.contains(t)).map(|t| format!("+{}", t)));
+  match parts.is_empty() {
+    true => "No change".to_string(),
+    false => parts.join(", "),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A journal with one imported transaction moving `amount` from checking
+  /// to an uncategorized expense.
+  fn imported(amount: f64) -> (Journal, TransactionId) {
+    let mut journal = Journal::new("Test".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let uncategorized = journal.find_or_create_account("Uncategorized", AccountType::Expense);
+    for name in ["Food", "Fuel", "Gifts"] {
+      journal.find_or_create_account(name, AccountType::Expense);
+    }
+    let mut transaction = Transaction::new("2024/3/1".to_string(), "MARKET 42".to_string(), String::new());
+    for (account_id, side) in [(uncategorized, BalanceType::Debit), (checking, BalanceType::Credit)] {
+      transaction.add_account_change(AccountChange::new(account_id, "usd".to_string(), side, amount));
+    }
+    let id = transaction.id.clone();
+    journal.transactions.insert(id.clone(), transaction);
+    (journal, id)
+  }
+
+  fn split_rule(journal: &Journal, splits: &[(&str, f64)]) -> ImportRule {
+    ImportRule {
+      splits: splits
+        .iter()
+        .map(|(name, percent)| RuleSplit {
+          account_id: journal.find_account(name).unwrap().clone(),
+          percent: *percent,
+        })
+        .collect(),
+      ..ImportRule::new("Market".to_string())
+    }
+  }
+
+  /// Debited accounts and amounts after applying `rule`.
+  fn debits(journal: &Journal, rule: &ImportRule, id: &TransactionId) -> Vec<(String, String)> {
+    let mut debits = journal.preview_rules(&[rule], std::slice::from_ref(id))
+      .pop()
+      .unwrap()
+      .after
+      .account_changes
+      .values()
+      .filter(|c| c.credit_or_debit == BalanceType::Debit)
+      .map(|c| (journal.account_full_name(&c.account_id).unwrap(), format!("{:.2}", c.amount)))
+      .collect::<Vec<_>>();
+    debits.sort();
+    debits
+  }
+
+  fn debit(account: &str, amount: &str) -> (String, String) {
+    (account.to_string(), amount.to_string())
+  }
+
+  #[test]
+  fn splits_divide_by_percent_with_the_remainder_last() {
+    let (journal, id) = imported(100.01);
+    let rule = split_rule(&journal, &[("Food", 60.0), ("Fuel", 40.0)]);
+    assert_eq!(debits(&journal, &rule, &id), vec![debit("Food", "60.01"), debit("Fuel", "40.00")]);
+
+    let (journal, id) = imported(100.0);
+    let rule = split_rule(&journal, &[("Food", 33.33), ("Fuel", 33.33), ("Gifts", 33.33)]);
+    assert_eq!(
+      debits(&journal, &rule, &id),
+      vec![debit("Food", "33.33"), debit("Fuel", "33.33"), debit("Gifts", "33.34")]
+    );
+  }
+
+  #[test]
+  fn a_single_split_recategorizes_and_balances() {
+    let (journal, id) = imported(12.5);
+    let rule = split_rule(&journal, &[("Food", 100.0)]);
+    assert_eq!(debits(&journal, &rule, &id), vec![debit("Food", "12.50")]);
+    let after = journal.preview_rules(&[&rule], &[id]).pop().unwrap().after;
+    assert!(after.is_balanced());
+  }
+
+  #[test]
+  fn remainder_posting_is_left_out_when_nothing_remains() {
+    let (journal, id) = imported(50.0);
+    let rule = split_rule(&journal, &[("Food", 100.0), ("Fuel", 0.0)]);
+    assert_eq!(debits(&journal, &rule, &id), vec![debit("Food", "50.00")]);
+  }
+
+  #[test]
+  fn amount_bounds_use_the_net_source_amount() {
+    let (journal, id) = imported(80.0);
+    let checking = journal.find_account("Checking").unwrap().clone();
+    let bounded = |min: f64, max: f64| ImportRule {
+      min_amount: Some(min),
+      max_amount: Some(max),
+      source_account_id: Some(checking.clone()),
+      ..split_rule(&journal, &[("Food", 100.0)])
+    };
+    assert_eq!(journal.preview_rules(&[&bounded(50.0, 80.0)], std::slice::from_ref(&id)).len(), 1);
+    assert!(journal.preview_rules(&[&bounded(81.0, 200.0)], std::slice::from_ref(&id)).is_empty());
+    assert!(journal.preview_rules(&[&bounded(0.0, 79.0)], &[id]).is_empty());
+  }
+
+  #[test]
+  fn invalid_patterns_never_match_and_are_reported() {
+    let (journal, id) = imported(10.0);
+    let with_pattern = |pattern: &str, account: &str| ImportRule {
+      pattern: Some(pattern.to_string()),
+      ..split_rule(&journal, &[(account, 100.0)])
+    };
+    let broken = with_pattern("market(", "Food");
+    let working = with_pattern("^market", "Fuel");
+    assert!(journal.preview_rules(&[&broken], std::slice::from_ref(&id)).is_empty());
+    assert_eq!(debits(&journal, &working, &id), vec![debit("Fuel", "10.00")]);
+
+    let errors = pattern_errors(&[&broken, &working]);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Rule \"Market\" has an invalid pattern"), "{}", errors[0]);
+    assert_eq!(working.pattern_error(), None);
+  }
+}
@@ -0,0 +1,304 @@
+use crate::{
+  account::AccountId,
+  import_rule::{describe_match, pattern_errors, ImportRule, ImportRuleId, RuleSplit},
+  journal::Journal,
+  page::Navigation,
+  report::account_label,
+  transaction::TransactionId,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_input_with_labels,
+    input_default,
+    input_optional_parsed,
+    input_until_number_with_validation,
+    input_until_parsed,
+    select_with_labels,
+  },
+};
+
+/// Lists the journal's import rules in the order they are tried.
+pub fn view_import_rules(journal: Option<&mut Journal>, navigation: &mut Navigation) {
+  enum Selection {
+    Rule(ImportRuleId),
+    NewRule,
+    RunAll,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = vec![LabeledItem::from("[Back]".to_string(), Selection::Back)];
+  options.extend(journal.ordered_rules()
+    .into_iter()
+    .map(|r| LabeledItem::from(format!("{}. {}", r.priority, r.name), Selection::Rule(r.id.clone())))
+  );
+  options.push(LabeledItem::from("[New Rule]".to_string(), Selection::NewRule));
+  if !journal.import_rules.is_empty() {
+    options.push(LabeledItem::from("[Run On All Transactions]".to_string(), Selection::RunAll));
+  }
+
+  let selection = match select_with_labels("Import Rules".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let result = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::NewRule => input_default("Rule Name:".to_string(), String::new())
+      .and_then(|name| edit_rule(journal, ImportRule::new(name))),
+    Selection::Rule(id) => match journal.import_rules.get(id) {
+      None => Ok(()),
+      Some(rule) => edit_rule(journal, rule.clone()),
+    },
+    Selection::RunAll => {
+      let rules = journal.ordered_rules();
+      preview_and_apply(journal, &rules.into_iter().cloned().collect::<Vec<_>>())
+    },
+  };
+  if let Err(error) = result {
+    on_error(navigation, error)
+  }
+}
+
+/// Edits a copy of `rule` one field at a time, storing it on save.
+fn edit_rule(journal: &mut Journal, mut rule: ImportRule) -> Result<(), std::io::Error> {
+  enum Selection {
+    Save,
+    Preview,
+    Name,
+    Priority,
+    Pattern,
+    MinAmount,
+    MaxAmount,
+    Payee,
+    Source,
+    Splits,
+    Rename,
+    Tags,
+    Delete,
+    Cancel,
+  }
+
+  loop {
+    let any = || "Any".to_string();
+    let none = || "None".to_string();
+    let splits = match rule.splits.is_empty() {
+      true => none(),
+      false => rule.splits
+        .iter()
+        .map(|s| format!("{} {}%", account_label(journal, &s.account_id), s.percent))
+        .collect::<Vec<_>>()
+        .join(", "),
+    };
+    let options = vec![
+      LabeledItem::from("[Save]".to_string(), Selection::Save),
+      LabeledItem::from("[Preview]".to_string(), Selection::Preview),
+      LabeledItem::from(format!("Name: {}", rule.name), Selection::Name),
+      LabeledItem::from(format!("Priority: {}", rule.priority), Selection::Priority),
+      LabeledItem::from(
+        format!("If Name Or Description Matches: {}", rule.pattern.clone().unwrap_or_else(any)),
+        Selection::Pattern
+      ),
+      LabeledItem::from(
+        format!("If Amount At Least: {}", rule.min_amount.map_or_else(any, |a| a.to_string())),
+        Selection::MinAmount
+      ),
+      LabeledItem::from(
+        format!("If Amount At Most: {}", rule.max_amount.map_or_else(any, |a| a.to_string())),
+        Selection::MaxAmount
+      ),
+      LabeledItem::from(
+        format!(
+          "If Payee: {}",
+          rule.payee_id.as_ref().and_then(|id| journal.payees.get(id)).map_or_else(any, |p| p.name.clone())
+        ),
+        Selection::Payee
+      ),
+      LabeledItem::from(
+        format!(
+          "If Source Account: {}",
+          rule.source_account_id.as_ref().map_or_else(any, |id| account_label(journal, id))
+        ),
+        Selection::Source
+      ),
+      LabeledItem::from(format!("Set Counter-Accounts: {}", splits), Selection::Splits),
+      LabeledItem::from(
+        format!("Rename To: {}", rule.rename.clone().unwrap_or_else(none)),
+        Selection::Rename
+      ),
+      LabeledItem::from(
+        format!(
+          "Add Tags: {}",
+          match rule.tags.is_empty() {
+            true => none(),
+            false => rule.tags.join(", "),
+          }
+        ),
+        Selection::Tags
+      ),
+      LabeledItem::from("[Delete]".to_string(), Selection::Delete),
+      LabeledItem::from("[Cancel]".to_string(), Selection::Cancel),
+    ];
+
+    match select_with_labels(rule.name.clone(), &options)? {
+      Selection::Cancel => return Ok(()),
+      Selection::Save => match rule.pattern_error() {
+        Some(error) => println!("{}", error),
+        None => {
+          journal.import_rules.insert(rule.id.clone(), rule);
+          return Ok(());
+        },
+      },
+      Selection::Delete => {
+        let prompt = format!("Are you sure you want to delete \"{}\"?", rule.name);
+        if confirm_default(prompt)? {
+          journal.import_rules.remove(&rule.id);
+          return Ok(());
+        }
+      },
+      Selection::Preview => preview_and_apply(journal, &[rule.clone()])?,
+      Selection::Name => rule.name = input_default("Rule Name:".to_string(), rule.name.clone())?,
+      Selection::Priority => rule.priority = input_until_parsed(
+        "Priority (lower runs first):".to_string(),
+        rule.priority.to_string()
+      )?,
+      Selection::Pattern => rule.pattern = input_pattern(rule.pattern.clone())?,
+      Selection::MinAmount => rule.min_amount = input_optional_parsed(
+        "Minimum Amount (empty for any):".to_string(),
+        rule.min_amount
+      )?,
+      Selection::MaxAmount => rule.max_amount = input_optional_parsed(
+        "Maximum Amount (empty for any):".to_string(),
+        rule.max_amount
+      )?,
+      Selection::Payee => {
+        let mut options = journal.payees
+          .values()
+          .map(|p| LabeledItem::from(p.label(), Some(p.id.clone())))
+          .collect::<Vec<_>>();
+        options.sort_by(|a, b| a.label.cmp(&b.label));
+        options.insert(0, LabeledItem::from("[Any]".to_string(), None));
+        rule.payee_id = fuzzy_input_with_labels("Payee:".to_string(), &options)?.clone();
+      },
+      Selection::Source => {
+        let mut options = account_options(journal);
+        options.insert(0, LabeledItem::from("[Any]".to_string(), None));
+        rule.source_account_id = fuzzy_input_with_labels("Source Account:".to_string(), &options)?.clone();
+      },
+      Selection::Splits => rule.splits = input_splits(journal)?,
+      Selection::Rename => rule.rename = input_optional_parsed(
+        "Rename To (empty to keep):".to_string(),
+        rule.rename.clone()
+      )?,
+      Selection::Tags => {
+        let tags = input_optional_parsed::<String>(
+          "Tags (comma separated, empty for none):".to_string(),
+          Some(rule.tags.join(", ")).filter(|tags| !tags.is_empty())
+        )?.unwrap_or_default();
+        rule.tags = tags
+          .split(',')
+          .map(|tag| tag.trim().to_string())
+          .filter(|tag| !tag.is_empty())
+          .collect();
+      },
+    }
+  }
+}
+
+/// Shows what `rules` would change in the existing transactions, then
+/// offers to make the changes.
+fn preview_and_apply(journal: &mut Journal, rules: &[ImportRule]) -> Result<(), std::io::Error> {
+  let rules = rules.iter().collect::<Vec<_>>();
+  for error in pattern_errors(&rules) {
+    println!("{}", error);
+  }
+  let transaction_ids = journal.transactions.keys().cloned().collect::<Vec<TransactionId>>();
+  let matches = journal.preview_rules(&rules, &transaction_ids);
+  if matches.is_empty() {
+    println!("No transactions match");
+    return Ok(());
+  }
+  for rule_match in &matches {
+    let rule_name = rules
+      .iter()
+      .find(|r| r.id == rule_match.rule_id)
+      .map(|r| r.name.as_str())
+      .unwrap_or_default();
+    println!(
+      "  {} {} [{}]: {}",
+      rule_match.before.date,
+      rule_match.before.name,
+      rule_name,
+      describe_match(journal, rule_match)
+    );
+  }
+  let prompt = format!("Apply to these {} transactions?", matches.len());
+  if confirm_default(prompt)? {
+    journal.apply_matches(matches);
+  }
+  Ok(())
+}
+
+/// Asks for a regular expression until it compiles. Empty input matches
+/// any text.
+fn input_pattern(current: Option<String>) -> Result<Option<String>, std::io::Error> {
+  loop {
+    let pattern = input_optional_parsed::<String>(
+      "Regular Expression (e.g. coffee|cafe, empty for any):".to_string(),
+      current.clone()
+    )?;
+    match pattern.as_deref().map(ImportRule::compile_pattern) {
+      Some(Err(error)) => println!("{}", error),
+      _ => return Ok(pattern),
+    }
+  }
+}
+
+/// Asks for the counter-accounts and the percentage each one takes, the
+/// last one taking whatever remains.
+fn input_splits(journal: &Journal) -> Result<Vec<RuleSplit>, std::io::Error> {
+  let mut options = account_options(journal);
+  options.insert(0, LabeledItem::from(String::new(), None));
+
+  let mut splits: Vec<RuleSplit> = Vec::new();
+  let mut remaining = 100.0;
+  loop {
+    let prompt = match splits.is_empty() {
+      true => "Counter-Account:".to_string(),
+      false => format!("Counter-Account For The Remaining {}%:", remaining),
+    };
+    options[0].label = match splits.is_empty() {
+      true => "[None]".to_string(),
+      false => "[Done, Last Takes The Rest]".to_string(),
+    };
+    let account_id = match fuzzy_input_with_labels(prompt, &options)? {
+      None => return Ok(splits),
+      Some(account_id) => account_id.clone(),
+    };
+    let percent: f64 = input_until_number_with_validation(
+      "Percent Of The Amount:".to_string(),
+      remaining.to_string(),
+      &|percent: f64| percent > 0.0 && percent <= remaining
+    )?;
+    remaining -= percent;
+    splits.push(RuleSplit { account_id, percent });
+    if remaining <= 0.0 {
+      return Ok(splits);
+    }
+  }
+}
+
+fn account_options(journal: &Journal) -> Vec<LabeledItem<Option<AccountId>>> {
+  let mut options = journal.accounts
+    .keys()
+    .map(|id| LabeledItem::from(account_label(journal, id), Some(id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options
+}
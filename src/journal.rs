@@ -2,18 +2,48 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use crate::{
-  account::{Account, AccountId},
+  account::{Account, AccountId, AccountType},
+  budget::{Budget, BudgetId},
+  duplicate::default_window_days,
+  filter::{FilterId, SavedFilter},
+  import_rule::{ImportRule, ImportRuleId},
+  payee::{Payee, PayeeId},
+  recurring::{RecurringId, RecurringTransaction},
+  template::{TemplateId, TransactionTemplate},
   transaction::{Transaction, TransactionId},
 };
 
 pub type JournalId = String;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Separator used between the segments of an account's full name,
+/// e.g. `Expenses:Food:Groceries`.
+pub const ACCOUNT_SEPARATOR: char = ':';
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Journal {
   pub id: JournalId,
   pub name: String,
   pub accounts: HashMap<AccountId, Account>,
   pub transactions: HashMap<TransactionId, Transaction>,
+  #[serde(default)]
+  pub budgets: HashMap<BudgetId, Budget>,
+  #[serde(default)]
+  pub recurring: HashMap<RecurringId, RecurringTransaction>,
+  #[serde(default)]
+  pub templates: HashMap<TemplateId, TransactionTemplate>,
+  #[serde(default)]
+  pub filters: HashMap<FilterId, SavedFilter>,
+  #[serde(default)]
+  pub payees: HashMap<PayeeId, Payee>,
+  #[serde(default)]
+  pub import_rules: HashMap<ImportRuleId, ImportRule>,
+  /// How many days apart transactions with the same postings may be to be
+  /// reported as duplicates.
+  #[serde(default = "default_window_days")]
+  pub duplicate_window_days: u32,
+  /// Pairs reviewed and kept as separate transactions.
+  #[serde(default)]
+  pub distinct_pairs: Vec<(TransactionId, TransactionId)>,
 }
 
 impl Journal {
@@ -24,6 +54,86 @@ impl Journal {
       name,
       accounts: HashMap::new(),
       transactions: HashMap::new(),
+      budgets: HashMap::new(),
+      recurring: HashMap::new(),
+      templates: HashMap::new(),
+      filters: HashMap::new(),
+      payees: HashMap::new(),
+      import_rules: HashMap::new(),
+      duplicate_window_days: default_window_days(),
+      distinct_pairs: Vec::new(),
+    }
+  }
+
+  /// Name of the account prefixed by the names of its parents.
+  pub fn account_full_name(&self, account_id: &AccountId) -> Option<String> {
+    let mut account = self.accounts.get(account_id)?;
+    let mut segments = vec![account.name.clone()];
+    while let Some(parent) = account.parent_id
+      .as_ref()
+      .and_then(|id| self.accounts.get(id)) 
+    {
+      // Guard against parent cycles in hand edited data
+      if segments.len() > self.accounts.len() {
+        break;
+      }
+      segments.push(parent.name.clone());
+      account = parent;
+    }
+    segments.reverse();
+    Some(segments.join(&ACCOUNT_SEPARATOR.to_string()))
+  }
+
+  /// Whether `account_id` is `ancestor_id` or one of its sub-accounts.
+  pub fn is_within(&self, account_id: &AccountId, ancestor_id: &AccountId) -> bool {
+    let mut current = Some(account_id);
+    for _ in 0..=self.accounts.len() {
+      match current {
+        None => return false,
+        Some(id) if id == ancestor_id => return true,
+        Some(id) => current = self.accounts.get(id).and_then(|a| a.parent_id.as_ref()),
+      }
+    }
+    false
+  }
+
+  pub fn find_account(&self, full_name: &str) -> Option<&AccountId> {
+    self.accounts
+      .keys()
+      .find(|id| self.account_full_name(id).as_deref() == Some(full_name))
+  }
+
+  /// Looks up an account by full name, creating it and any missing parents 
+  /// with the normal balance of `account_type`.
+  pub fn find_or_create_account(
+    &mut self, 
+    full_name: &str, 
+    account_type: AccountType
+  ) -> AccountId {
+    let mut parent_id: Option<AccountId> = None;
+    let mut path = String::new();
+    for segment in full_name.split(ACCOUNT_SEPARATOR).map(str::trim) {
+      if !path.is_empty() {
+        path.push(ACCOUNT_SEPARATOR);
+      }
+      path.push_str(segment);
+
+      let id = match self.find_account(&path) {
+        Some(id) => id.clone(),
+        None => {
+          let mut account = Account::new(
+            segment.to_string(), 
+            account_type.normal_balance(), 
+            account_type
+          );
+          account.parent_id = parent_id.clone();
+          let id = account.id.clone();
+          self.accounts.insert(id.clone(), account);
+          id
+        }
+      };
+      parent_id = Some(id);
     }
+    parent_id.unwrap_or_default()
   }
 }
@@ -1,6 +1,8 @@
 use crate::{
+  currency::{Currency, CurrencyId},
+  display::journal_details,
   journal::{Journal, JournalId},
-  page::Page,
+  page::{Navigation, Page},
   utility::{
     on_error, 
     on_not_found,
@@ -11,32 +13,30 @@ use crate::{
 use dialoguer::{theme::ColorfulTheme, Confirm, Input};
 use std::collections::HashMap;
 
-pub fn new_journal<'a>(journals: &'a mut HashMap<JournalId, Journal>, page: &mut Page) {
+pub fn new_journal(journals: &mut HashMap<JournalId, Journal>, navigation: &mut Navigation) {
   let name = match Input::with_theme(&ColorfulTheme::default())
     .with_prompt("Journal Name:")
     .with_initial_text(format!("Journal {}", journals.len()))
     .interact()
   {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(name) => name,
   };
 
   let journal = Journal::new(name);
   journals.insert(journal.id.clone(), journal);
-  *page = Page::SelectJournal;
+  navigation.back();
 }
 
-pub fn select_journal<'a>(
-  journals: &'a HashMap<JournalId, Journal>,
-  journal_id: &mut Option<JournalId>,
-  page: &mut Page,
-  terminate_signal: &mut bool,
+pub fn select_journal(
+  journals: &HashMap<JournalId, Journal>,
+  navigation: &mut Navigation,
 ) {
-  *journal_id = None;
-
   enum Selection<'a> {
     Journal(&'a JournalId),
     NewJournal,
+    ImportJournal,
+    Currencies,
     Quit,
   }
 
@@ -47,41 +47,59 @@ pub fn select_journal<'a>(
   options.sort_by(|a, b| a.label.cmp(&b.label));
   options.extend(vec![
     LabeledItem::from("[New Journal]".to_string(), Selection::NewJournal),
+    LabeledItem::from("[Import Journal]".to_string(), Selection::ImportJournal),
+    LabeledItem::from("[Currencies]".to_string(), Selection::Currencies),
     LabeledItem::from("[Quit]".to_string(), Selection::Quit),
   ]);
   let options = options;
 
   let selection = match select_with_labels("Select Journal".to_string(), &options) {
     Ok(selection) => selection,
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
   };
 
   match *selection {
     Selection::Journal(id) => {
       match journals.get(id) {
-        None => return on_not_found(page),
-        Some(journal) => {
-          *journal_id = Some(journal.id.clone());
-          *page = Page::ViewJournal;
-        }
-      };
+        None => on_not_found(navigation),
+        Some(journal) => navigation.open_journal(journal.id.clone()),
+      }
     }
     Selection::NewJournal => {
-      *page = Page::NewJournal;
+      navigation.go(Page::NewJournal);
+    }
+    Selection::ImportJournal => {
+      navigation.go(Page::ImportNewJournal);
+    }
+    Selection::Currencies => {
+      navigation.go(Page::Currencies);
     }
     Selection::Quit => {
-      *terminate_signal = true;
+      navigation.terminate_signal = true;
     }
-  };
+  }
 }
 
-pub fn view_journal<'a>(journal: Option<&'a mut Journal>, page: &mut Page) {
+pub fn view_journal(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
   enum Selection {
     Display,
     Delete,
     Back,
     Accounts,
     Transactions,
+    Import,
+    Export,
+    Reports,
+    Charts,
+    Budgets,
+    Recurring,
+    Payees,
+    ImportRules,
+    Duplicates,
   }
 
   let options = vec![
@@ -89,48 +107,84 @@ pub fn view_journal<'a>(journal: Option<&'a mut Journal>, page: &mut Page) {
     LabeledItem::from("[Accounts]".to_string(), Selection::Accounts),
     LabeledItem::from("[Display]".to_string(), Selection::Display),
     LabeledItem::from("[Transactions]".to_string(), Selection::Transactions),
+    LabeledItem::from("[Reports]".to_string(), Selection::Reports),
+    LabeledItem::from("[Charts]".to_string(), Selection::Charts),
+    LabeledItem::from("[Budgets]".to_string(), Selection::Budgets),
+    LabeledItem::from("[Recurring]".to_string(), Selection::Recurring),
+    LabeledItem::from("[Payees]".to_string(), Selection::Payees),
+    LabeledItem::from("[Import]".to_string(), Selection::Import),
+    LabeledItem::from("[Import Rules]".to_string(), Selection::ImportRules),
+    LabeledItem::from("[Find Duplicates]".to_string(), Selection::Duplicates),
+    LabeledItem::from("[Export]".to_string(), Selection::Export),
     LabeledItem::from("[Delete]".to_string(), Selection::Delete),
   ];
 
   let journal = match journal {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(journal) => journal,
   };
 
   let selection = match select_with_labels(journal.name.clone(), &options) {
     Ok(selection) => selection,
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
   };
 
   match *selection {
-    Selection::Display => println!("{:#?}", journal),
+    Selection::Display => println!("{}", journal_details(journal, currencies)),
     Selection::Delete => {
-      *page = Page::DeleteJournal;
+      navigation.go(Page::DeleteJournal);
     }
     Selection::Back => {
-      *page = Page::SelectJournal;
+      navigation.back();
     }
     Selection::Accounts => {
-      *page = Page::SelectAccount;
+      navigation.go(Page::SelectAccount);
     }
     Selection::Transactions => {
-      *page = Page::SelectTransaction;
+      navigation.go(Page::SelectTransaction);
+    },
+    Selection::Import => {
+      navigation.go(Page::Import);
+    },
+    Selection::Export => {
+      navigation.go(Page::Export);
+    },
+    Selection::Reports => {
+      navigation.go(Page::Reports);
+    },
+    Selection::Charts => {
+      navigation.go(Page::Charts);
+    },
+    Selection::Budgets => {
+      navigation.go(Page::Budgets);
+    },
+    Selection::Recurring => {
+      navigation.go(Page::Recurring);
+    },
+    Selection::Payees => {
+      navigation.go(Page::Payees);
+    },
+    Selection::ImportRules => {
+      navigation.go(Page::ImportRules);
+    },
+    Selection::Duplicates => {
+      navigation.go(Page::Duplicates);
     },
   }
 }
 
-pub fn delete_journal<'a>(
-  journals: &'a mut HashMap<JournalId, Journal>,
+pub fn delete_journal(
+  journals: &mut HashMap<JournalId, Journal>,
   journal_id: &Option<JournalId>,
-  page: &mut Page,
+  navigation: &mut Navigation,
 ) {
   let journal_id = match journal_id {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(id) => id,
   };
 
   let name = match journals.get(journal_id) {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(journal) => journal.name.clone(),
   };
 
@@ -139,15 +193,15 @@ pub fn delete_journal<'a>(
     .default(false)
     .interact()
   {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(should_delete) => should_delete,
   };
 
   match should_delete {
-    false => *page = Page::ViewJournal,
+    false => navigation.back(),
     true => {
       journals.remove(journal_id);
-      *page = Page::SelectJournal;
+      navigation.back_to(Page::SelectJournal);
     }
   }
 }
@@ -0,0 +1,848 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::{Journal, ACCOUNT_SEPARATOR},
+  parse_error::ParseError,
+  transaction::Transaction,
+};
+
+/// Writes `journal` in the plain text format shared by ledger-cli and
+/// hledger. Transactions are ordered by date, use their name as the payee
+/// and carry their description as a comment. Debits are positive postings
+/// and credits negative, so every balanced transaction balances in ledger.
+pub fn export_ledger(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+) -> String {
+  let mut output = format!("; {}\n\n", journal.name);
+
+  let mut symbols = journal.transactions
+    .values()
+    .flat_map(|t| t.account_changes.values())
+    .filter_map(|c| currencies.get(&c.currency_id))
+    .map(|c| c.symbol.clone())
+    .collect::<Vec<_>>();
+  symbols.sort();
+  symbols.dedup();
+  for symbol in &symbols {
+    output.push_str(&format!("commodity {}\n", commodity(symbol)));
+  }
+  if !symbols.is_empty() {
+    output.push('\n');
+  }
+
+  let mut accounts = journal.accounts
+    .values()
+    .filter_map(|a| Some((account_name(&journal.account_full_name(&a.id)?), a)))
+    .collect::<Vec<_>>();
+  accounts.sort_by(|a, b| a.0.cmp(&b.0));
+  for (name, account) in &accounts {
+    let tag = match account.is_cash {
+      true => "C",
+      false => type_tag(account.account_type),
+    };
+    output.push_str(&format!("account {}  ; type:{}\n", name, tag));
+  }
+  if !accounts.is_empty() {
+    output.push('\n');
+  }
+
+  let mut transactions = journal.transactions.values().collect::<Vec<_>>();
+  transactions.sort_by(|a, b| {
+    (a.parsed_date(), &a.name).cmp(&(b.parsed_date(), &b.name))
+  });
+
+  for transaction in transactions {
+    let date = match transaction.parsed_date() {
+      None => {
+        output.push_str(&format!(
+          "; Skipped \"{}\", invalid date \"{}\"\n\n", 
+          transaction.name, 
+          transaction.date
+        ));
+        continue;
+      },
+      Some(date) => date,
+    };
+
+    output.push_str(&format!("{} {}\n", date.format("%Y-%m-%d"), single_line(&transaction.name)));
+    for line in transaction.description.lines().filter(|l| !l.trim().is_empty()) {
+      output.push_str(&format!("    ; {}\n", line.trim()));
+    }
+
+    let mut postings = transaction.account_changes
+      .values()
+      .map(|change| {
+        let account = journal
+          .account_full_name(&change.account_id)
+          .map(|name| account_name(&name))
+          .unwrap_or_else(|| "Unknown".to_string());
+        let amount = change.signed_amount(BalanceType::Debit);
+        let amount = match currencies.get(&change.currency_id) {
+          Some(currency) => format_amount(amount, currency),
+          None => format!("{:.2}", amount),
+        };
+        (change.credit_or_debit == BalanceType::Credit, account, amount)
+      })
+      .collect::<Vec<_>>();
+    // Debits first, matching the usual ledger layout
+    postings.sort();
+    for (_, account, amount) in postings {
+      output.push_str(&format!("    {}  {}\n", account, amount));
+    }
+    output.push('\n');
+  }
+
+  output
+}
+
+/// Formats an amount the way ledger expects: single character symbols such
+/// as `$` are prefixed as in the rest of the app, while codes and single
+/// letters follow the number as a (quoted if needed) commodity.
+pub fn format_amount(amount: f64, currency: &Currency) -> String {
+  let symbol = currency.symbol.as_str();
+  match symbol.chars().count() == 1 && !symbol.chars().all(char::is_alphabetic) {
+    true => currency.format_amount(amount),
+    false => format!("{} {}", currency.format_number(amount), commodity(symbol)),
+  }
+}
+
+/// hledger's single letter account type codes.
+fn type_tag(account_type: AccountType) -> &'static str {
+  match account_type {
+    AccountType::Asset => "A",
+    AccountType::Liability => "L",
+    AccountType::Equity => "E",
+    AccountType::Revenue => "R",
+    AccountType::Expense => "X",
+  }
+}
+
+/// Quotes commodity symbols containing anything other than letters.
+fn commodity(symbol: &str) -> String {
+  let is_simple = symbol.chars().all(char::is_alphabetic) 
+    || (symbol.chars().count() == 1 && !symbol.chars().any(|c| c.is_ascii_digit() || c == '-'));
+  match is_simple {
+    true => symbol.to_string(),
+    false => format!("\"{}\"", symbol.replace('"', "")),
+  }
+}
+
+/// Ledger ends an account name at two consecutive spaces or a tab.
+fn account_name(name: &str) -> String {
+  single_line(name)
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn single_line(text: &str) -> String {
+  text.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+/// A posting as written in the source file, before elided amounts are filled.
+struct ParsedPosting {
+  account_id: AccountId,
+  amount: Option<(f64, CurrencyId)>,
+}
+
+struct PendingTransaction {
+  line: usize,
+  transaction: Transaction,
+  postings: Vec<ParsedPosting>,
+  has_price: bool,
+}
+
+/// Parses the common subset of ledger-cli, hledger and beancount syntax into
+/// a new journal: account declarations (`account`, `open`), dated
+/// transactions, postings with amounts and commodities, one elided amount
+/// per transaction and comments. Accounts are created as they are first
+/// seen, with their type taken from an hledger `type:` tag or the root of
+/// their name. Commodities without a matching `Currency` symbol are added to
+/// `currencies`; bare numbers use `default_currency_id`.
+pub fn import_ledger(
+  name: String,
+  source: &str,
+  currencies: &mut HashMap<CurrencyId, Currency>,
+  default_currency_id: &CurrencyId,
+) -> Result<Journal, ParseError> {
+  let mut journal = Journal::new(name);
+  let mut new_currencies: HashMap<CurrencyId, Currency> = HashMap::new();
+  // Most decimal places written for each new currency
+  let mut places: HashMap<CurrencyId, usize> = HashMap::new();
+  let mut pending: Option<PendingTransaction> = None;
+  let mut in_comment_block = false;
+  let mut in_directive_block = false;
+
+  for (index, raw) in source.lines().enumerate() {
+    let line = index + 1;
+    let text = raw.trim_start_matches('\u{feff}').trim_end();
+    let trimmed = text.trim_start();
+    let is_indented = text.len() != trimmed.len();
+
+    if in_comment_block {
+      if trimmed == "end comment" || trimmed == "end test" {
+        in_comment_block = false;
+      }
+      continue;
+    }
+
+    if trimmed.is_empty() {
+      finish_transaction(&mut journal, &mut pending)?;
+      in_directive_block = false;
+      continue;
+    }
+
+    if is_indented {
+      if trimmed.starts_with(';') || trimmed.starts_with('#') {
+        if let Some(current) = pending.as_mut() {
+          append_line(
+            &mut current.transaction.description, 
+            trimmed.trim_start_matches([';', '#']).trim()
+          );
+        }
+        continue;
+      }
+      if let Some(current) = pending.as_mut() {
+        if is_metadata(trimmed) {
+          continue;
+        }
+        let mut lookup = |symbol: &str, decimals: usize| {
+          let id = currency_for(symbol, currencies, &mut new_currencies, default_currency_id);
+          record_places(&mut places, &new_currencies, &id, decimals);
+          id
+        };
+        let (posting, has_price) = parse_posting(&mut journal, trimmed, line, &mut lookup)?;
+        current.postings.push(posting);
+        current.has_price |= has_price;
+        continue;
+      }
+      if in_directive_block {
+        continue;
+      }
+      return Err(ParseError::new(line, "Indented line outside of a transaction"));
+    }
+
+    finish_transaction(&mut journal, &mut pending)?;
+    in_directive_block = false;
+
+    if let Some(first) = trimmed.chars().next() {
+      if matches!(first, ';' | '#' | '%' | '|' | '*') {
+        continue;
+      }
+    }
+
+    let (word, rest) = split_word(trimmed);
+    match word {
+      "comment" | "test" => in_comment_block = true,
+      "account" => {
+        let (account, comment) = split_comment(rest);
+        let account = account.trim();
+        if account.is_empty() {
+          return Err(ParseError::new(line, "Account declaration has no name"));
+        }
+        let account_type = comment
+          .and_then(declared_type)
+          .unwrap_or_else(|| type_from_name(account));
+        let account_id = journal.find_or_create_account(account, account_type);
+        if comment.is_some_and(is_cash_type) {
+          if let Some(account) = journal.accounts.get_mut(&account_id) {
+            account.is_cash = true;
+          }
+        }
+        in_directive_block = true;
+      },
+      "commodity" => {
+        // Ledger also accepts a sample amount such as `commodity $1,000.00`
+        let text = split_comment(rest).0.trim();
+        match parse_amount(text) {
+          Some((_, symbol)) => {
+            let id = currency_for(&symbol, currencies, &mut new_currencies, default_currency_id);
+            record_places(&mut places, &new_currencies, &id, decimal_places(text));
+          },
+          None => {
+            currency_for(text.trim_matches('"'), currencies, &mut new_currencies, default_currency_id);
+          },
+        }
+        in_directive_block = true;
+      },
+      "payee" | "tag" | "P" | "D" | "Y" | "year" | "decimal-mark" | "option" 
+      | "plugin" | "pushtag" | "poptag" => {
+        in_directive_block = true;
+      },
+      _ if first_is_digit(word) => {
+        let date = parse_date(word.split('=').next().unwrap_or_default())
+          .ok_or_else(|| ParseError::new(line, format!("Invalid date \"{}\"", word)))?;
+        let (keyword, arguments) = split_word(rest);
+        match keyword {
+          "open" => {
+            let account = split_word(arguments).0;
+            if account.is_empty() {
+              return Err(ParseError::new(line, "Open directive has no account"));
+            }
+            journal.find_or_create_account(account, type_from_name(account));
+            in_directive_block = true;
+          },
+          "commodity" => {
+            currency_for(
+              split_word(arguments).0, 
+              currencies, 
+              &mut new_currencies, 
+              default_currency_id
+            );
+            in_directive_block = true;
+          },
+          "close" | "balance" | "price" | "note" | "document" | "event" 
+          | "query" | "custom" => {
+            in_directive_block = true;
+          },
+          "pad" => {
+            return Err(ParseError::new(line, "Pad directives are not supported"));
+          },
+          _ => {
+            let (name, description) = parse_header(rest);
+            pending = Some(PendingTransaction {
+              line,
+              transaction: Transaction::new(
+                Transaction::format_date(date), 
+                name, 
+                description
+              ),
+              postings: Vec::new(),
+              has_price: false,
+            });
+          },
+        }
+      },
+      _ => {
+        return Err(ParseError::new(line, format!("Unsupported directive \"{}\"", word)));
+      },
+    }
+  }
+  finish_transaction(&mut journal, &mut pending)?;
+
+  for (id, currency) in new_currencies.iter_mut() {
+    if let Some(&decimals) = places.get(id) {
+      currency.precision = decimals;
+    }
+  }
+  currencies.extend(new_currencies);
+  Ok(journal)
+}
+
+/// Fills in an elided amount, checks the transaction balances and adds it to
+/// the journal.
+fn finish_transaction(
+  journal: &mut Journal,
+  pending: &mut Option<PendingTransaction>,
+) -> Result<(), ParseError> {
+  let Some(PendingTransaction { 
+    line, 
+    mut transaction, 
+    postings, 
+    has_price 
+  }) = pending.take() else {
+    return Ok(());
+  };
+
+  if postings.iter().filter(|p| p.amount.is_none()).count() > 1 {
+    return Err(ParseError::new(line, "More than one posting has no amount"));
+  }
+
+  let mut totals: Vec<(CurrencyId, f64)> = Vec::new();
+  for (amount, currency_id) in postings.iter().filter_map(|p| p.amount.as_ref()) {
+    match totals.iter_mut().find(|(id, _)| id == currency_id) {
+      Some((_, total)) => *total += amount,
+      None => totals.push((currency_id.clone(), *amount)),
+    }
+  }
+
+  let mut has_elided = false;
+  for posting in postings {
+    match posting.amount {
+      Some((amount, currency_id)) => add_posting(
+        &mut transaction, 
+        posting.account_id, 
+        currency_id, 
+        amount
+      ),
+      None => {
+        has_elided = true;
+        for (currency_id, total) in &totals {
+          add_posting(
+            &mut transaction, 
+            posting.account_id.clone(), 
+            currency_id.clone(), 
+            -total
+          );
+        }
+      },
+    }
+  }
+
+  let is_unbalanced = totals.iter().any(|(_, total)| total.abs() > 0.005);
+  if !has_elided && !has_price && is_unbalanced {
+    return Err(ParseError::new(line, "Transaction does not balance"));
+  }
+
+  journal.transactions.insert(transaction.id.clone(), transaction);
+  Ok(())
+}
+
+fn add_posting(
+  transaction: &mut Transaction,
+  account_id: AccountId,
+  currency_id: CurrencyId,
+  amount: f64,
+) {
+  let side = match amount < 0.0 {
+    true => BalanceType::Credit,
+    false => BalanceType::Debit,
+  };
+  transaction.add_account_change(
+    AccountChange::new(account_id, currency_id, side, amount.abs())
+  );
+}
+
+/// Parses `[*|!] Account  Amount [@ price] [= assertion] [; comment]`.
+fn parse_posting(
+  journal: &mut Journal,
+  text: &str,
+  line: usize,
+  lookup: &mut dyn FnMut(&str, usize) -> CurrencyId,
+) -> Result<(ParsedPosting, bool), ParseError> {
+  let text = split_comment(text).0;
+  let text = text.trim_start_matches(['*', '!']).trim_start();
+
+  // Accounts end at a tab or two spaces in ledger, and at the first space
+  // in beancount where account names cannot contain spaces.
+  let account_end = text
+    .find("  ")
+    .into_iter()
+    .chain(text.find('\t'))
+    .min()
+    .or_else(|| text
+      .find(' ')
+      .filter(|&i| parse_amount(text[i..].split(['@', '{', '=']).next().unwrap_or_default()).is_some())
+    )
+    .unwrap_or(text.len());
+  let (account, amount) = text.split_at(account_end);
+  let account = account.trim().trim_matches(['(', ')', '[', ']']);
+  if account.is_empty() {
+    return Err(ParseError::new(line, "Posting has no account"));
+  }
+
+  let account_id = match journal.find_account(account) {
+    Some(id) => id.clone(),
+    None => journal.find_or_create_account(account, type_from_name(account)),
+  };
+
+  let amount = amount.trim();
+  let has_price = amount.contains(['@', '{']);
+  let amount = amount
+    .split(['@', '{', '='])
+    .next()
+    .unwrap_or_default()
+    .trim();
+  if amount.is_empty() {
+    return Ok((ParsedPosting { account_id, amount: None }, has_price));
+  }
+
+  let (value, symbol) = parse_amount(amount)
+    .ok_or_else(|| ParseError::new(line, format!("Invalid amount \"{}\"", amount)))?;
+  Ok((
+    ParsedPosting { account_id, amount: Some((value, lookup(&symbol, decimal_places(amount)))) },
+    has_price
+  ))
+}
+
+/// Splits an amount such as `-$1,000.50`, `$-5`, `12 USD` or `"MY COIN" 3`
+/// into its value and commodity symbol.
+fn parse_amount(text: &str) -> Option<(f64, String)> {
+  let mut text = text.trim();
+  let mut negative = false;
+  if let Some(rest) = text.strip_prefix('-') {
+    negative = true;
+    text = rest.trim_start();
+  }
+
+  let is_number_char = |c: char| c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+');
+  let (number, symbol) = if let Some(rest) = text.strip_prefix('"') {
+    let end = rest.find('"')?;
+    (rest[end + 1..].trim(), rest[..end].to_string())
+  } else if text.starts_with(is_number_char) {
+    let end = text.find(|c: char| !is_number_char(c)).unwrap_or(text.len());
+    let symbol = text[end..].trim().trim_matches('"');
+    (&text[..end], symbol.to_string())
+  } else {
+    let end = text.find(|c: char| is_number_char(c) || c.is_whitespace())?;
+    (text[end..].trim(), text[..end].to_string())
+  };
+
+  let value: f64 = number.replace(',', "").parse().ok()?;
+  Some((if negative { -value } else { value }, symbol))
+}
+
+/// Number of digits after the decimal point of an amount, ignoring quoted
+/// commodity symbols.
+fn decimal_places(text: &str) -> usize {
+  text
+    .split('"')
+    .step_by(2)
+    .filter_map(|part| part.split_once('.'))
+    .map(|(_, fraction)| fraction.chars().take_while(char::is_ascii_digit).count())
+    .max()
+    .unwrap_or(0)
+}
+
+/// Splits `[*|!] [(code)] payee [; comment]` or beancount's
+/// `*|txn "payee" "narration" #tags` into a name and description.
+fn parse_header(text: &str) -> (String, String) {
+  let (text, comment) = split_comment(text);
+  let mut text = text.trim();
+  for flag in ["txn ", "* ", "! "] {
+    if let Some(rest) = text.strip_prefix(flag) {
+      text = rest.trim_start();
+    }
+  }
+  if text == "*" || text == "!" || text == "txn" {
+    text = "";
+  }
+  if let Some(rest) = text.strip_prefix('(') {
+    text = rest.split_once(')').map_or(rest, |(_, rest)| rest).trim_start();
+  }
+
+  let mut description = String::new();
+  let name = if text.starts_with('"') {
+    let strings = text
+      .split('"')
+      .skip(1)
+      .step_by(2)
+      .collect::<Vec<_>>();
+    match strings.as_slice() {
+      [] => String::new(),
+      [narration] => narration.to_string(),
+      [payee, narration, ..] => {
+        description.push_str(narration);
+        payee.to_string()
+      },
+    }
+  } else {
+    text.to_string()
+  };
+
+  if let Some(comment) = comment {
+    append_line(&mut description, comment.trim());
+  }
+  (name, description)
+}
+
+fn currency_for(
+  symbol: &str,
+  currencies: &HashMap<CurrencyId, Currency>,
+  new_currencies: &mut HashMap<CurrencyId, Currency>,
+  default_currency_id: &CurrencyId,
+) -> CurrencyId {
+  if symbol.is_empty() {
+    return default_currency_id.clone();
+  }
+  if let Some(currency) = currencies
+    .values()
+    .chain(new_currencies.values())
+    .find(|c| c.symbol == symbol)
+  {
+    return currency.id.clone();
+  }
+  let currency = Currency::new(symbol.to_string(), symbol.to_string());
+  let id = currency.id.clone();
+  new_currencies.insert(id.clone(), currency);
+  id
+}
+
+/// Raises the precision to be given to a currency the import creates, so
+/// that amounts show with as many decimals as the file wrote them with.
+fn record_places(
+  places: &mut HashMap<CurrencyId, usize>,
+  new_currencies: &HashMap<CurrencyId, Currency>,
+  currency_id: &CurrencyId,
+  decimals: usize,
+) {
+  if new_currencies.contains_key(currency_id) {
+    let places = places.entry(currency_id.clone()).or_insert(0);
+    *places = (*places).max(decimals);
+  }
+}
+
+/// Reads an hledger account type tag such as `type: A` or `type:Expense`.
+fn declared_type(comment: &str) -> Option<AccountType> {
+  let value = comment.split("type:").nth(1)?;
+  let value = value.trim().split([',', ' ']).next()?;
+  match value.to_ascii_lowercase().as_str() {
+    "a" | "c" | "asset" | "cash" => Some(AccountType::Asset),
+    "l" | "liability" => Some(AccountType::Liability),
+    "e" | "equity" => Some(AccountType::Equity),
+    "r" | "revenue" => Some(AccountType::Revenue),
+    "x" | "expense" => Some(AccountType::Expense),
+    _ => None,
+  }
+}
+
+/// hledger's `C` type marks cash accounts, a subtype of assets.
+fn is_cash_type(comment: &str) -> bool {
+  comment
+    .split("type:")
+    .nth(1)
+    .and_then(|value| value.trim().split([',', ' ']).next())
+    .is_some_and(|value| value.eq_ignore_ascii_case("c") || value.eq_ignore_ascii_case("cash"))
+}
+
+/// Infers an account type from the conventional top level account names,
+/// defaulting to an asset.
+pub fn type_from_name(name: &str) -> AccountType {
+  let root = name.split(ACCOUNT_SEPARATOR).next().unwrap_or_default();
+  match root.trim().to_ascii_lowercase().as_str() {
+    "liabilities" | "liability" => AccountType::Liability,
+    "equity" => AccountType::Equity,
+    "income" | "revenue" | "revenues" => AccountType::Revenue,
+    "expenses" | "expense" => AccountType::Expense,
+    _ => AccountType::Asset,
+  }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+  ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+    .iter()
+    .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+fn is_metadata(text: &str) -> bool {
+  let (key, _) = split_word(text);
+  key.ends_with(':') 
+    && key.starts_with(|c: char| c.is_ascii_lowercase())
+    && key[..key.len() - 1]
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn first_is_digit(text: &str) -> bool {
+  text.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+  let text = text.trim();
+  match text.split_once(char::is_whitespace) {
+    Some((word, rest)) => (word, rest.trim_start()),
+    None => (text, ""),
+  }
+}
+
+fn split_comment(text: &str) -> (&str, Option<&str>) {
+  match text.split_once(';') {
+    Some((text, comment)) => (text, Some(comment)),
+    None => (text, None),
+  }
+}
+
+fn append_line(text: &mut String, line: &str) {
+  if line.is_empty() {
+    return;
+  }
+  if !text.is_empty() {
+    text.push('\n');
+  }
+  text.push_str(line);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn currencies() -> HashMap<CurrencyId, Currency> {
+    [("$", "Dollars"), ("EUR", "Euros")]
+      .into_iter()
+      .map(|(symbol, name)| {
+        let currency = Currency::new(name.to_string(), symbol.to_string());
+        (currency.id.clone(), currency)
+      })
+      .collect()
+  }
+
+  fn currency_id(currencies: &HashMap<CurrencyId, Currency>, symbol: &str) -> CurrencyId {
+    currencies.values().find(|c| c.symbol == symbol).unwrap().id.clone()
+  }
+
+  /// Account, side, amount and commodity.
+  type Posting = (String, &'static str, String, String);
+
+  /// Each transaction's date, name and postings, sorted for comparing
+  /// journals.
+  fn summary(
+    journal: &Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+  ) -> Vec<(String, String, Vec<Posting>)> {
+    let mut transactions = journal.transactions
+      .values()
+      .map(|t| {
+        let mut postings = t.account_changes
+          .values()
+          .map(|c| (
+            journal.account_full_name(&c.account_id).unwrap_or_default(),
+            c.credit_or_debit.as_str(),
+            format!("{:.2}", c.amount),
+            currencies[&c.currency_id].symbol.clone(),
+          ))
+          .collect::<Vec<_>>();
+        postings.sort();
+        (t.date.clone(), t.name.clone(), postings)
+      })
+      .collect::<Vec<_>>();
+    transactions.sort();
+    transactions
+  }
+
+  #[test]
+  fn parse_amounts() {
+    let amount = |value: f64, symbol: &str| Some((value, symbol.to_string()));
+    assert_eq!(parse_amount("-$1,000.50"), amount(-1000.5, "$"));
+    assert_eq!(parse_amount("$-5"), amount(-5.0, "$"));
+    assert_eq!(parse_amount("12 USD"), amount(12.0, "USD"));
+    assert_eq!(parse_amount("\"MY COIN\" 3"), amount(3.0, "MY COIN"));
+    assert_eq!(parse_amount("7"), amount(7.0, ""));
+    assert_eq!(parse_amount("USD"), None);
+  }
+
+  #[test]
+  fn format_amounts() {
+    let currency = |symbol: &str, precision: usize| Currency {
+      precision,
+      ..Currency::new(symbol.to_string(), symbol.to_string())
+    };
+    assert_eq!(format_amount(-1234.5, &currency("$", 2)), "-$1234.50");
+    assert_eq!(format_amount(12.0, &currency("EUR", 2)), "12.00 EUR");
+    assert_eq!(format_amount(-0.001, &currency("EUR", 2)), "0.00 EUR");
+    assert_eq!(format_amount(3.0, &currency("MY COIN", 0)), "3 \"MY COIN\"");
+    assert_eq!(format_amount(1.0, &currency("X", 1)), "1.0 X");
+  }
+
+  #[test]
+  fn parse_headers() {
+    let header = |name: &str, description: &str| (name.to_string(), description.to_string());
+    assert_eq!(parse_header("* (42) Grocery Store ; weekly"), header("Grocery Store", "weekly"));
+    assert_eq!(parse_header("! Pending"), header("Pending", ""));
+    assert_eq!(parse_header("* \"Cafe\" \"Lunch\" #food"), header("Cafe", "Lunch"));
+    assert_eq!(parse_header("txn \"Narration only\""), header("Narration only", ""));
+  }
+
+  #[test]
+  fn import_fills_elided_amounts_and_declared_types() {
+    let source = "\
+account Assets:Wallet  ; type:C
+account Income:Gifts  ; type:R
+
+2024-01-15 * Grocery Store
+    ; weekly shop
+    Expenses:Food    $45.00
+    Assets:Wallet
+
+2024/01/16 Present
+    Assets:Wallet    10 EUR
+    Income:Gifts    -10 EUR
+";
+    let mut currencies = currencies();
+    let journal = import_ledger("Test".to_string(), source, &mut currencies, &"none".to_string()).unwrap();
+    let posting = |account: &str, side, amount: &str, symbol: &str| {
+      (account.to_string(), side, amount.to_string(), symbol.to_string())
+    };
+    assert_eq!(summary(&journal, &currencies), vec![
+      ("2024/1/15".to_string(), "Grocery Store".to_string(), vec![
+        posting("Assets:Wallet", "Credit", "45.00", "$"),
+        posting("Expenses:Food", "Debit", "45.00", "$"),
+      ]),
+      ("2024/1/16".to_string(), "Present".to_string(), vec![
+        posting("Assets:Wallet", "Debit", "10.00", "EUR"),
+        posting("Income:Gifts", "Credit", "10.00", "EUR"),
+      ]),
+    ]);
+    let account = |name: &str| &journal.accounts[journal.find_account(name).unwrap()];
+    assert!(account("Assets:Wallet").is_cash);
+    assert_eq!(account("Income:Gifts").account_type, AccountType::Revenue);
+    assert_eq!(account("Expenses:Food").account_type, AccountType::Expense);
+    let grocery = journal.transactions.values().find(|t| t.name == "Grocery Store").unwrap();
+    assert_eq!(grocery.description, "weekly shop");
+  }
+
+  #[test]
+  fn import_sets_new_currency_precision_from_amounts() {
+    let source = "\
+commodity 1.000 XAU
+commodity \"MY COIN\"
+
+2024-01-15 Buy
+    Assets:Coins    0.00012345 BTC
+    Assets:Coins    1.5 BTC
+    Assets:Stock    10 AAPL
+    Assets:Coins    3 \"MY COIN\"
+    Assets:Cash    $-1,000.5
+    Equity:Opening
+";
+    let mut currencies = currencies();
+    import_ledger("Test".to_string(), source, &mut currencies, &"none".to_string()).unwrap();
+    let precision = |symbol: &str| currencies[&currency_id(&currencies, symbol)].precision;
+    assert_eq!(precision("BTC"), 8);
+    assert_eq!(precision("AAPL"), 0);
+    assert_eq!(precision("MY COIN"), 0);
+    assert_eq!(precision("XAU"), 3);
+    // Currencies that already existed keep their precision
+    assert_eq!(precision("$"), 2);
+    assert_eq!(decimal_places("\"A.B\" 3"), 0);
+  }
+
+  #[test]
+  fn import_errors_name_the_line() {
+    let error = |source: &str| {
+      import_ledger("Test".to_string(), source, &mut currencies(), &"none".to_string()).unwrap_err()
+    };
+    assert_eq!(error("2024-01-01 Off\n    A  $1\n    B  $2\n").line, 1);
+    assert_eq!(error("2024-01-01 Two\n    A\n    B\n").line, 1);
+    assert_eq!(error("\n2024-13-01 Bad date\n").line, 2);
+    assert_eq!(error("    A  $1\n").line, 1);
+    assert_eq!(error("2024-01-01 Price\n    A  $1\n    B  $x\n").line, 3);
+  }
+
+  #[test]
+  fn export_then_import_round_trips() {
+    let mut currencies = currencies();
+    let dollars = currency_id(&currencies, "$");
+    let euros = currency_id(&currencies, "EUR");
+    let mut journal = Journal::new("Home".to_string());
+    let checking = journal.find_or_create_account("Assets:Checking", AccountType::Asset);
+    let card = journal.find_or_create_account("Liabilities:Card", AccountType::Liability);
+    let food = journal.find_or_create_account("Expenses:Food  Court", AccountType::Expense);
+    let date = |day| Transaction::format_date(NaiveDate::from_ymd_opt(2024, 2, day).unwrap());
+    let mut lunch = Transaction::new(date(3), "Lunch".to_string(), "with\nfriends".to_string());
+    lunch.add_account_change(AccountChange::new(food.clone(), dollars.clone(), BalanceType::Debit, 12.5));
+    lunch.add_account_change(AccountChange::new(card, dollars.clone(), BalanceType::Credit, 12.5));
+    let mut trip = Transaction::new(date(4), "Trip".to_string(), String::new());
+    trip.add_account_change(AccountChange::new(food, euros.clone(), BalanceType::Debit, 30.0));
+    trip.add_account_change(AccountChange::new(checking, euros, BalanceType::Credit, 30.0));
+    for transaction in [lunch, trip] {
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+
+    let exported = export_ledger(&journal, &currencies);
+    let imported = import_ledger("Home".to_string(), &exported, &mut currencies, &dollars).unwrap();
+    let mut expected = summary(&journal, &currencies);
+    // Ledger cannot keep two spaces inside an account name
+    for (_, _, postings) in &mut expected {
+      for posting in postings.iter_mut() {
+        posting.0 = posting.0.replace("  ", " ");
+      }
+      postings.sort();
+    }
+    assert_eq!(summary(&imported, &currencies), expected);
+    assert_eq!(export_ledger(&imported, &currencies), exported);
+  }
+}
@@ -2,6 +2,7 @@ pub mod account;
 pub mod account_change;
 pub mod account_controller;
 pub mod currency;
+pub mod currency_controller;
 pub mod journal;
 pub mod journal_controller;
 pub mod page;
@@ -11,4 +12,42 @@ pub mod transaction_controller;
 pub mod unwrapper;
 pub mod utility;
 pub mod account_change_controller;
-pub mod data;
\ No newline at end of file
+pub mod data;
+pub mod parse_error;
+pub mod qif;
+pub mod ledger;
+pub mod gnucash;
+pub mod posting;
+pub mod report;
+pub mod display;
+pub mod general_ledger;
+pub mod cash_flow;
+pub mod periodic;
+pub mod chart;
+pub mod budget;
+pub mod recurring;
+pub mod template;
+pub mod filter;
+pub mod query;
+pub mod bulk;
+pub mod duplicate;
+pub mod import_rule;
+pub mod payee;
+pub mod reconcile;
+pub mod payee_controller;
+pub mod delimited;
+pub mod xlsx;
+pub mod html;
+pub mod report_controller;
+pub mod chart_controller;
+pub mod budget_controller;
+pub mod recurring_controller;
+pub mod filter_controller;
+pub mod bulk_controller;
+pub mod cli;
+pub mod tui;
+pub mod import_controller;
+pub mod import_rule_controller;
+pub mod reconcile_controller;
+pub mod duplicate_controller;
+pub mod export_controller;
\ No newline at end of file
@@ -1,125 +1,262 @@
 use lib::{
-  account::AccountId,
   account_controller::*,
   journal::{Journal, JournalId},
   journal_controller::*,
   transaction_controller::*,
-  page::Page,
+  page::{Navigation, Page},
   unwrapper::*, 
-  transaction::TransactionId,
   currency::{
     Currency, 
     CurrencyId}, 
+    currency_controller::*,
     account_change_controller::*, 
-    account_change::AccountChangeId, 
     data::Data,
+    import_controller::*,
+    export_controller::*,
+    report_controller::*,
+    chart_controller::*,
+    budget_controller::*,
+    recurring_controller::*,
+    filter_controller::*,
+    bulk_controller::*,
+    payee_controller::*,
+    import_rule_controller::*,
+    reconcile_controller::*,
+    duplicate_controller::*,
+    cli,
+    tui,
 };
 use std::collections::HashMap;
 
 fn main() {
-  let data = Data::load();
+  let mut data = Data::load();
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  if args.first().map(String::as_str) == Some("tui") {
+    if let Err(error) = tui::run(&mut data) {
+      eprintln!("{}", error);
+      std::process::exit(1);
+    }
+    data.save();
+    return;
+  }
+  if !args.is_empty() {
+    if let Err(error) = cli::run(&args, &data) {
+      eprintln!("{}", error);
+      std::process::exit(1);
+    }
+    return;
+  }
+
+  let default_currency_id = data.default_currency_id();
   let mut journals: HashMap<JournalId, Journal> = data.journals;
-  let currencies: HashMap<CurrencyId, Currency> = data.currencies;
-  let currency_id = currencies.iter().last().unwrap().0;
+  let mut currencies: HashMap<CurrencyId, Currency> = data.currencies;
+
+  let mut navigation = Navigation::new(Page::DueItems);
+  while !navigation.terminate_signal {
+    // Pages whose records were deleted or never existed are skipped
+    navigation.discard_missing(&journals);
+    println!("{}", navigation.breadcrumb(&journals));
+
+    let context = navigation.context();
+    let journal_id = context.journal_id;
+    let account_id = context.account_id;
+    let transaction_id = context.transaction_id;
+    let account_change_id = context.account_change_id;
+    let currency_id = get_journal(&journals, &journal_id)
+      .and_then(Journal::last_used_currency)
+      .unwrap_or_else(|| default_currency_id.clone());
+    match navigation.page() {
+      Page::DueItems => review_due_items(&mut journals, &currencies, &mut navigation),
 
-  let mut page = Page::SelectJournal;
-  let mut journal_id: Option<JournalId> = None;
-  let mut account_id: Option<AccountId> = None;
-  let mut transaction_id: Option<TransactionId> = None;
-  let mut account_change_id: Option<AccountChangeId> = None;
-  let mut terminate_signal = false;
-  while !terminate_signal {
-    match page {
       // Journal Pages
       Page::SelectJournal => select_journal(
         &journals, 
-        &mut journal_id, 
-        &mut page, 
-        &mut terminate_signal
+        &mut navigation, 
       ),
       Page::NewJournal => new_journal(
         &mut journals, 
-        &mut page
+        &mut navigation
       ),
       Page::ViewJournal => view_journal(
-        get_journal_mut(&mut journals, &journal_id), &mut page
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
       ),
       Page::DeleteJournal => delete_journal(
         &mut journals, 
         &journal_id, 
-        &mut page
+        &mut navigation
+      ),
+      Page::Currencies => view_currencies(
+        &mut currencies,
+        &mut navigation
+      ),
+      Page::ImportNewJournal => import_new_journal(
+        &currency_id,
+        &mut currencies,
+        &mut journals,
+        &mut navigation
+      ),
+      Page::Import => import_into_journal(
+        &currency_id,
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Reports => view_reports(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Charts => view_journal_charts(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Budgets => view_budgets(
+        &currencies,
+        &currency_id,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Recurring => view_recurring(
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Payees => view_payees(
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::ImportRules => view_import_rules(
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Duplicates => find_duplicates(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::Export => export_journal(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
       ),
 
       // Account Pages
       Page::SelectAccount => select_account(
-        &mut account_id,
         get_journal_mut(&mut journals, &journal_id),
-        &mut page,
+        &mut navigation,
       ),
       Page::NewAccount => new_account(
-        get_accounts_mut(&mut journals, &journal_id), &mut page
+        get_accounts_mut(&mut journals, &journal_id), &mut navigation
       ),
       Page::ViewAccount => view_account(
-        get_account_mut(&mut journals, &journal_id, &account_id),
-        &mut page,
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &account_id,
+        &mut navigation,
+      ),
+      Page::AccountChart => view_account_chart(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &account_id,
+        &mut navigation,
+      ),
+      Page::Reconcile => reconcile_account(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &account_id,
+        &mut navigation,
       ),
       Page::DeleteAccount => delete_account(
         get_accounts_mut(&mut journals, &journal_id),
         &account_id,
-        &mut page,
+        &mut navigation,
       ),
 
       // Transaction Pages
       Page::SelectTransaction => select_transaction(
-        &mut page, 
-        &mut transaction_id, 
-        get_transactions_mut(&mut journals, &journal_id)
+        &mut navigation, 
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id)
+      ),
+      Page::FilterTransactions => filter_transactions(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::BulkTransactions => bulk_transactions(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
+      ),
+      Page::SavedFilters => saved_filters(
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &mut navigation
       ),
       Page::NewTransaction => new_transaction(
-        &mut page, 
-        get_transactions_mut(&mut journals, &journal_id)
+        &mut navigation, 
+        &currencies,
+        &currency_id,
+        get_journal_mut(&mut journals, &journal_id)
+      ),
+      Page::NewFromTemplate => new_from_template(
+        &mut navigation,
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id)
+      ),
+      Page::SaveTemplate => save_template(
+        &mut navigation,
+        &currencies,
+        get_journal_mut(&mut journals, &journal_id),
+        &transaction_id
       ),
       Page::ViewTransaction => view_transaction(
-        &mut page, 
-        get_transaction_mut(&mut journals, &journal_id, &transaction_id)
+        &mut navigation, 
+        &currencies,
+        get_journal(&journals, &journal_id),
+        &transaction_id
       ),
       Page::DeleteTransaction => delete_transaction(
-        &mut page,
+        &mut navigation,
         get_transactions_mut(&mut journals, &journal_id), 
-        &mut transaction_id, 
+        &transaction_id, 
       ),
 
       // Debits/Credits Pages
       Page::SelectAccountChange => select_account_change(
-        &mut account_change_id, 
         &journal_id,
         &mut journals, 
-        &mut page, 
+        &mut navigation, 
         &transaction_id
       ),
       Page::NewAccountChange => new_account_change(
+        &currencies,
         currency_id.clone(), 
         &journal_id, 
         &mut journals, 
-        &mut page, 
+        &mut navigation, 
         &transaction_id
       ),
       Page::ViewAccountChange => view_account_change(
-        &mut account_change_id, 
+        &currencies,
+        &account_change_id, 
         &journal_id, 
         &mut journals, 
-        &mut page,
+        &mut navigation,
         &transaction_id),
       Page::DeleteAccountChange => delete_account_change(
         &account_change_id, 
         &journal_id, 
         &mut journals, 
-        &mut page, 
+        &mut navigation, 
         &transaction_id),
     };
   }
   Data {
     journals,
-    currencies
+    currencies,
+    default_currency_id: Some(default_currency_id),
   }.save();
 }
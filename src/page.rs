@@ -1,21 +1,249 @@
+use std::collections::HashMap;
+use crate::{
+  account::AccountId,
+  account_change::AccountChangeId,
+  filter::TransactionFilter,
+  journal::{Journal, JournalId},
+  transaction::TransactionId,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Page {
+  DueItems,
   SelectJournal,
   NewJournal,
+  ImportNewJournal,
   ViewJournal,
   DeleteJournal,
+  Currencies,
+  Import,
+  Export,
+  Reports,
+  Charts,
+  Budgets,
+  Recurring,
+  Payees,
+  ImportRules,
+  Duplicates,
 
   SelectAccount,
   NewAccount,
   ViewAccount,
+  AccountChart,
+  Reconcile,
   DeleteAccount,
 
   SelectTransaction,
+  FilterTransactions,
+  SavedFilters,
+  BulkTransactions,
   NewTransaction,
+  NewFromTemplate,
   ViewTransaction,
   DeleteTransaction,
+  SaveTemplate,
 
   SelectAccountChange,
   NewAccountChange,
   ViewAccountChange,
   DeleteAccountChange,
 }
+
+impl Page {
+  fn title(self) -> &'static str {
+    match self {
+      Page::DueItems => "Due Items",
+      Page::SelectJournal => "Journals",
+      Page::NewJournal => "New Journal",
+      Page::ImportNewJournal => "Import Journal",
+      Page::ViewJournal => "Journal",
+      Page::DeleteJournal => "Delete",
+      Page::Currencies => "Currencies",
+      Page::Import => "Import",
+      Page::Export => "Export",
+      Page::Reports => "Reports",
+      Page::Charts => "Charts",
+      Page::Budgets => "Budgets",
+      Page::Recurring => "Recurring",
+      Page::Payees => "Payees",
+      Page::ImportRules => "Import Rules",
+      Page::Duplicates => "Duplicates",
+      Page::SelectAccount => "Accounts",
+      Page::NewAccount => "New Account",
+      Page::ViewAccount => "Account",
+      Page::AccountChart => "Chart",
+      Page::Reconcile => "Reconcile",
+      Page::DeleteAccount => "Delete",
+      Page::SelectTransaction => "Transactions",
+      Page::FilterTransactions => "Filter",
+      Page::SavedFilters => "Saved Filters",
+      Page::BulkTransactions => "Select Multiple",
+      Page::NewTransaction => "New Transaction",
+      Page::NewFromTemplate => "From Template",
+      Page::ViewTransaction => "Transaction",
+      Page::DeleteTransaction => "Delete",
+      Page::SaveTemplate => "Save Template",
+      Page::SelectAccountChange => "Changes",
+      Page::NewAccountChange => "New Change",
+      Page::ViewAccountChange => "Change",
+      Page::DeleteAccountChange => "Delete",
+    }
+  }
+}
+
+/// Ids of the records the current page works on.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+  pub journal_id: Option<JournalId>,
+  pub account_id: Option<AccountId>,
+  pub transaction_id: Option<TransactionId>,
+  pub account_change_id: Option<AccountChangeId>,
+  /// Narrows the transaction list.
+  pub filter: TransactionFilter,
+}
+
+#[derive(Debug, Clone)]
+struct Location {
+  page: Page,
+  context: Context,
+}
+
+/// The pages visited to reach the current one, each with its context, so
+/// Back and errors return to wherever the user came from.
+#[derive(Debug)]
+pub struct Navigation {
+  history: Vec<Location>,
+  pub terminate_signal: bool,
+}
+
+impl Navigation {
+  /// Starts on `page` with the journal list underneath it.
+  pub fn new(page: Page) -> Navigation {
+    let mut navigation = Navigation {
+      history: vec![Location { page: Page::SelectJournal, context: Context::default() }],
+      terminate_signal: false,
+    };
+    if page != Page::SelectJournal {
+      navigation.go(page);
+    }
+    navigation
+  }
+
+  pub fn page(&self) -> Page {
+    self.history.last().map_or(Page::SelectJournal, |l| l.page)
+  }
+
+  pub fn context(&self) -> Context {
+    self.history.last().map(|l| l.context.clone()).unwrap_or_default()
+  }
+
+  /// Opens `page` with the current context.
+  pub fn go(&mut self, page: Page) {
+    let context = self.context();
+    self.history.push(Location { page, context });
+  }
+
+  pub fn open_journal(&mut self, journal_id: JournalId) {
+    let context = Context { journal_id: Some(journal_id), ..Context::default() };
+    self.history.push(Location { page: Page::ViewJournal, context });
+  }
+
+  pub fn open_account(&mut self, account_id: AccountId) {
+    let context = Context { account_id: Some(account_id), ..self.context() };
+    self.history.push(Location { page: Page::ViewAccount, context });
+  }
+
+  pub fn open_transaction(&mut self, transaction_id: TransactionId) {
+    let context = Context {
+      transaction_id: Some(transaction_id),
+      account_change_id: None,
+      ..self.context()
+    };
+    self.history.push(Location { page: Page::ViewTransaction, context });
+  }
+
+  pub fn open_account_change(&mut self, account_change_id: AccountChangeId) {
+    let context = Context { account_change_id: Some(account_change_id), ..self.context() };
+    self.history.push(Location { page: Page::ViewAccountChange, context });
+  }
+
+  /// Replaces the filter of the current page and the pages it opens.
+  pub fn set_filter(&mut self, filter: TransactionFilter) {
+    if let Some(location) = self.history.last_mut() {
+      location.context.filter = filter;
+    }
+  }
+
+  /// Returns to the previous page. The journal list is never left.
+  pub fn back(&mut self) {
+    if self.history.len() > 1 {
+      self.history.pop();
+    }
+  }
+
+  /// Returns to the most recent visit of `page`, or the journal list.
+  pub fn back_to(&mut self, page: Page) {
+    while self.history.len() > 1 && self.page() != page {
+      self.history.pop();
+    }
+  }
+
+  /// Drops pages whose journal, account, transaction or change no longer
+  /// exists, e.g. after it was deleted.
+  pub fn discard_missing(&mut self, journals: &HashMap<JournalId, Journal>) {
+    while self.history.len() > 1 && !self.is_valid(journals) {
+      self.history.pop();
+    }
+  }
+
+  fn is_valid(&self, journals: &HashMap<JournalId, Journal>) -> bool {
+    let context = self.context();
+    let journal = context.journal_id.as_ref().and_then(|id| journals.get(id));
+    let transaction = journal
+      .zip(context.transaction_id.as_ref())
+      .and_then(|(j, id)| j.transactions.get(id));
+    let needs = |id: bool, found: bool| !id || found;
+    needs(context.journal_id.is_some(), journal.is_some())
+      && needs(
+        context.account_id.is_some(),
+        journal.zip(context.account_id.as_ref()).is_some_and(|(j, id)| j.accounts.contains_key(id)),
+      )
+      && needs(context.transaction_id.is_some(), transaction.is_some())
+      && needs(
+        context.account_change_id.is_some(),
+        transaction
+          .zip(context.account_change_id.as_ref())
+          .is_some_and(|(t, id)| t.account_changes.contains_key(id)),
+      )
+  }
+
+  /// The path to the current page, e.g. `Journals > Home > Accounts > Cash`.
+  pub fn breadcrumb(&self, journals: &HashMap<JournalId, Journal>) -> String {
+    self.history
+      .iter()
+      .map(|location| {
+        let context = &location.context;
+        let journal = context.journal_id.as_ref().and_then(|id| journals.get(id));
+        let name = match location.page {
+          Page::ViewJournal => journal.map(|j| j.name.clone()),
+          Page::ViewAccount => journal
+            .zip(context.account_id.as_ref())
+            .and_then(|(j, id)| j.account_full_name(id)),
+          Page::ViewTransaction => journal
+            .zip(context.transaction_id.as_ref())
+            .and_then(|(j, id)| j.transactions.get(id))
+            .map(|t| t.name.clone()),
+          Page::ViewAccountChange => journal
+            .zip(context.transaction_id.as_ref())
+            .and_then(|(j, id)| j.transactions.get(id))
+            .zip(context.account_change_id.as_ref())
+            .and_then(|(t, id)| t.account_changes.get(id))
+            .and_then(|c| journal?.account_full_name(&c.account_id)),
+          _ => None,
+        };
+        name.unwrap_or_else(|| location.page.title().to_string())
+      })
+      .collect::<Vec<_>>()
+      .join(" > ")
+  }
+}
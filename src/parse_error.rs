@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// Error produced by the file importers, pointing at the offending line.
+#[derive(Debug)]
+pub struct ParseError {
+  pub line: usize,
+  pub message: String,
+}
+
+impl ParseError {
+  pub fn new(line: usize, message: impl Into<String>) -> ParseError {
+    ParseError { line, message: message.into() }
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Line {}: {}", self.line, self.message)
+  }
+}
+
+impl std::error::Error for ParseError {}
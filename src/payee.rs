@@ -0,0 +1,63 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account::AccountId,
+  journal::Journal,
+  transaction::Transaction,
+};
+
+pub type PayeeId = String;
+
+/// A merchant or person transactions are made with, known under one name
+/// and any number of other spellings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payee {
+  pub id: PayeeId,
+  pub name: String,
+  /// Other spellings, e.g. as they appear on bank statements.
+  pub aliases: Vec<String>,
+  /// Account usually posted against, offered first when entering postings.
+  pub default_account_id: Option<AccountId>,
+}
+
+impl Payee {
+  pub fn new(name: String) -> Payee {
+    Payee {
+      id: Uuid::new_v4().to_string(),
+      name,
+      aliases: Vec::new(),
+      default_account_id: None,
+    }
+  }
+
+  /// Whether `text` is the name or one of the aliases, ignoring case and
+  /// surrounding spaces.
+  pub fn is_called(&self, text: &str) -> bool {
+    let text = text.trim();
+    self.name.eq_ignore_ascii_case(text) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(text))
+  }
+
+  /// Name followed by the aliases, for listing and fuzzy matching.
+  pub fn label(&self) -> String {
+    match self.aliases.is_empty() {
+      true => self.name.clone(),
+      false => format!("{} ({})", self.name, self.aliases.join(", ")),
+    }
+  }
+}
+
+impl Journal {
+  /// The payee known by `name` or an alias of it.
+  pub fn find_payee(&self, name: &str) -> Option<&Payee> {
+    self.payees.values().find(|p| p.is_called(name))
+  }
+
+  /// The most recent transaction with the payee, also counting transactions
+  /// entered before it was registered under one of its names.
+  pub fn last_transaction_with(&self, payee: &Payee) -> Option<&Transaction> {
+    self.transactions
+      .values()
+      .filter(|t| t.payee_id.as_ref() == Some(&payee.id) || payee.is_called(&t.name))
+      .max_by_key(|t| (t.parsed_date(), t.name.clone(), t.id.clone()))
+  }
+}
@@ -0,0 +1,194 @@
+use crate::{
+  journal::Journal,
+  page::Navigation,
+  payee::{Payee, PayeeId},
+  report::account_label,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_input_with_labels,
+    input_default,
+    select_with_labels,
+  },
+};
+
+/// Lists the journal's payees to add or edit.
+pub fn view_payees(journal: Option<&mut Journal>, navigation: &mut Navigation) {
+  enum Selection {
+    Payee(PayeeId),
+    NewPayee,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = journal.payees
+    .values()
+    .map(|p| LabeledItem::from(p.label(), Selection::Payee(p.id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.insert(0, LabeledItem::from("[Back]".to_string(), Selection::Back));
+  options.insert(1, LabeledItem::from("[New Payee]".to_string(), Selection::NewPayee));
+
+  let selection = match fuzzy_input_with_labels("Payees".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let result = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::NewPayee => new_payee(journal).map(|_| ()),
+    Selection::Payee(id) => edit_payee(journal, id),
+  };
+  if let Err(error) = result {
+    on_error(navigation, error)
+  }
+}
+
+/// Asks for a name and registers it, unless a payee already goes by it.
+fn new_payee(journal: &mut Journal) -> Result<PayeeId, std::io::Error> {
+  let name = input_default("Payee Name:".to_string(), String::new())?;
+  if let Some(payee) = journal.find_payee(&name) {
+    println!("Already known as {}", payee.name);
+    return Ok(payee.id.clone());
+  }
+  let payee = Payee::new(name.trim().to_string());
+  let payee_id = payee.id.clone();
+  journal.payees.insert(payee.id.clone(), payee);
+  Ok(payee_id)
+}
+
+fn edit_payee(journal: &mut Journal, payee_id: &PayeeId) -> Result<(), std::io::Error> {
+  enum Selection {
+    Back,
+    Rename,
+    AddAlias,
+    RemoveAlias,
+    DefaultAccount,
+    Delete,
+  }
+
+  let mut account_options = journal.accounts
+    .keys()
+    .map(|id| LabeledItem::from(account_label(journal, id), Some(id.clone())))
+    .collect::<Vec<_>>();
+  account_options.sort_by(|a, b| a.label.cmp(&b.label));
+  account_options.insert(0, LabeledItem::from("[None]".to_string(), None));
+
+  let payee = match journal.payees.get(payee_id) {
+    None => return Ok(()),
+    Some(payee) => payee,
+  };
+  let default_account = payee.default_account_id
+    .as_ref()
+    .map_or_else(|| "None".to_string(), |id| account_label(journal, id));
+  let mut options = vec![
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+    LabeledItem::from("[Rename]".to_string(), Selection::Rename),
+    LabeledItem::from("[Add Alias]".to_string(), Selection::AddAlias),
+    LabeledItem::from(format!("[Default Account] {}", default_account), Selection::DefaultAccount),
+    LabeledItem::from("[Delete]".to_string(), Selection::Delete),
+  ];
+  if !payee.aliases.is_empty() {
+    options.insert(3, LabeledItem::from("[Remove Alias]".to_string(), Selection::RemoveAlias));
+  }
+  let selection = select_with_labels(payee.label(), &options)?;
+
+  match selection {
+    Selection::Back => {},
+    Selection::Rename => {
+      let name = input_default("Payee Name:".to_string(), payee.name.clone())?;
+      let name = name.trim().to_string();
+      if let Some(other) = journal.find_payee(&name).filter(|p| &p.id != payee_id) {
+        println!("Already known as {}", other.name);
+        return Ok(());
+      }
+      if let Some(payee) = journal.payees.get_mut(payee_id) {
+        // Keep the old name so transactions entered under it still match
+        let old_name = std::mem::replace(&mut payee.name, name);
+        payee.aliases.retain(|a| !a.eq_ignore_ascii_case(&payee.name));
+        if !old_name.eq_ignore_ascii_case(&payee.name) {
+          payee.aliases.push(old_name);
+        }
+      }
+    },
+    Selection::AddAlias => {
+      let alias = input_default("Alias:".to_string(), String::new())?;
+      let alias = alias.trim().to_string();
+      match journal.find_payee(&alias) {
+        Some(other) => println!("Already known as {}", other.name),
+        None => if let Some(payee) = journal.payees.get_mut(payee_id) {
+          payee.aliases.push(alias);
+        },
+      }
+    },
+    Selection::RemoveAlias => {
+      let options = payee.aliases
+        .iter()
+        .map(|a| LabeledItem::from(a.clone(), a.clone()))
+        .collect::<Vec<_>>();
+      let alias = select_with_labels("Remove Alias".to_string(), &options)?.clone();
+      if let Some(payee) = journal.payees.get_mut(payee_id) {
+        payee.aliases.retain(|a| a != &alias);
+      }
+    },
+    Selection::DefaultAccount => {
+      let account_id = fuzzy_input_with_labels("Default Account:".to_string(), &account_options)?.clone();
+      if let Some(payee) = journal.payees.get_mut(payee_id) {
+        payee.default_account_id = account_id;
+      }
+    },
+    Selection::Delete => {
+      let prompt = format!("Are you sure you want to delete \"{}\"?", payee.name);
+      if confirm_default(prompt)? {
+        journal.payees.remove(payee_id);
+        for transaction in journal.transactions.values_mut() {
+          if transaction.payee_id.as_ref() == Some(payee_id) {
+            transaction.payee_id = None;
+          }
+        }
+      }
+    },
+  }
+  Ok(())
+}
+
+/// Picks the payee of a new transaction from the registry, registers a new
+/// one, or takes a one-off name. Returns the transaction name and the payee.
+pub fn input_payee(journal: &mut Journal, default_name: String) -> Result<(String, Option<PayeeId>), std::io::Error> {
+  enum Selection {
+    Payee(PayeeId),
+    NewPayee,
+    Other,
+  }
+
+  let mut options = journal.payees
+    .values()
+    .map(|p| LabeledItem::from(p.label(), Selection::Payee(p.id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.push(LabeledItem::from("[New Payee]".to_string(), Selection::NewPayee));
+  options.push(LabeledItem::from("[Other Name]".to_string(), Selection::Other));
+
+  let payee_id = match fuzzy_input_with_labels("Payee:".to_string(), &options)? {
+    Selection::Payee(id) => id.clone(),
+    Selection::NewPayee => new_payee(journal)?,
+    Selection::Other => {
+      let name = input_default("Transaction Name:".to_string(), default_name)?;
+      // A name typed out in full may still belong to a known payee
+      return Ok(match journal.find_payee(&name) {
+        Some(payee) => (payee.name.clone(), Some(payee.id.clone())),
+        None => (name, None),
+      });
+    },
+  };
+  match journal.payees.get(&payee_id) {
+    None => Ok((default_name, None)),
+    Some(payee) => Ok((payee.name.clone(), Some(payee_id))),
+  }
+}
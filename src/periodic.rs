@@ -0,0 +1,344 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::{Datelike, Months, NaiveDate};
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  report::{account_label, currency_label, Cell, ReportOptions, Table},
+};
+
+/// Length of the periods a periodic report buckets postings into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interval {
+  Week,
+  #[default]
+  Month,
+  Quarter,
+  Year,
+}
+
+impl Interval {
+  pub fn all() -> Vec<Interval> {
+    vec![Interval::Week, Interval::Month, Interval::Quarter, Interval::Year]
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Interval::Week => "Weekly",
+      Interval::Month => "Monthly",
+      Interval::Quarter => "Quarterly",
+      Interval::Year => "Yearly",
+    }
+  }
+
+  pub fn cli_name(self) -> &'static str {
+    match self {
+      Interval::Week => "week",
+      Interval::Month => "month",
+      Interval::Quarter => "quarter",
+      Interval::Year => "year",
+    }
+  }
+
+  pub fn from_cli_name(name: &str) -> Option<Interval> {
+    Interval::all().into_iter().find(|interval| interval.cli_name() == name)
+  }
+
+  /// First day of the period containing `date`. Weeks start on Monday.
+  pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+    let first_of_month = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date);
+    match self {
+      Interval::Week => date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64),
+      Interval::Month => first_of_month(date.month()),
+      Interval::Quarter => first_of_month(date.month0() / 3 * 3 + 1),
+      Interval::Year => first_of_month(1),
+    }
+  }
+
+  /// First day of the period after the one starting on `start`.
+  pub fn next(self, start: NaiveDate) -> NaiveDate {
+    match self {
+      Interval::Week => start + chrono::Duration::days(7),
+      Interval::Month => start + Months::new(1),
+      Interval::Quarter => start + Months::new(3),
+      Interval::Year => start + Months::new(12),
+    }
+  }
+
+  /// Column heading for the period starting on `start`.
+  pub fn label(self, start: NaiveDate) -> String {
+    match self {
+      Interval::Week => start.format("%G-W%V").to_string(),
+      Interval::Month => start.format("%Y-%m").to_string(),
+      Interval::Quarter => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+      Interval::Year => start.year().to_string(),
+    }
+  }
+}
+
+/// Start dates of every period from the one containing `first` to the one
+/// containing `last`.
+pub fn periods(interval: Interval, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+  let mut periods = Vec::new();
+  let mut start = interval.start_of(first);
+  while start <= last {
+    periods.push(start);
+    start = interval.next(start);
+  }
+  periods
+}
+
+/// Accounts by periods matrix of either each period's change or the balance
+/// at its end, in the sign of each account's normal balance. The range
+/// defaults to the first and last dated postings; undated postings are left
+/// out. Accounts are grouped by type with totals per currency.
+pub fn periodic_report(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let interval = options.interval;
+  let postings = journal.postings()
+    .into_iter()
+    .filter_map(|p| Some((p.date?, p.change)))
+    .collect::<Vec<_>>();
+  let first = options.range.start.or_else(|| postings.iter().map(|(d, _)| *d).min());
+  let last = options.range.end.or_else(|| postings.iter().map(|(d, _)| *d).max());
+  let periods = match (first, last) {
+    (Some(first), Some(last)) => periods(interval, first, last),
+    _ => Vec::new(),
+  };
+  let mode = match options.cumulative {
+    true => "Balances",
+    false => "Changes",
+  };
+
+  let mut headers = vec!["Account".to_string(), "Currency".to_string()];
+  headers.extend(periods.iter().map(|start| interval.label(*start)));
+  if !options.cumulative {
+    headers.push("Total".to_string());
+  }
+  headers.push("Average".to_string());
+  let mut table = Table::new(
+    format!(
+      "{} - {} {} ({})",
+      journal.name,
+      interval.as_str(),
+      mode,
+      options.range.describe()
+    ),
+    &headers.iter().map(String::as_str).collect::<Vec<_>>(),
+  );
+  let Some(first_period) = periods.first() else {
+    return table;
+  };
+  let first_date = options.range.start.unwrap_or(*first_period);
+
+  // Debit positive amounts per account and currency, one slot per period
+  let mut changes: HashMap<(AccountId, CurrencyId), Vec<f64>> = HashMap::new();
+  let mut opening: HashMap<(AccountId, CurrencyId), f64> = HashMap::new();
+  for (date, change) in &postings {
+    if options.range.end.is_some_and(|end| *date > end) {
+      continue;
+    }
+    let key = (change.account_id.clone(), change.currency_id.clone());
+    let amount = change.signed_amount(BalanceType::Debit);
+    if *date < first_date {
+      if options.cumulative {
+        *opening.entry(key).or_insert(0.0) += amount;
+      }
+      continue;
+    }
+    let index = periods.partition_point(|start| start <= date).saturating_sub(1);
+    changes.entry(key).or_insert_with(|| vec![0.0; periods.len()])[index] += amount;
+  }
+  for key in opening.keys() {
+    changes.entry(key.clone()).or_insert_with(|| vec![0.0; periods.len()]);
+  }
+
+  let mut rows = changes.into_iter().collect::<Vec<_>>();
+  rows.sort_by_cached_key(|((account_id, currency_id), _)| (
+    account_label(journal, account_id),
+    currency_label(currencies, currency_id),
+  ));
+
+  for (section, account_type) in [
+    ("Assets", AccountType::Asset),
+    ("Liabilities", AccountType::Liability),
+    ("Equity", AccountType::Equity),
+    ("Revenue", AccountType::Revenue),
+    ("Expenses", AccountType::Expense),
+  ] {
+    let sign = match account_type.normal_balance() {
+      BalanceType::Debit => 1.0,
+      BalanceType::Credit => -1.0,
+    };
+    let mut section_totals: BTreeMap<String, (CurrencyId, Vec<f64>)> = BTreeMap::new();
+    for (key, amounts) in &rows {
+      let (account_id, currency_id) = key;
+      if journal.accounts.get(account_id).map(|a| a.account_type) != Some(account_type) {
+        continue;
+      }
+      let mut balance = opening.get(key).copied().unwrap_or(0.0);
+      let values = amounts
+        .iter()
+        .map(|amount| match options.cumulative {
+          true => {
+            balance += amount;
+            sign * balance
+          },
+          false => sign * amount,
+        })
+        .collect::<Vec<_>>();
+      if values.iter().all(|value| value.abs() < 0.005) {
+        continue;
+      }
+      let label = currency_label(currencies, currency_id);
+      let totals = &mut section_totals
+        .entry(label.clone())
+        .or_insert_with(|| (currency_id.clone(), vec![0.0; periods.len()]))
+        .1;
+      for (total, value) in totals.iter_mut().zip(&values) {
+        *total += value;
+      }
+      table.rows.push(matrix_row(
+        account_label(journal, account_id),
+        label,
+        currency_id,
+        &values,
+        options.cumulative,
+      ));
+    }
+    for (label, (currency_id, values)) in section_totals {
+      table.rows.push(matrix_row(
+        format!("Total {}", section),
+        label,
+        &currency_id,
+        &values,
+        options.cumulative,
+      ));
+    }
+  }
+  table
+}
+
+fn matrix_row(
+  name: String,
+  currency: String,
+  currency_id: &CurrencyId,
+  values: &[f64],
+  cumulative: bool,
+) -> Vec<Cell> {
+  let total = values.iter().sum::<f64>();
+  let mut row = vec![Cell::Text(name), Cell::Text(currency)];
+  row.extend(values.iter().map(|value| Cell::Amount(*value, currency_id.clone())));
+  if !cumulative {
+    row.push(Cell::Amount(total, currency_id.clone()));
+  }
+  row.push(Cell::Amount(total / values.len().max(1) as f64, currency_id.clone()));
+  row
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    account_change::AccountChange,
+    report::DateRange,
+    transaction::Transaction,
+  };
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  /// Food bought from cash on the edges of months, quarters and years.
+  fn journal() -> Journal {
+    let mut journal = Journal::new("Home".to_string());
+    let cash = journal.find_or_create_account("Cash", AccountType::Asset);
+    let food = journal.find_or_create_account("Food", AccountType::Expense);
+    let purchases = [
+      (date(2024, 1, 31), 1.0),
+      (date(2024, 2, 1), 2.0),
+      (date(2024, 3, 31), 4.0),
+      (date(2024, 4, 1), 8.0),
+      (date(2024, 12, 31), 16.0),
+      (date(2025, 1, 1), 32.0),
+    ];
+    for (posted, amount) in purchases {
+      let mut transaction = Transaction::new(Transaction::format_date(posted), String::new(), String::new());
+      transaction.add_account_change(AccountChange::new(food.clone(), "usd".to_string(), BalanceType::Debit, amount));
+      transaction.add_account_change(AccountChange::new(cash.clone(), "usd".to_string(), BalanceType::Credit, amount));
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+    journal
+  }
+
+  /// Headers and the amounts of the Food row.
+  fn food_row(options: &ReportOptions) -> (Vec<String>, Vec<f64>) {
+    let table = periodic_report(&journal(), &HashMap::new(), options);
+    let row = table.rows
+      .iter()
+      .find(|row| matches!(&row[0], Cell::Text(name) if name == "Food"))
+      .unwrap();
+    let amounts = row
+      .iter()
+      .filter_map(|cell| match cell {
+        Cell::Amount(amount, _) => Some(*amount),
+        _ => None,
+      })
+      .collect();
+    (table.headers[2..].to_vec(), amounts)
+  }
+
+  #[test]
+  fn periods_start_on_interval_boundaries() {
+    assert_eq!(Interval::Month.start_of(date(2024, 2, 29)), date(2024, 2, 1));
+    assert_eq!(Interval::Quarter.start_of(date(2024, 6, 30)), date(2024, 4, 1));
+    assert_eq!(Interval::Quarter.start_of(date(2024, 10, 1)), date(2024, 10, 1));
+    assert_eq!(Interval::Year.start_of(date(2024, 12, 31)), date(2024, 1, 1));
+    assert_eq!(Interval::Week.start_of(date(2024, 1, 7)), date(2024, 1, 1));
+    assert_eq!(
+      periods(Interval::Quarter, date(2024, 11, 15), date(2025, 2, 1)),
+      vec![date(2024, 10, 1), date(2025, 1, 1)]
+    );
+    assert_eq!(Interval::Quarter.label(date(2024, 10, 1)), "2024-Q4");
+    assert_eq!(Interval::Week.label(date(2024, 12, 30)), "2025-W01");
+  }
+
+  #[test]
+  fn buckets_postings_by_month() {
+    let options = ReportOptions {
+      range: DateRange::new(Some(date(2024, 1, 1)), Some(date(2024, 4, 30))),
+      ..ReportOptions::default()
+    };
+    let (headers, amounts) = food_row(&options);
+    assert_eq!(headers, vec!["2024-01", "2024-02", "2024-03", "2024-04", "Total", "Average"]);
+    assert_eq!(amounts, vec![1.0, 2.0, 4.0, 8.0, 15.0, 3.75]);
+  }
+
+  #[test]
+  fn buckets_postings_by_quarter_and_year() {
+    let quarterly = ReportOptions { interval: Interval::Quarter, ..ReportOptions::default() };
+    let (headers, amounts) = food_row(&quarterly);
+    assert_eq!(headers, vec!["2024-Q1", "2024-Q2", "2024-Q3", "2024-Q4", "2025-Q1", "Total", "Average"]);
+    assert_eq!(amounts, vec![7.0, 8.0, 0.0, 16.0, 32.0, 63.0, 12.6]);
+
+    let yearly = ReportOptions { interval: Interval::Year, ..ReportOptions::default() };
+    let (headers, amounts) = food_row(&yearly);
+    assert_eq!(headers, vec!["2024", "2025", "Total", "Average"]);
+    assert_eq!(amounts, vec![31.0, 32.0, 63.0, 31.5]);
+  }
+
+  #[test]
+  fn cumulative_balances_include_postings_before_the_range() {
+    let options = ReportOptions {
+      range: DateRange::new(Some(date(2024, 4, 1)), Some(date(2025, 3, 31))),
+      interval: Interval::Quarter,
+      cumulative: true,
+    };
+    let (headers, amounts) = food_row(&options);
+    assert_eq!(headers, vec!["2024-Q2", "2024-Q3", "2024-Q4", "2025-Q1", "Average"]);
+    assert_eq!(amounts, vec![15.0, 15.0, 31.0, 63.0, 31.0]);
+  }
+}
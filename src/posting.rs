@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use crate::{
+  account::BalanceType,
+  account_change::AccountChange,
+  currency::CurrencyId,
+  journal::Journal,
+  transaction::Transaction,
+};
+
+/// An account change together with the transaction it belongs to.
+#[derive(Clone, Copy)]
+pub struct Posting<'a> {
+  pub transaction: &'a Transaction,
+  pub change: &'a AccountChange,
+  pub date: Option<NaiveDate>,
+}
+
+impl Journal {
+  /// Currency of the latest dated posting, the one new postings most
+  /// likely use.
+  pub fn last_used_currency(&self) -> Option<CurrencyId> {
+    self.transactions
+      .values()
+      .filter_map(|t| Some((t.parsed_date()?, t)))
+      .max_by(|(a, t), (b, u)| (a, &t.id).cmp(&(b, &u.id)))
+      .and_then(|(_, t)| {
+        let mut changes = t.account_changes.values().collect::<Vec<_>>();
+        changes.sort_by(|a, b| a.id.cmp(&b.id));
+        changes.first().map(|c| c.currency_id.clone())
+      })
+  }
+
+  /// Every account change in the journal, ordered by date, then transaction
+  /// name, debits before credits. Changes with an unreadable date come first.
+  pub fn postings(&self) -> Vec<Posting<'_>> {
+    let mut postings = self.transactions
+      .values()
+      .flat_map(|transaction| {
+        let date = transaction.parsed_date();
+        transaction.account_changes
+          .values()
+          .map(move |change| Posting { transaction, change, date })
+      })
+      .collect::<Vec<_>>();
+    postings.sort_by_cached_key(|p| (
+      p.date,
+      p.transaction.name.clone(),
+      p.transaction.id.clone(),
+      p.change.credit_or_debit == BalanceType::Credit,
+      p.change.id.clone(),
+    ));
+    postings
+  }
+}
@@ -0,0 +1,611 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  account_change::AccountChange,
+  currency::CurrencyId,
+  journal::Journal,
+  parse_error::ParseError,
+  transaction::{Transaction, TransactionId},
+};
+
+/// Category used when a QIF transaction has neither a category nor splits.
+const UNCATEGORIZED: &str = "Uncategorized";
+
+enum Section {
+  None,
+  Transactions(AccountType),
+  Categories,
+  Account,
+}
+
+struct QifSplit {
+  category: String,
+  memo: String,
+  amount: f64,
+}
+
+struct QifTransaction {
+  line: usize,
+  account: Option<(String, AccountType)>,
+  date: Option<NaiveDate>,
+  amount: Option<f64>,
+  payee: String,
+  memo: String,
+  category: String,
+  splits: Vec<QifSplit>,
+}
+
+impl QifTransaction {
+  fn new(line: usize, account: Option<(String, AccountType)>) -> QifTransaction {
+    QifTransaction {
+      line,
+      account,
+      date: None,
+      amount: None,
+      payee: String::new(),
+      memo: String::new(),
+      category: String::new(),
+      splits: Vec::new(),
+    }
+  }
+
+  fn total(&self) -> f64 {
+    self.amount.unwrap_or_else(|| self.splits.iter().map(|s| s.amount).sum())
+  }
+}
+
+/// Imports the bank, credit card and category sections of a QIF file into
+/// `journal`. Transactions are posted against `account_id` unless the file
+/// names its own accounts with `!Account` blocks. Categories become revenue
+/// or expense accounts, created on demand, and the journal's import rules
+/// are applied to the new transactions. Returns the ids of the transactions
+/// imported; nothing is imported if any line fails to parse.
+pub fn import_qif(
+  journal: &mut Journal,
+  source: &str,
+  account_id: Option<&AccountId>,
+  currency_id: &CurrencyId,
+) -> Result<Vec<TransactionId>, ParseError> {
+  let (categories, records) = parse(source)?;
+
+  for record in &records {
+    if record.account.is_none() && account_id.is_none() {
+      return Err(ParseError::new(
+        record.line,
+        "Transaction is not under an !Account block and no account was chosen"
+      ));
+    }
+  }
+
+  for (name, account_type) in &categories {
+    journal.find_or_create_account(name, *account_type);
+  }
+
+  let mut transaction_ids = Vec::new();
+  for record in records {
+    let target = match &record.account {
+      Some((name, account_type)) => journal.find_or_create_account(name, *account_type),
+      None => account_id.cloned().unwrap_or_default(),
+    };
+    let total = record.total();
+
+    let mut transaction = Transaction::new(
+      record.date.map(Transaction::format_date).unwrap_or_default(),
+      first_non_empty(&[&record.payee, &record.memo, &record.category]),
+      record.memo.clone(),
+    );
+
+    // Positive QIF amounts increase a bank account and pay down a card,
+    // both of which are debits to the QIF account.
+    if let Some(change) = change_for(&target, currency_id, total, BalanceType::Debit) {
+      transaction.add_account_change(change);
+    }
+
+    let counter_lines = match record.splits.is_empty() {
+      true => vec![(record.category.clone(), String::new(), total)],
+      false => record.splits
+        .iter()
+        .map(|s| (s.category.clone(), s.memo.clone(), s.amount))
+        .collect(),
+    };
+    for (category, memo, amount) in counter_lines {
+      let counter = resolve_category(journal, &categories, &category, amount);
+      if let Some(change) = change_for(&counter, currency_id, amount, BalanceType::Credit) {
+        transaction.add_account_change(AccountChange { memo, ..change });
+      }
+    }
+
+    transaction_ids.push(transaction.id.clone());
+    journal.transactions.insert(transaction.id.clone(), transaction);
+  }
+
+  journal.apply_rules(&transaction_ids);
+  Ok(transaction_ids)
+}
+
+/// Writes every transaction touching `account_id` as a QIF file, preceded
+/// by a `!Type:Cat` section listing the journal's revenue and expense
+/// accounts. Liability accounts are written as credit card sections.
+pub fn export_qif(journal: &Journal, account_id: &AccountId) -> Option<String> {
+  let account = journal.accounts.get(account_id)?;
+  let mut output = String::new();
+
+  let mut categories = journal.accounts
+    .values()
+    .filter_map(|a| match a.account_type {
+      AccountType::Revenue => Some(("I", journal.account_full_name(&a.id)?)),
+      AccountType::Expense => Some(("E", journal.account_full_name(&a.id)?)),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  categories.sort_by(|a, b| a.1.cmp(&b.1));
+  if !categories.is_empty() {
+    output.push_str("!Type:Cat\n");
+    for (flag, name) in categories {
+      output.push_str(&format!("N{}\n{}\n^\n", name, flag));
+    }
+  }
+
+  output.push_str(match account.account_type {
+    AccountType::Liability => "!Type:CCard\n",
+    _ => "!Type:Bank\n",
+  });
+
+  let mut transactions = journal.transactions
+    .values()
+    .filter(|t| t.account_changes.values().any(|c| &c.account_id == account_id))
+    .collect::<Vec<_>>();
+  transactions.sort_by(|a, b| {
+    (a.parsed_date(), &a.date).cmp(&(b.parsed_date(), &b.date))
+  });
+
+  for transaction in transactions {
+    let (own, counters): (Vec<_>, Vec<_>) = transaction.account_changes
+      .values()
+      .partition(|c| &c.account_id == account_id);
+
+    let date = match transaction.parsed_date() {
+      Some(date) => date.format("%m/%d/%Y").to_string(),
+      None => transaction.date.clone(),
+    };
+    let total: f64 = own.iter().map(|c| c.signed_amount(BalanceType::Debit)).sum();
+
+    output.push_str(&format!("D{}\nT{:.2}\n", date, total));
+    if !transaction.name.is_empty() {
+      output.push_str(&format!("P{}\n", transaction.name));
+    }
+    if !transaction.description.is_empty() {
+      output.push_str(&format!("M{}\n", transaction.description));
+    }
+
+    let mut counters = counters
+      .iter()
+      .map(|c| (
+        category_name(journal, &c.account_id),
+        c.memo.as_str(),
+        c.signed_amount(BalanceType::Credit),
+      ))
+      .collect::<Vec<_>>();
+    counters.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    match counters.as_slice() {
+      [] => {},
+      [(category, "", _)] => output.push_str(&format!("L{}\n", category)),
+      splits => {
+        for (category, memo, amount) in splits {
+          output.push_str(&format!("S{}\n", category));
+          if !memo.is_empty() {
+            output.push_str(&format!("E{}\n", memo));
+          }
+          output.push_str(&format!("${:.2}\n", amount));
+        }
+      },
+    }
+    output.push_str("^\n");
+  }
+
+  Some(output)
+}
+
+fn parse(
+  source: &str
+) -> Result<(HashMap<String, AccountType>, Vec<QifTransaction>), ParseError> {
+  let mut categories: HashMap<String, AccountType> = HashMap::new();
+  let mut records: Vec<QifTransaction> = Vec::new();
+
+  let mut section = Section::None;
+  let mut current_account: Option<(String, AccountType)> = None;
+  let mut record: Option<QifTransaction> = None;
+  let mut category: (String, AccountType) = (String::new(), AccountType::Expense);
+  let mut account: (String, AccountType) = (String::new(), AccountType::Asset);
+
+  for (index, raw) in source.lines().enumerate() {
+    let line = index + 1;
+    let text = raw.trim_start_matches('\u{feff}').trim_end();
+    if text.is_empty() {
+      continue;
+    }
+
+    if text.starts_with('!') {
+      let header = text.to_ascii_lowercase();
+      let header = header.trim_end();
+      section = if header == "!account" {
+        Section::Account
+      } else if header.starts_with("!option") || header.starts_with("!clear") {
+        continue;
+      } else {
+        match header.strip_prefix("!type:").map(str::trim) {
+          Some("bank") | Some("cash") | Some("oth a") => {
+            Section::Transactions(AccountType::Asset)
+          },
+          Some("ccard") | Some("oth l") => Section::Transactions(AccountType::Liability),
+          Some("cat") => Section::Categories,
+          _ => return Err(ParseError::new(line, format!("Unsupported section \"{}\"", text))),
+        }
+      };
+      record = None;
+      continue;
+    }
+
+    let (code, value) = text.split_at(text.chars().next().map_or(0, char::len_utf8));
+    let value = value.trim();
+
+    match section {
+      Section::None => {
+        return Err(ParseError::new(line, "Expected a !Type header"));
+      },
+      Section::Account => match code {
+        "N" => account.0 = value.to_string(),
+        "T" => account.1 = match value.to_ascii_lowercase().as_str() {
+          "ccard" | "oth l" => AccountType::Liability,
+          _ => AccountType::Asset,
+        },
+        "^" => {
+          if account.0.is_empty() {
+            return Err(ParseError::new(line, "Account block has no name"));
+          }
+          current_account = Some(std::mem::replace(
+            &mut account,
+            (String::new(), AccountType::Asset)
+          ));
+        },
+        _ => {},
+      },
+      Section::Categories => match code {
+        "N" => category.0 = value.to_string(),
+        "I" => category.1 = AccountType::Revenue,
+        "E" => category.1 = AccountType::Expense,
+        "^" => {
+          if category.0.is_empty() {
+            return Err(ParseError::new(line, "Category has no name"));
+          }
+          let (name, account_type) = std::mem::replace(
+            &mut category,
+            (String::new(), AccountType::Expense)
+          );
+          categories.insert(name, account_type);
+        },
+        _ => {},
+      },
+      Section::Transactions(account_type) => {
+        // An !Account block without a T field defaults to Asset, so let
+        // the section header decide the type in that case.
+        let current = record.get_or_insert_with(|| {
+          let account = current_account
+            .clone()
+            .map(|(name, declared)| match declared {
+              AccountType::Asset => (name, account_type),
+              _ => (name, declared),
+            });
+          QifTransaction::new(line, account)
+        });
+        match code {
+          "D" => current.date = Some(parse_date(value).ok_or_else(|| {
+            ParseError::new(line, format!("Invalid date \"{}\"", value))
+          })?),
+          "T" => current.amount = Some(parse_amount(value, line)?),
+          "U" => if current.amount.is_none() {
+            current.amount = Some(parse_amount(value, line)?);
+          },
+          "P" => current.payee = value.to_string(),
+          "M" => current.memo = value.to_string(),
+          "L" => current.category = value.to_string(),
+          "S" => current.splits.push(QifSplit {
+            category: value.to_string(),
+            memo: String::new(),
+            amount: 0.0
+          }),
+          "E" => match current.splits.last_mut() {
+            None => return Err(ParseError::new(line, "Split memo before any split")),
+            Some(split) => split.memo = value.to_string(),
+          },
+          "$" => match current.splits.last_mut() {
+            None => return Err(ParseError::new(line, "Split amount before any split")),
+            Some(split) => split.amount = parse_amount(value, line)?,
+          },
+          "^" => {
+            let finished = record.take().unwrap_or_else(|| QifTransaction::new(line, None));
+            if finished.date.is_none() {
+              return Err(ParseError::new(finished.line, "Transaction has no date"));
+            }
+            let split_total = finished.splits.iter().fold(0.0, |total, s| total + s.amount);
+            match finished.amount {
+              Some(amount) if !finished.splits.is_empty() && (amount - split_total).abs() > 0.005 => {
+                return Err(ParseError::new(finished.line, format!(
+                  "Splits add up to {:.2} but the transaction total is {:.2}",
+                  split_total,
+                  amount
+                )));
+              },
+              _ => {},
+            }
+            records.push(finished);
+          },
+          "N" | "C" | "A" | "%" | "X" => {},
+          _ => return Err(ParseError::new(line, format!("Unknown field \"{}\"", text))),
+        }
+      },
+    }
+  }
+
+  if let Some(unfinished) = record {
+    return Err(ParseError::new(unfinished.line, "Transaction is missing its closing ^"));
+  }
+
+  Ok((categories, records))
+}
+
+fn parse_amount(value: &str, line: usize) -> Result<f64, ParseError> {
+  value
+    .replace([',', ' '], "")
+    .parse()
+    .map_err(|_| ParseError::new(line, format!("Invalid amount \"{}\"", value)))
+}
+
+/// Accepts `M/D/YYYY`, `M/D/YY`, Quicken's `M/D'YY` and ISO `YYYY-MM-DD`.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return Some(date);
+  }
+
+  let normalized = value.replace(['\'', '-'], "/").replace(' ', "");
+  let parts = normalized.split('/').collect::<Vec<_>>();
+  let [month, day, year] = parts.as_slice() else {
+    return None;
+  };
+  let month: u32 = month.parse().ok()?;
+  let day: u32 = day.parse().ok()?;
+  let mut year: i32 = year.parse().ok()?;
+  if year < 100 {
+    year += if year < 70 { 2000 } else { 1900 };
+  }
+  NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// Maps a QIF category field (`Food:Groceries`, `Food/Class` or a transfer
+/// such as `[Savings]`) to an account, creating it if needed.
+fn resolve_category(
+  journal: &mut Journal,
+  categories: &HashMap<String, AccountType>,
+  category: &str,
+  amount: f64,
+) -> AccountId {
+  let category = category.trim();
+  if let Some(transfer) = category
+    .strip_prefix('[')
+    .and_then(|c| c.split(']').next())
+  {
+    return journal.find_or_create_account(transfer, AccountType::Asset);
+  }
+
+  let name = category.split('/').next().unwrap_or_default().trim();
+  let name = match name.is_empty() {
+    true => UNCATEGORIZED,
+    false => name,
+  };
+  let root = name.split(':').next().unwrap_or_default();
+  let account_type = categories
+    .get(name)
+    .or_else(|| categories.get(root))
+    .copied()
+    .unwrap_or(match amount > 0.0 {
+      true => AccountType::Revenue,
+      false => AccountType::Expense,
+    });
+  journal.find_or_create_account(name, account_type)
+}
+
+/// Builds a change where a positive amount is posted as `positive_side`.
+fn change_for(
+  account_id: &AccountId,
+  currency_id: &CurrencyId,
+  amount: f64,
+  positive_side: BalanceType,
+) -> Option<AccountChange> {
+  if amount == 0.0 {
+    return None;
+  }
+  let side = match (amount > 0.0, positive_side) {
+    (true, side) => side,
+    (false, BalanceType::Debit) => BalanceType::Credit,
+    (false, BalanceType::Credit) => BalanceType::Debit,
+  };
+  Some(AccountChange::new(account_id.clone(), currency_id.clone(), side, amount.abs()))
+}
+
+fn category_name(journal: &Journal, account_id: &AccountId) -> String {
+  let name = journal.account_full_name(account_id).unwrap_or_default();
+  match journal.accounts.get(account_id).map(|a| a.account_type) {
+    Some(AccountType::Revenue) | Some(AccountType::Expense) => name,
+    _ => format!("[{}]", name),
+  }
+}
+
+fn first_non_empty(candidates: &[&String]) -> String {
+  candidates
+    .iter()
+    .find(|c| !c.is_empty())
+    .map(|c| c.to_string())
+    .unwrap_or_else(|| "QIF Transaction".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Account, side and amount.
+  type Posting = (String, &'static str, String);
+
+  /// Each transaction's date, name and postings, sorted for comparing
+  /// journals.
+  fn summary(journal: &Journal) -> Vec<(String, String, Vec<Posting>)> {
+    let mut transactions = journal.transactions
+      .values()
+      .map(|t| {
+        let mut postings = t.account_changes
+          .values()
+          .map(|c| (
+            journal.account_full_name(&c.account_id).unwrap_or_default(),
+            c.credit_or_debit.as_str(),
+            format!("{:.2}", c.amount),
+          ))
+          .collect::<Vec<_>>();
+        postings.sort();
+        (t.date.clone(), t.name.clone(), postings)
+      })
+      .collect::<Vec<_>>();
+    transactions.sort();
+    transactions
+  }
+
+  fn posting(account: &str, side: &'static str, amount: &str) -> Posting {
+    (account.to_string(), side, amount.to_string())
+  }
+
+  #[test]
+  fn parse_dates() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+    assert_eq!(parse_date("1/15/2024"), date(2024, 1, 15));
+    assert_eq!(parse_date("01/15/24"), date(2024, 1, 15));
+    assert_eq!(parse_date("1/15'99"), date(1999, 1, 15));
+    assert_eq!(parse_date("2024-01-15"), date(2024, 1, 15));
+    assert_eq!(parse_date("13/1/2024"), None);
+    assert_eq!(parse_date("yesterday"), None);
+  }
+
+  #[test]
+  fn import_categories_and_splits() {
+    let source = "\
+!Type:Cat
+NSalary
+I
+^
+!Type:Bank
+D1/15/2024
+T-1,234.50
+PGrocery Store
+LFood:Groceries
+^
+D1/31/2024
+T2000
+PEmployer
+SSalary
+EMarch bonus
+$2500
+S[Savings]
+$-500
+^
+";
+    let mut journal = Journal::new("Test".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let ids = import_qif(&mut journal, source, Some(&checking), &"usd".to_string()).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(summary(&journal), vec![
+      ("2024/1/15".to_string(), "Grocery Store".to_string(), vec![
+        posting("Checking", "Credit", "1234.50"),
+        posting("Food:Groceries", "Debit", "1234.50"),
+      ]),
+      ("2024/1/31".to_string(), "Employer".to_string(), vec![
+        posting("Checking", "Debit", "2000.00"),
+        posting("Salary", "Credit", "2500.00"),
+        posting("Savings", "Debit", "500.00"),
+      ]),
+    ]);
+    let account_type = |name: &str| journal.accounts[journal.find_account(name).unwrap()].account_type;
+    assert_eq!(account_type("Salary"), AccountType::Revenue);
+    assert_eq!(account_type("Food"), AccountType::Expense);
+    assert_eq!(account_type("Savings"), AccountType::Asset);
+    let memos = journal.transactions
+      .values()
+      .flat_map(|t| t.account_changes.values())
+      .filter(|c| !c.memo.is_empty())
+      .map(|c| (journal.account_full_name(&c.account_id).unwrap(), c.memo.as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(memos, vec![("Salary".to_string(), "March bonus")]);
+  }
+
+  #[test]
+  fn import_errors_name_the_line() {
+    let mut journal = Journal::new("Test".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let error = |source: &str| {
+      import_qif(&mut journal.clone(), source, Some(&checking), &"usd".to_string()).unwrap_err()
+    };
+    assert_eq!(error("!Type:Invst\n").line, 1);
+    assert_eq!(error("!Type:Bank\nD1/1/2024\nTabc\n^\n").line, 3);
+    assert_eq!(error("!Type:Bank\nD1/1/2024\nT5\n").line, 2);
+    assert_eq!(error("!Type:Bank\nT5\n^\n").line, 2);
+    let unbalanced = error("!Type:Bank\nD1/1/2024\nT10\nSFood\n$4\nSFuel\n$5\n^\n");
+    assert_eq!(unbalanced.line, 2);
+    assert!(unbalanced.message.contains("9.00"), "{}", unbalanced.message);
+    // Nothing is imported when a later transaction fails
+    let source = "!Type:Bank\nD1/1/2024\nT5\n^\nTx\n^\n";
+    assert!(import_qif(&mut journal, source, Some(&checking), &"usd".to_string()).is_err());
+    assert!(journal.transactions.is_empty());
+  }
+
+  #[test]
+  fn export_then_import_round_trips() {
+    let mut journal = Journal::new("Test".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let food = journal.find_or_create_account("Food", AccountType::Expense);
+    let fuel = journal.find_or_create_account("Fuel", AccountType::Expense);
+    let savings = journal.find_or_create_account("Savings", AccountType::Asset);
+    let currency_id = "usd".to_string();
+    let mut add = |date: NaiveDate, name: &str, changes: Vec<(&AccountId, BalanceType, f64)>| {
+      let mut transaction = Transaction::new(
+        Transaction::format_date(date),
+        name.to_string(),
+        String::new()
+      );
+      for (account_id, side, amount) in changes {
+        let change = AccountChange::new(account_id.clone(), currency_id.clone(), side, amount);
+        transaction.add_account_change(change);
+      }
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    };
+    let date = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+    add(date(1), "Market", vec![
+      (&food, BalanceType::Debit, 30.0),
+      (&fuel, BalanceType::Debit, 20.0),
+      (&checking, BalanceType::Credit, 50.0),
+    ]);
+    add(date(2), "Transfer", vec![
+      (&savings, BalanceType::Debit, 100.0),
+      (&checking, BalanceType::Credit, 100.0),
+    ]);
+    for change in journal.transactions.values_mut().flat_map(|t| t.account_changes.values_mut()) {
+      if change.account_id == fuel {
+        change.memo = "Diesel".to_string();
+      }
+    }
+
+    let exported = export_qif(&journal, &checking).unwrap();
+    assert!(exported.contains("SFuel\nEDiesel\n$-20.00\n"), "{}", exported);
+    let mut imported = Journal::new("Imported".to_string());
+    let target = imported.find_or_create_account("Checking", AccountType::Asset);
+    import_qif(&mut imported, &exported, Some(&target), &currency_id).unwrap();
+    assert_eq!(summary(&imported), summary(&journal));
+    assert_eq!(export_qif(&imported, &target).unwrap(), exported);
+  }
+}
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use chrono::{Months, NaiveDate};
+use regex::{Regex, RegexBuilder};
+use serde::{Serialize, Deserialize};
+use crate::{
+  account::BalanceType,
+  account_change::AccountChange,
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  posting::Posting,
+  report::DateRange,
+  transaction::Transaction,
+};
+
+/// Postings selected by a line of search terms, e.g.
+/// `account:Expenses:Food date:2024-01.. amount:>50 desc:/coffee/`.
+///
+/// Every term must hold for a posting to be selected:
+/// - `account:TEXT` the account's full name contains the text
+/// - `desc:TEXT`, or a bare word, the transaction name or description
+///   contains the text
+/// - `date:FROM..TO` with either end optional, where each end is a year,
+///   month or day such as `2024`, `2024-01` or `2024-01-15`, both ends
+///   included
+/// - `amount:>50`, `>=`, `<`, `<=`, `=` or `amount:10..50`
+/// - `cur:TEXT` the currency's symbol or name
+/// - `side:debit` or `side:credit`
+/// - `tag:TEXT` the transaction has the tag
+///
+/// Text is matched ignoring case. Values holding spaces are wrapped in
+/// double quotes, taken literally, or in slashes, taken as a regular
+/// expression such as `desc:/^coffee$/`. `not:` before a term inverts it.
+///
+/// Queries are stored as their text and parsed again when loaded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(into = "StoredQuery", try_from = "StoredQuery")]
+pub struct Query {
+  pub text: String,
+  terms: Vec<Term>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredQuery {
+  text: String,
+}
+
+impl From<Query> for StoredQuery {
+  fn from(query: Query) -> StoredQuery {
+    StoredQuery { text: query.text }
+  }
+}
+
+impl TryFrom<StoredQuery> for Query {
+  type Error = String;
+
+  fn try_from(stored: StoredQuery) -> Result<Query, String> {
+    Query::parse(&stored.text)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+  negated: bool,
+  condition: Condition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+  Account(TextMatch),
+  Description(TextMatch),
+  Date(DateRange),
+  Amount(Comparison, f64),
+  /// Amounts between the bounds, both included.
+  AmountRange(Option<f64>, Option<f64>),
+  Currency(TextMatch),
+  Side(BalanceType),
+  Tag(TextMatch),
+}
+
+/// Lowercase text, or a regular expression ignoring case.
+#[derive(Debug, Clone)]
+enum TextMatch {
+  Text(String),
+  Pattern(Regex),
+}
+
+impl TextMatch {
+  fn parse(value: &str, pattern: bool) -> Result<TextMatch, String> {
+    match pattern {
+      false => Ok(TextMatch::Text(value.to_lowercase())),
+      true => RegexBuilder::new(value)
+        .case_insensitive(true)
+        .build()
+        .map(TextMatch::Pattern)
+        .map_err(|error| format!("Invalid pattern /{}/: {}", value, error)),
+    }
+  }
+
+  /// Whether `text` contains the text or matches the pattern.
+  fn is_within(&self, text: &str) -> bool {
+    match self {
+      TextMatch::Text(value) => text.to_lowercase().contains(value),
+      TextMatch::Pattern(regex) => regex.is_match(text),
+    }
+  }
+
+  /// Whether `text` is the text or matches the pattern.
+  fn is_whole(&self, text: &str) -> bool {
+    match self {
+      TextMatch::Text(value) => &text.to_lowercase() == value,
+      TextMatch::Pattern(regex) => regex.is_match(text),
+    }
+  }
+}
+
+impl PartialEq for TextMatch {
+  fn eq(&self, other: &TextMatch) -> bool {
+    match (self, other) {
+      (TextMatch::Text(a), TextMatch::Text(b)) => a == b,
+      (TextMatch::Pattern(a), TextMatch::Pattern(b)) => a.as_str() == b.as_str(),
+      _ => false,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+  Less,
+  LessOrEqual,
+  Equal,
+  GreaterOrEqual,
+  Greater,
+}
+
+impl Comparison {
+  fn holds(self, amount: f64, value: f64) -> bool {
+    match self {
+      Comparison::Less => amount < value,
+      Comparison::LessOrEqual => amount <= value,
+      Comparison::Equal => (amount - value).abs() < 0.005,
+      Comparison::GreaterOrEqual => amount >= value,
+      Comparison::Greater => amount > value,
+    }
+  }
+}
+
+impl Query {
+  pub fn parse(text: &str) -> Result<Query, String> {
+    let mut terms = Vec::new();
+    for token in tokenize(text)? {
+      let (negated, text) = match token.text.strip_prefix("not:") {
+        Some(rest) => (true, rest),
+        None => (false, token.text.as_str()),
+      };
+      terms.push(Term { negated, condition: parse_term(text, token.pattern)? });
+    }
+    Ok(Query { text: text.trim().to_string(), terms })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.terms.is_empty()
+  }
+
+  /// Whether every term holds for `change` in `transaction`.
+  pub fn matches(
+    &self,
+    journal: &Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+    transaction: &Transaction,
+    change: &AccountChange,
+  ) -> bool {
+    self.terms.iter().all(|term| {
+      let holds = match &term.condition {
+        Condition::Account(text) => journal
+          .account_full_name(&change.account_id)
+          .is_some_and(|name| text.is_within(&name)),
+        Condition::Description(text) => text.is_within(&transaction.name)
+          || text.is_within(&transaction.description),
+        Condition::Date(range) => range.contains(transaction.parsed_date()),
+        Condition::Amount(comparison, value) => comparison.holds(change.amount, *value),
+        Condition::AmountRange(min, max) => min.is_none_or(|min| change.amount >= min)
+          && max.is_none_or(|max| change.amount <= max),
+        Condition::Currency(text) => currencies
+          .get(&change.currency_id)
+          .is_some_and(|c| text.is_whole(&c.symbol) || text.is_whole(&c.name)),
+        Condition::Side(side) => change.credit_or_debit == *side,
+        Condition::Tag(tag) => transaction.tags.iter().any(|t| tag.is_whole(t)),
+      };
+      holds != term.negated
+    })
+  }
+
+  /// Whether any posting of `transaction` is selected.
+  pub fn matches_transaction(
+    &self,
+    journal: &Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+    transaction: &Transaction,
+  ) -> bool {
+    transaction.account_changes
+      .values()
+      .any(|change| self.matches(journal, currencies, transaction, change))
+  }
+
+  /// Selected postings in the order of `Journal::postings`.
+  pub fn postings<'a>(
+    &self,
+    journal: &'a Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+  ) -> Vec<Posting<'a>> {
+    journal.postings()
+      .into_iter()
+      .filter(|p| self.matches(journal, currencies, p.transaction, p.change))
+      .collect()
+  }
+
+  /// A copy of `journal` keeping only the selected postings, for running
+  /// reports over them.
+  pub fn select(&self, journal: &Journal, currencies: &HashMap<CurrencyId, Currency>) -> Journal {
+    let mut selected = journal.clone();
+    for transaction in selected.transactions.values_mut() {
+      let original = &journal.transactions[&transaction.id];
+      transaction.account_changes
+        .retain(|_, change| self.matches(journal, currencies, original, change));
+    }
+    selected.transactions.retain(|_, t| !t.account_changes.is_empty());
+    selected
+  }
+}
+
+/// A search term, with whether its value was delimited by slashes.
+#[derive(Debug, PartialEq)]
+struct Token {
+  text: String,
+  pattern: bool,
+}
+
+/// Splits on whitespace, keeping double quoted and slash delimited values
+/// together.
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = Vec::new();
+  let mut token = Token { text: String::new(), pattern: false };
+  let mut delimiter: Option<char> = None;
+  for c in text.chars() {
+    match delimiter {
+      Some(end) if c == end => delimiter = None,
+      Some(_) => token.text.push(c),
+      None if c == '"' || (c == '/' && (token.text.is_empty() || token.text.ends_with(':'))) => {
+        delimiter = Some(c);
+        token.pattern = c == '/';
+      },
+      None if c.is_whitespace() => {
+        if !token.text.is_empty() {
+          tokens.push(std::mem::replace(&mut token, Token { text: String::new(), pattern: false }));
+        }
+      },
+      None => token.text.push(c),
+    }
+  }
+  if let Some(end) = delimiter {
+    return Err(format!("Missing closing {}", end));
+  }
+  if !token.text.is_empty() {
+    tokens.push(token);
+  }
+  Ok(tokens)
+}
+
+fn parse_term(token: &str, pattern: bool) -> Result<Condition, String> {
+  let (field, value) = match token.split_once(':') {
+    Some((field, value)) if is_field(field) => (field, value),
+    _ => return Ok(Condition::Description(TextMatch::parse(token, pattern)?)),
+  };
+  if value.is_empty() {
+    return Err(format!("Missing value for {}:", field));
+  }
+  Ok(match field {
+    "account" | "acct" => Condition::Account(TextMatch::parse(value, pattern)?),
+    "desc" | "description" => Condition::Description(TextMatch::parse(value, pattern)?),
+    "cur" | "currency" => Condition::Currency(TextMatch::parse(value, pattern)?),
+    "tag" => Condition::Tag(TextMatch::parse(value, pattern)?),
+    _ if pattern => return Err(format!("{}: does not take a pattern", field)),
+    "date" => Condition::Date(parse_date_range(value)?),
+    "side" => match value.to_lowercase().as_str() {
+      "debit" | "d" => Condition::Side(BalanceType::Debit),
+      "credit" | "c" => Condition::Side(BalanceType::Credit),
+      _ => return Err(format!("Unknown side \"{}\"", value)),
+    },
+    _ => parse_amount(value)?,
+  })
+}
+
+fn is_field(field: &str) -> bool {
+  matches!(
+    field,
+    "account" | "acct" | "desc" | "description" | "cur" | "currency" | "date" | "side" | "tag" | "amount" | "amt"
+  )
+}
+
+fn parse_amount(value: &str) -> Result<Condition, String> {
+  let number = |text: &str| text
+    .trim()
+    .parse::<f64>()
+    .map_err(|_| format!("Invalid amount \"{}\"", text));
+  let optional_number = |text: &str| match text.is_empty() {
+    true => Ok(None),
+    false => number(text).map(Some),
+  };
+  if let Some((min, max)) = value.split_once("..") {
+    return Ok(Condition::AmountRange(optional_number(min)?, optional_number(max)?));
+  }
+  let (comparison, rest) = [
+    (">=", Comparison::GreaterOrEqual),
+    ("<=", Comparison::LessOrEqual),
+    (">", Comparison::Greater),
+    ("<", Comparison::Less),
+    ("=", Comparison::Equal),
+  ]
+    .into_iter()
+    .find_map(|(prefix, comparison)| value.strip_prefix(prefix).map(|rest| (comparison, rest)))
+    .unwrap_or((Comparison::Equal, value));
+  Ok(Condition::Amount(comparison, number(rest)?))
+}
+
+fn parse_date_range(value: &str) -> Result<DateRange, String> {
+  let (start, end) = match value.split_once("..") {
+    None => {
+      let (first, last) = parse_period(value)?;
+      return Ok(DateRange::new(Some(first), Some(last)));
+    },
+    Some(ends) => ends,
+  };
+  let start = match start.is_empty() {
+    true => None,
+    false => Some(parse_period(start)?.0),
+  };
+  let end = match end.is_empty() {
+    true => None,
+    false => Some(parse_period(end)?.1),
+  };
+  Ok(DateRange::new(start, end))
+}
+
+/// First and last day of a year, month or day.
+fn parse_period(text: &str) -> Result<(NaiveDate, NaiveDate), String> {
+  let invalid = || format!("Invalid date \"{}\"", text);
+  let parts = text
+    .split('-')
+    .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+    .collect::<Result<Vec<_>, _>>()?;
+  let (first, months) = match parts[..] {
+    [year] => (NaiveDate::from_ymd_opt(year as i32, 1, 1), 12),
+    [year, month] => (NaiveDate::from_ymd_opt(year as i32, month, 1), 1),
+    [year, month, day] => {
+      let date = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(invalid)?;
+      return Ok((date, date));
+    },
+    _ => return Err(invalid()),
+  };
+  let first = first.ok_or_else(invalid)?;
+  let last = first
+    .checked_add_months(Months::new(months))
+    .and_then(|next| next.pred_opt())
+    .ok_or_else(invalid)?;
+  Ok((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::account::AccountType;
+
+  fn token(text: &str, pattern: bool) -> Token {
+    Token { text: text.to_string(), pattern }
+  }
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  fn conditions(text: &str) -> Vec<(bool, Condition)> {
+    Query::parse(text)
+      .unwrap()
+      .terms
+      .into_iter()
+      .map(|t| (t.negated, t.condition))
+      .collect()
+  }
+
+  #[test]
+  fn tokenize_keeps_delimited_values_together() {
+    assert_eq!(
+      tokenize(r#"desc:"big store"  account:/^exp.*food$/ coffee"#).unwrap(),
+      vec![
+        token("desc:big store", false),
+        token("account:^exp.*food$", true),
+        token("coffee", false),
+      ]
+    );
+    assert_eq!(tokenize("/a b/").unwrap(), vec![token("a b", true)]);
+    assert_eq!(tokenize("a/b").unwrap(), vec![token("a/b", false)]);
+    assert!(tokenize("desc:\"open").is_err());
+    assert!(tokenize("desc:/open").is_err());
+  }
+
+  #[test]
+  fn parse_fields() {
+    let text = |value: &str| TextMatch::Text(value.to_string());
+    assert_eq!(
+      conditions("Account:x acct:Food desc:Coffee bare cur:USD tag:Trip side:c"),
+      vec![
+        (false, Condition::Description(text("account:x"))),
+        (false, Condition::Account(text("food"))),
+        (false, Condition::Description(text("coffee"))),
+        (false, Condition::Description(text("bare"))),
+        (false, Condition::Currency(text("usd"))),
+        (false, Condition::Tag(text("trip"))),
+        (false, Condition::Side(BalanceType::Credit)),
+      ]
+    );
+    assert!(Query::parse("desc:").is_err());
+    assert!(Query::parse("side:both").is_err());
+    assert!(Query::parse("date:/2024/").is_err());
+  }
+
+  #[test]
+  fn parse_date_ranges() {
+    assert_eq!(
+      conditions("date:2024-01.. date:..2024 date:2024-02-29"),
+      vec![
+        (false, Condition::Date(DateRange::new(Some(date(2024, 1, 1)), None))),
+        (false, Condition::Date(DateRange::new(None, Some(date(2024, 12, 31))))),
+        (false, Condition::Date(DateRange::new(Some(date(2024, 2, 29)), Some(date(2024, 2, 29))))),
+      ]
+    );
+    assert_eq!(
+      parse_date_range("2024-02").unwrap(),
+      DateRange::new(Some(date(2024, 2, 1)), Some(date(2024, 2, 29)))
+    );
+    assert!(parse_date_range("2024-13").is_err());
+    assert!(parse_date_range("2023-02-29").is_err());
+    assert!(parse_date_range("last-week").is_err());
+  }
+
+  #[test]
+  fn parse_amount_comparisons() {
+    assert_eq!(
+      conditions("amount:>50 amt:>=5 amount:<1.5 amount:<=2 amount:=3 amount:4 amount:10..20 amount:..7"),
+      vec![
+        (false, Condition::Amount(Comparison::Greater, 50.0)),
+        (false, Condition::Amount(Comparison::GreaterOrEqual, 5.0)),
+        (false, Condition::Amount(Comparison::Less, 1.5)),
+        (false, Condition::Amount(Comparison::LessOrEqual, 2.0)),
+        (false, Condition::Amount(Comparison::Equal, 3.0)),
+        (false, Condition::Amount(Comparison::Equal, 4.0)),
+        (false, Condition::AmountRange(Some(10.0), Some(20.0))),
+        (false, Condition::AmountRange(None, Some(7.0))),
+      ]
+    );
+    assert!(Query::parse("amount:>fifty").is_err());
+  }
+
+  #[test]
+  fn parse_not() {
+    assert_eq!(
+      conditions("not:tag:trip not:coffee"),
+      vec![
+        (true, Condition::Tag(TextMatch::Text("trip".to_string()))),
+        (true, Condition::Description(TextMatch::Text("coffee".to_string()))),
+      ]
+    );
+  }
+
+  #[test]
+  fn matches_postings() {
+    let mut journal = Journal::new("Test".to_string());
+    let food = journal.find_or_create_account("Expenses:Food", AccountType::Expense);
+    let cash = journal.find_or_create_account("Assets:Cash", AccountType::Asset);
+    let currency = Currency::new("Dollars".to_string(), "$".to_string());
+    let currencies = HashMap::from([(currency.id.clone(), currency.clone())]);
+    let mut transaction = Transaction::new(
+      Transaction::format_date(date(2024, 1, 15)),
+      "Coffee Shop".to_string(),
+      String::new()
+    );
+    transaction.add_tag("trip");
+    let debit = AccountChange::new(food, currency.id.clone(), BalanceType::Debit, 60.0);
+    let credit = AccountChange::new(cash, currency.id.clone(), BalanceType::Credit, 60.0);
+
+    let matches = |text: &str, change: &AccountChange| {
+      Query::parse(text).unwrap().matches(&journal, &currencies, &transaction, change)
+    };
+    assert!(matches("desc:/^coffee/ account:food amount:>50 date:2024-01 cur:$ tag:TRIP", &debit));
+    assert!(!matches("desc:/^shop/", &debit));
+    assert!(!matches(r#"desc:"^coffee""#, &debit));
+    assert!(matches("account:/^assets:/ side:credit", &credit));
+    assert!(!matches("not:account:/^assets:/", &credit));
+    assert!(matches("not:date:2023", &debit));
+    assert!(!matches("amount:..50", &debit));
+  }
+
+  #[test]
+  fn stored_as_text() {
+    let query = Query::parse(r#"desc:/^coffee$/ not:tag:"road trip" amount:10..20"#).unwrap();
+    let json = serde_json::to_string(&query).unwrap();
+    assert_eq!(json, r#"{"text":"desc:/^coffee$/ not:tag:\"road trip\" amount:10..20"}"#);
+    assert_eq!(serde_json::from_str::<Query>(&json).unwrap(), query);
+    assert!(serde_json::from_str::<Query>(r#"{"text":"amount:>x"}"#).is_err());
+  }
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+use crate::{
+  account::{AccountId, AccountType},
+  account_change::{AccountChangeId, ClearedStatus},
+  currency::{Currency, CurrencyId},
+  journal::Journal,
+  posting::Posting,
+  report::{account_label, currency_label, date_label, Cell, ReportOptions, Table},
+  transaction::TransactionId,
+};
+
+/// An account's cleared balance agreeing with a bank statement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reconciliation {
+  pub statement_date: NaiveDate,
+  pub currency_id: CurrencyId,
+  /// Balance on the statement, on the account's normal balance side.
+  pub ending_balance: f64,
+  /// Day the reconciliation was finished.
+  pub reconciled_on: NaiveDate,
+  /// Postings reconciled by this statement.
+  pub postings: usize,
+}
+
+impl Journal {
+  /// Postings to the account in the currency, dated on or before the
+  /// statement, that an earlier statement has not reconciled yet.
+  pub fn reconcilable_postings(
+    &self,
+    account_id: &AccountId,
+    currency_id: &CurrencyId,
+    statement_date: NaiveDate,
+  ) -> Vec<Posting<'_>> {
+    self.postings()
+      .into_iter()
+      .filter(|p| &p.change.account_id == account_id && &p.change.currency_id == currency_id)
+      .filter(|p| p.change.status != ClearedStatus::Reconciled)
+      .filter(|p| p.date.is_none_or(|date| date <= statement_date))
+      .collect()
+  }
+
+  /// Balance of the cleared and reconciled postings up to the statement
+  /// date, on the account's normal balance side.
+  pub fn cleared_balance(
+    &self,
+    account_id: &AccountId,
+    currency_id: &CurrencyId,
+    statement_date: NaiveDate,
+  ) -> f64 {
+    let balance_type = match self.accounts.get(account_id) {
+      None => return 0.0,
+      Some(account) => account.balance_type,
+    };
+    self.postings()
+      .into_iter()
+      .filter(|p| &p.change.account_id == account_id && &p.change.currency_id == currency_id)
+      .filter(|p| p.change.status != ClearedStatus::Uncleared)
+      .filter(|p| p.date.is_none_or(|date| date <= statement_date))
+      .fold(0.0, |total, p| total + p.change.signed_amount(balance_type))
+  }
+
+  pub fn set_cleared_status(
+    &mut self,
+    transaction_id: &TransactionId,
+    change_id: &AccountChangeId,
+    status: ClearedStatus,
+  ) {
+    if let Some(change) = self.transactions
+      .get_mut(transaction_id)
+      .and_then(|t| t.account_changes.get_mut(change_id))
+    {
+      change.status = status;
+    }
+  }
+
+  /// Marks the cleared postings covered by the statement as reconciled and
+  /// records the reconciliation on the account.
+  pub fn finish_reconciliation(
+    &mut self,
+    account_id: &AccountId,
+    mut reconciliation: Reconciliation,
+  ) {
+    let cleared = self.reconcilable_postings(
+      account_id,
+      &reconciliation.currency_id,
+      reconciliation.statement_date
+    )
+      .into_iter()
+      .filter(|p| p.change.status == ClearedStatus::Cleared)
+      .map(|p| (p.transaction.id.clone(), p.change.id.clone()))
+      .collect::<Vec<_>>();
+    for (transaction_id, change_id) in &cleared {
+      self.set_cleared_status(transaction_id, change_id, ClearedStatus::Reconciled);
+    }
+    reconciliation.postings = cleared.len();
+    if let Some(account) = self.accounts.get_mut(account_id) {
+      account.reconciliations.push(reconciliation);
+    }
+  }
+
+  /// Number of reconciled postings in `transaction_ids`, counting only those
+  /// to `account_id` if given.
+  pub fn count_reconciled(&self, transaction_ids: &[TransactionId], account_id: Option<&AccountId>) -> usize {
+    transaction_ids
+      .iter()
+      .filter_map(|id| self.transactions.get(id))
+      .flat_map(|t| t.account_changes.values())
+      .filter(|c| c.is_reconciled() && account_id.is_none_or(|id| &c.account_id == id))
+      .count()
+  }
+
+  /// The statement most recently reconciled in the currency.
+  pub fn last_reconciliation(&self, account_id: &AccountId, currency_id: &CurrencyId) -> Option<&Reconciliation> {
+    self.accounts
+      .get(account_id)?
+      .reconciliations
+      .iter()
+      .filter(|r| &r.currency_id == currency_id)
+      .max_by_key(|r| (r.statement_date, r.reconciled_on))
+  }
+}
+
+/// Postings to asset and liability accounts not yet cleared against a
+/// statement, with the total outstanding per account and currency.
+pub fn uncleared_report(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - Uncleared Items ({})", journal.name, options.range.describe()),
+    &["Date", "Transaction", "Account", "Amount"],
+  );
+
+  let mut totals: BTreeMap<(String, String), (CurrencyId, f64)> = BTreeMap::new();
+  for posting in journal.postings().into_iter().filter(|p| options.range.contains(p.date)) {
+    let change = posting.change;
+    let account = match journal.accounts.get(&change.account_id) {
+      Some(account) if matches!(account.account_type, AccountType::Asset | AccountType::Liability) => account,
+      _ => continue,
+    };
+    if change.status != ClearedStatus::Uncleared {
+      continue;
+    }
+    let amount = change.signed_amount(account.balance_type);
+    let label = account_label(journal, &change.account_id);
+    table.rows.push(vec![
+      Cell::Text(date_label(posting.date, &posting.transaction.date)),
+      Cell::Text(posting.transaction.name.clone()),
+      Cell::Text(label.clone()),
+      Cell::Amount(amount, change.currency_id.clone()),
+    ]);
+    totals
+      .entry((label, currency_label(currencies, &change.currency_id)))
+      .or_insert((change.currency_id.clone(), 0.0))
+      .1 += amount;
+  }
+  for ((label, _), (currency_id, total)) in totals {
+    table.rows.push(vec![
+      Cell::Text("Total".to_string()),
+      Cell::Empty,
+      Cell::Text(label),
+      Cell::Amount(total, currency_id),
+    ]);
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    account::BalanceType,
+    account_change::AccountChange,
+    transaction::Transaction,
+  };
+
+  fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+  }
+
+  /// Checking with a reconciled deposit, a cleared and an uncleared
+  /// withdrawal before the January statement, a cleared deposit after it
+  /// and a cleared posting in another currency.
+  fn journal() -> (Journal, AccountId) {
+    let mut journal = Journal::new("Test".to_string());
+    let checking = journal.find_or_create_account("Checking", AccountType::Asset);
+    let other = journal.find_or_create_account("Other", AccountType::Equity);
+    let postings = [
+      ("Deposit", date(1, 5), BalanceType::Debit, 100.0, "usd", ClearedStatus::Reconciled),
+      ("Rent", date(1, 20), BalanceType::Credit, 30.0, "usd", ClearedStatus::Cleared),
+      ("Cafe", date(1, 25), BalanceType::Credit, 10.0, "usd", ClearedStatus::Uncleared),
+      ("Refund", date(1, 28), BalanceType::Debit, 5.0, "eur", ClearedStatus::Cleared),
+      ("Salary", date(2, 3), BalanceType::Debit, 50.0, "usd", ClearedStatus::Cleared),
+    ];
+    for (name, posted, side, amount, currency_id, status) in postings {
+      let mut transaction = Transaction::new(
+        Transaction::format_date(posted),
+        name.to_string(),
+        String::new()
+      );
+      let change = AccountChange::new(checking.clone(), currency_id.to_string(), side, amount);
+      transaction.add_account_change(AccountChange { status, ..change });
+      let counter_side = match side {
+        BalanceType::Debit => BalanceType::Credit,
+        BalanceType::Credit => BalanceType::Debit,
+      };
+      let counter = AccountChange::new(other.clone(), currency_id.to_string(), counter_side, amount);
+      transaction.add_account_change(counter);
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+    (journal, checking)
+  }
+
+  fn names(postings: Vec<Posting>) -> Vec<String> {
+    let mut names = postings.iter().map(|p| p.transaction.name.clone()).collect::<Vec<_>>();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn reconcilable_postings_skip_reconciled_and_later_postings() {
+    let (journal, checking) = journal();
+    let usd = "usd".to_string();
+    assert_eq!(names(journal.reconcilable_postings(&checking, &usd, date(1, 31))), vec!["Cafe", "Rent"]);
+    assert_eq!(
+      names(journal.reconcilable_postings(&checking, &usd, date(2, 29))),
+      vec!["Cafe", "Rent", "Salary"]
+    );
+    assert_eq!(names(journal.reconcilable_postings(&checking, &usd, date(1, 19))), Vec::<String>::new());
+  }
+
+  #[test]
+  fn cleared_balance_counts_cleared_and_reconciled_up_to_the_statement() {
+    let (journal, checking) = journal();
+    let usd = "usd".to_string();
+    assert_eq!(journal.cleared_balance(&checking, &usd, date(1, 31)), 70.0);
+    assert_eq!(journal.cleared_balance(&checking, &usd, date(2, 29)), 120.0);
+    assert_eq!(journal.cleared_balance(&checking, &usd, date(1, 10)), 100.0);
+    assert_eq!(journal.cleared_balance(&checking, &"eur".to_string(), date(1, 31)), 5.0);
+  }
+
+  #[test]
+  fn finish_reconciliation_reconciles_only_cleared_postings_in_range() {
+    let (mut journal, checking) = journal();
+    let usd = "usd".to_string();
+    let reconciliation = Reconciliation {
+      statement_date: date(1, 31),
+      currency_id: usd.clone(),
+      ending_balance: 70.0,
+      reconciled_on: date(2, 5),
+      postings: 0,
+    };
+    journal.finish_reconciliation(&checking, reconciliation);
+
+    let status = |name: &str| journal.transactions
+      .values()
+      .find(|t| t.name == name)
+      .and_then(|t| t.account_changes.values().find(|c| c.account_id == checking))
+      .map(|c| c.status)
+      .unwrap();
+    assert_eq!(status("Deposit"), ClearedStatus::Reconciled);
+    assert_eq!(status("Rent"), ClearedStatus::Reconciled);
+    assert_eq!(status("Cafe"), ClearedStatus::Uncleared);
+    assert_eq!(status("Refund"), ClearedStatus::Cleared);
+    assert_eq!(status("Salary"), ClearedStatus::Cleared);
+
+    let last = journal.last_reconciliation(&checking, &usd).unwrap();
+    assert_eq!((last.statement_date, last.postings), (date(1, 31), 1));
+    assert_eq!(names(journal.reconcilable_postings(&checking, &usd, date(1, 31))), vec!["Cafe"]);
+    assert_eq!(journal.cleared_balance(&checking, &usd, date(1, 31)), 70.0);
+  }
+}
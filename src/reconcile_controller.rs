@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use chrono::{NaiveDate, Utc};
+use crate::{
+  account::AccountId,
+  account_change::{AccountChangeId, ClearedStatus},
+  currency::{format_amount, Currency, CurrencyId},
+  journal::Journal,
+  page::Navigation,
+  reconcile::Reconciliation,
+  report::{currency_label, date_label},
+  transaction::TransactionId,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_page_with_labels,
+    input_until_parsed,
+    select_with_labels,
+  },
+};
+
+/// Postings shown at a time while ticking them off.
+const PAGE_SIZE: usize = 15;
+
+/// Asks before changing `count` reconciled postings, which would no longer
+/// agree with the statements that reconciled them. Nothing is asked when
+/// there are none.
+pub fn confirm_reconciled(count: usize, action: &str) -> Result<bool, std::io::Error> {
+  match count {
+    0 => Ok(true),
+    _ => confirm_default(reconciled_warning(count, action)),
+  }
+}
+
+/// The question `confirm_reconciled` asks, shared with the TUI.
+pub fn reconciled_warning(count: usize, action: &str) -> String {
+  format!(
+    "{} reconciled postings would no longer match their statements. {} anyway?",
+    count,
+    action
+  )
+}
+
+/// Reconciles the account against a statement: postings are ticked off as
+/// cleared until their balance matches the statement's ending balance.
+/// Cleared marks can be kept to finish later.
+pub fn reconcile_account(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  account_id: &Option<AccountId>,
+  navigation: &mut Navigation,
+) {
+  let (journal, account_id) = match (journal, account_id) {
+    (Some(journal), Some(account_id)) if journal.accounts.contains_key(account_id) => (journal, account_id),
+    _ => return on_not_found(navigation),
+  };
+
+  match reconcile(currencies, journal, account_id) {
+    Err(error) => on_error(navigation, error),
+    Ok(()) => navigation.back(),
+  }
+}
+
+fn reconcile(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &mut Journal,
+  account_id: &AccountId,
+) -> Result<(), std::io::Error> {
+  enum Selection {
+    Toggle(TransactionId, AccountChangeId, ClearedStatus),
+    Finish,
+    Later,
+    Cancel,
+  }
+
+  let mut currency_ids = journal.postings()
+    .into_iter()
+    .filter(|p| &p.change.account_id == account_id)
+    .map(|p| p.change.currency_id.clone())
+    .collect::<Vec<_>>();
+  currency_ids.sort();
+  currency_ids.dedup();
+  let currency_id = match currency_ids.len() {
+    0 => {
+      println!("The account has no postings to reconcile");
+      return Ok(());
+    },
+    1 => currency_ids.remove(0),
+    _ => {
+      let options = currency_ids
+        .into_iter()
+        .map(|id| LabeledItem::from(currency_label(currencies, &id), id))
+        .collect::<Vec<_>>();
+      select_with_labels("Currency".to_string(), &options)?.clone()
+    },
+  };
+  let format = |amount: f64| format_amount(currencies, &currency_id, amount);
+
+  if let Some(last) = journal.last_reconciliation(account_id, &currency_id) {
+    println!(
+      "Last reconciled to {} on {} with {}",
+      last.statement_date,
+      last.reconciled_on,
+      format(last.ending_balance)
+    );
+  }
+  let today = Utc::now().date_naive();
+  let statement_date: NaiveDate = input_until_parsed(
+    "Statement End Date (YYYY-MM-DD):".to_string(),
+    today.to_string()
+  )?;
+  let ending_balance: f64 = input_until_parsed(
+    "Statement Ending Balance:".to_string(),
+    format!("{:.2}", journal.cleared_balance(account_id, &currency_id, statement_date))
+  )?;
+
+  // Restored on cancel
+  let original = journal.reconcilable_postings(account_id, &currency_id, statement_date)
+    .into_iter()
+    .map(|p| (p.transaction.id.clone(), p.change.id.clone(), p.change.status))
+    .collect::<Vec<_>>();
+  let balance_type = journal.accounts[account_id].balance_type;
+
+  loop {
+    let cleared = journal.cleared_balance(account_id, &currency_id, statement_date);
+    let difference = ending_balance - cleared;
+
+    let mut options = Vec::new();
+    if difference.abs() < 0.005 {
+      options.push(LabeledItem::from("[Finish]".to_string(), Selection::Finish));
+    }
+    options.push(LabeledItem::from("[Save For Later]".to_string(), Selection::Later));
+    options.push(LabeledItem::from("[Cancel]".to_string(), Selection::Cancel));
+    options.extend(journal.reconcilable_postings(account_id, &currency_id, statement_date)
+      .into_iter()
+      .map(|p| {
+        let mark = match p.change.status {
+          ClearedStatus::Uncleared => "[ ]",
+          _ => "[x]",
+        };
+        LabeledItem::from(
+          format!(
+            "{} {} {} {}",
+            mark,
+            date_label(p.date, &p.transaction.date),
+            p.transaction.name,
+            format(p.change.signed_amount(balance_type))
+          ),
+          Selection::Toggle(p.transaction.id.clone(), p.change.id.clone(), p.change.status)
+        )
+      })
+    );
+
+    let prompt = format!(
+      "Statement {}, Cleared {}, Difference {}",
+      format(ending_balance),
+      format(cleared),
+      format(difference)
+    );
+    match fuzzy_page_with_labels(prompt, &options, PAGE_SIZE)? {
+      Selection::Toggle(transaction_id, change_id, status) => {
+        let status = match status {
+          ClearedStatus::Uncleared => ClearedStatus::Cleared,
+          _ => ClearedStatus::Uncleared,
+        };
+        journal.set_cleared_status(transaction_id, change_id, status);
+      },
+      Selection::Later => return Ok(()),
+      Selection::Cancel => {
+        for (transaction_id, change_id, status) in &original {
+          journal.set_cleared_status(transaction_id, change_id, *status);
+        }
+        return Ok(());
+      },
+      Selection::Finish => {
+        let reconciliation = Reconciliation {
+          statement_date,
+          currency_id: currency_id.clone(),
+          ending_balance,
+          reconciled_on: today,
+          postings: 0,
+        };
+        journal.finish_reconciliation(account_id, reconciliation);
+        println!("Reconciled to {}", statement_date);
+        return Ok(());
+      },
+    }
+  }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use chrono::{Duration, Months, NaiveDate};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account_change::{AccountChange, AccountChangeId},
+  transaction::Transaction,
+};
+
+pub type RecurringId = String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+  Daily,
+  Weekly,
+  Monthly,
+  Yearly,
+}
+
+impl Frequency {
+  pub fn all() -> Vec<Frequency> {
+    vec![Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly]
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Frequency::Daily => "Daily",
+      Frequency::Weekly => "Weekly",
+      Frequency::Monthly => "Monthly",
+      Frequency::Yearly => "Yearly",
+    }
+  }
+
+  fn unit(self) -> &'static str {
+    match self {
+      Frequency::Daily => "days",
+      Frequency::Weekly => "weeks",
+      Frequency::Monthly => "months",
+      Frequency::Yearly => "years",
+    }
+  }
+}
+
+/// Repeats every `every` days, weeks, months or years from the start date.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rule {
+  pub frequency: Frequency,
+  pub every: u32,
+}
+
+impl Rule {
+  pub fn describe(self) -> String {
+    match self.every {
+      1 => self.frequency.as_str().to_string(),
+      every => format!("Every {} {}", every, self.frequency.unit()),
+    }
+  }
+}
+
+/// A transaction that repeats on a schedule. Each occurrence becomes a copy
+/// of the template's account changes dated on the occurrence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurringTransaction {
+  pub id: RecurringId,
+  pub name: String,
+  pub description: String,
+  pub account_changes: HashMap<AccountChangeId, AccountChange>,
+  pub rule: Rule,
+  pub start: NaiveDate,
+  /// Last date an occurrence may fall on.
+  pub end: Option<NaiveDate>,
+  /// Index of the first occurrence not yet posted or skipped.
+  pub next_occurrence: u32,
+}
+
+impl RecurringTransaction {
+  pub fn new(
+    name: String,
+    description: String,
+    rule: Rule,
+    start: NaiveDate,
+  ) -> RecurringTransaction {
+    RecurringTransaction {
+      id: Uuid::new_v4().to_string(),
+      name,
+      description,
+      account_changes: HashMap::new(),
+      rule,
+      start,
+      end: None,
+      next_occurrence: 0,
+    }
+  }
+
+  /// Copies the account changes of `transaction` as the template.
+  pub fn from_transaction(
+    transaction: &Transaction,
+    rule: Rule,
+    start: NaiveDate,
+  ) -> RecurringTransaction {
+    let mut recurring = RecurringTransaction::new(
+      transaction.name.clone(),
+      transaction.description.clone(),
+      rule,
+      start,
+    );
+    for change in transaction.account_changes.values() {
+      let change = copy_change(change);
+      recurring.account_changes.insert(change.id.clone(), change);
+    }
+    recurring
+  }
+
+  /// Date of the `index`th occurrence. Counting from the start keeps monthly
+  /// rules on the 31st from drifting after shorter months.
+  pub fn occurrence(&self, index: u32) -> Option<NaiveDate> {
+    let steps = index.checked_mul(self.rule.every.max(1))?;
+    let date = match self.rule.frequency {
+      Frequency::Daily => self.start.checked_add_signed(Duration::days(steps as i64)),
+      Frequency::Weekly => self.start.checked_add_signed(Duration::weeks(steps as i64)),
+      Frequency::Monthly => self.start.checked_add_months(Months::new(steps)),
+      Frequency::Yearly => self.start.checked_add_months(Months::new(steps.checked_mul(12)?)),
+    }?;
+    match self.end {
+      Some(end) if date > end => None,
+      _ => Some(date),
+    }
+  }
+
+  /// The next occurrence to post, if the schedule has not ended.
+  pub fn next_due(&self) -> Option<NaiveDate> {
+    self.occurrence(self.next_occurrence)
+  }
+
+  /// Switches to `rule` without posting any occurrence twice. The next
+  /// unposted date stays the next one; once the schedule has ended, the new
+  /// rule continues from the last posted occurrence.
+  pub fn reschedule(&mut self, rule: Rule) {
+    match self.next_due() {
+      Some(next_due) => {
+        self.start = next_due;
+        self.next_occurrence = 0;
+      },
+      None => if let Some(last_posted) = self.next_occurrence
+        .checked_sub(1)
+        .and_then(|index| self.occurrence(index))
+      {
+        self.start = last_posted;
+        self.next_occurrence = 1;
+      },
+    }
+    self.rule = rule;
+  }
+
+  /// Whether an occurrence is waiting on or before `today`.
+  pub fn is_due(&self, today: NaiveDate) -> bool {
+    self.next_due().is_some_and(|date| date <= today)
+  }
+
+  /// A new transaction for the next occurrence, dated `date`.
+  pub fn to_transaction(&self, date: NaiveDate) -> Transaction {
+    let mut transaction = Transaction::new(
+      Transaction::format_date(date),
+      self.name.clone(),
+      self.description.clone(),
+    );
+    for change in self.account_changes.values() {
+      transaction.add_account_change(copy_change(change));
+    }
+    transaction
+  }
+
+  /// Moves past the next occurrence once it was posted or skipped.
+  pub fn advance(&mut self) {
+    self.next_occurrence += 1;
+  }
+}
+
+fn copy_change(change: &AccountChange) -> AccountChange {
+  AccountChange {
+    memo: change.memo.clone(),
+    ..AccountChange::new(
+      change.account_id.clone(),
+      change.currency_id.clone(),
+      change.credit_or_debit,
+      change.amount,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  fn recurring(frequency: Frequency, every: u32, start: NaiveDate) -> RecurringTransaction {
+    RecurringTransaction::new(String::new(), String::new(), Rule { frequency, every }, start)
+  }
+
+  #[test]
+  fn occurrences_keep_month_end_days() {
+    let monthly = recurring(Frequency::Monthly, 1, date(2024, 1, 31));
+    assert_eq!(monthly.occurrence(1), Some(date(2024, 2, 29)));
+    assert_eq!(monthly.occurrence(2), Some(date(2024, 3, 31)));
+    assert_eq!(monthly.occurrence(3), Some(date(2024, 4, 30)));
+    assert_eq!(monthly.occurrence(12), Some(date(2025, 1, 31)));
+
+    let yearly = recurring(Frequency::Yearly, 1, date(2024, 2, 29));
+    assert_eq!(yearly.occurrence(1), Some(date(2025, 2, 28)));
+    assert_eq!(yearly.occurrence(4), Some(date(2028, 2, 29)));
+  }
+
+  #[test]
+  fn occurrences_use_custom_intervals() {
+    let start = date(2024, 1, 15);
+    assert_eq!(recurring(Frequency::Daily, 10, start).occurrence(2), Some(date(2024, 2, 4)));
+    assert_eq!(recurring(Frequency::Weekly, 2, start).occurrence(3), Some(date(2024, 2, 26)));
+    assert_eq!(recurring(Frequency::Monthly, 3, start).occurrence(2), Some(date(2024, 7, 15)));
+    assert_eq!(recurring(Frequency::Yearly, 2, start).occurrence(1), Some(date(2026, 1, 15)));
+    assert_eq!(recurring(Frequency::Monthly, 1, start).occurrence(0), Some(start));
+  }
+
+  #[test]
+  fn occurrences_stop_at_the_end_date() {
+    let mut monthly = recurring(Frequency::Monthly, 1, date(2024, 1, 31));
+    monthly.end = Some(date(2024, 3, 31));
+    assert_eq!(monthly.occurrence(2), Some(date(2024, 3, 31)));
+    assert_eq!(monthly.occurrence(3), None);
+
+    monthly.next_occurrence = 3;
+    assert_eq!(monthly.next_due(), None);
+    assert!(!monthly.is_due(date(2030, 1, 1)));
+  }
+
+  #[test]
+  fn reschedule_keeps_the_next_unposted_date() {
+    let mut recurring = recurring(Frequency::Monthly, 1, date(2024, 1, 15));
+    recurring.next_occurrence = 2;
+    recurring.reschedule(Rule { frequency: Frequency::Weekly, every: 1 });
+    assert_eq!(recurring.next_due(), Some(date(2024, 3, 15)));
+    assert_eq!(recurring.occurrence(1), Some(date(2024, 3, 22)));
+  }
+
+  #[test]
+  fn reschedule_after_the_end_continues_from_the_last_posting() {
+    let mut recurring = recurring(Frequency::Monthly, 1, date(2024, 1, 31));
+    recurring.end = Some(date(2024, 3, 31));
+    recurring.next_occurrence = 3;
+    recurring.reschedule(Rule { frequency: Frequency::Monthly, every: 2 });
+    assert_eq!(recurring.next_due(), None);
+
+    // Lifting the end date continues after the last posted occurrence
+    recurring.end = None;
+    assert_eq!(recurring.next_due(), Some(date(2024, 5, 31)));
+  }
+
+  #[test]
+  fn reschedule_before_anything_is_posted() {
+    let mut recurring = recurring(Frequency::Monthly, 1, date(2024, 1, 15));
+    recurring.reschedule(Rule { frequency: Frequency::Daily, every: 3 });
+    assert_eq!(recurring.next_due(), Some(date(2024, 1, 15)));
+    assert_eq!(recurring.occurrence(1), Some(date(2024, 1, 18)));
+  }
+}
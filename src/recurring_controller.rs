@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use chrono::{NaiveDate, Utc};
+use crate::{
+  account::BalanceType,
+  account_change,
+  currency::{format_amount, Currency, CurrencyId},
+  journal::{Journal, JournalId},
+  page::{Navigation, Page},
+  recurring::{Frequency, RecurringId, RecurringTransaction, Rule},
+  transaction::Transaction,
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    confirm_default,
+    fuzzy_input_with_labels,
+    input_default,
+    input_optional_parsed,
+    input_until_number_with_validation,
+    input_until_parsed,
+    select_with_labels,
+  },
+};
+
+pub fn view_recurring(journal: Option<&mut Journal>, navigation: &mut Navigation) {
+  enum Selection {
+    Recurring(RecurringId),
+    NewRecurring,
+    DueItems,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = journal.recurring
+    .values()
+    .map(|r| LabeledItem::from(recurring_label(r), Selection::Recurring(r.id.clone())))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.extend(vec![
+    LabeledItem::from("[New From Transaction]".to_string(), Selection::NewRecurring),
+    LabeledItem::from("[Due Items]".to_string(), Selection::DueItems),
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+  ]);
+
+  let selection = match select_with_labels("Recurring Transactions".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let result = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::DueItems => return navigation.go(Page::DueItems),
+    Selection::NewRecurring => new_recurring(journal),
+    Selection::Recurring(id) => edit_recurring(journal, id),
+  };
+  if let Err(error) = result {
+    on_error(navigation, error)
+  }
+}
+
+fn new_recurring(journal: &mut Journal) -> Result<(), std::io::Error> {
+  let mut options = journal.transactions
+    .values()
+    .map(|t| LabeledItem::from(format!("{}: {}", t.date, t.name), t))
+    .collect::<Vec<_>>();
+  if options.is_empty() {
+    println!("Recurring transactions are copied from an existing transaction");
+    return Ok(());
+  }
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  let transaction = *fuzzy_input_with_labels("Template Transaction:".to_string(), &options)?;
+
+  let rule = input_rule(None)?;
+  let start: NaiveDate = input_until_parsed(
+    "First Date (YYYY-MM-DD):".to_string(),
+    Utc::now().date_naive().to_string()
+  )?;
+  let mut recurring = RecurringTransaction::from_transaction(transaction, rule, start);
+  recurring.end = input_end(None)?;
+  journal.recurring.insert(recurring.id.clone(), recurring);
+  Ok(())
+}
+
+fn edit_recurring(journal: &mut Journal, recurring_id: &RecurringId) -> Result<(), std::io::Error> {
+  enum Selection {
+    Back,
+    Schedule,
+    End,
+    Delete,
+  }
+
+  let recurring = match journal.recurring.get_mut(recurring_id) {
+    None => return Ok(()),
+    Some(recurring) => recurring,
+  };
+  let options = vec![
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+    LabeledItem::from("[Schedule]".to_string(), Selection::Schedule),
+    LabeledItem::from("[End Date]".to_string(), Selection::End),
+    LabeledItem::from("[Delete]".to_string(), Selection::Delete),
+  ];
+
+  match select_with_labels(recurring_label(recurring), &options)? {
+    Selection::Back => {},
+    Selection::Schedule => {
+      let rule = input_rule(Some(recurring.rule))?;
+      recurring.reschedule(rule);
+    },
+    Selection::End => recurring.end = input_end(recurring.end)?,
+    Selection::Delete => {
+      let prompt = format!("Are you sure you want to delete \"{}\"?", recurring.name);
+      if confirm_default(prompt)? {
+        journal.recurring.remove(recurring_id);
+      }
+    },
+  }
+  Ok(())
+}
+
+/// Walks through every occurrence due today or earlier in all journals,
+/// oldest first, posting, skipping or editing each one.
+pub fn review_due_items(
+  journals: &mut HashMap<JournalId, Journal>,
+  currencies: &HashMap<CurrencyId, Currency>,
+  navigation: &mut Navigation,
+) {
+  match post_due_items(journals, currencies) {
+    Err(error) => on_error(navigation, error),
+    Ok(()) => navigation.back(),
+  }
+}
+
+fn post_due_items(
+  journals: &mut HashMap<JournalId, Journal>,
+  currencies: &HashMap<CurrencyId, Currency>,
+) -> Result<(), std::io::Error> {
+  enum Selection {
+    Post,
+    Edit,
+    Skip,
+    Later,
+  }
+
+  let today = Utc::now().date_naive();
+  loop {
+    let due = journals
+      .values()
+      .flat_map(|j| j.recurring.values().map(move |r| (j, r)))
+      .filter(|(_, r)| r.is_due(today))
+      .filter_map(|(j, r)| Some((r.next_due()?, j.name.clone(), j.id.clone(), r.id.clone())))
+      .min();
+    let (date, journal_name, journal_id, recurring_id) = match due {
+      None => return Ok(()),
+      Some(due) => due,
+    };
+    let journal = match journals.get_mut(&journal_id) {
+      None => return Ok(()),
+      Some(journal) => journal,
+    };
+    let recurring = match journal.recurring.get(&recurring_id) {
+      None => return Ok(()),
+      Some(recurring) => recurring,
+    };
+
+    let options = vec![
+      LabeledItem::from("[Post]".to_string(), Selection::Post),
+      LabeledItem::from("[Edit and Post]".to_string(), Selection::Edit),
+      LabeledItem::from("[Skip]".to_string(), Selection::Skip),
+      LabeledItem::from("[Later]".to_string(), Selection::Later),
+    ];
+    let prompt = format!("Due {}: {} ({})", date, recurring.name, journal_name);
+    let transaction = match select_with_labels(prompt, &options)? {
+      Selection::Later => return Ok(()),
+      Selection::Skip => None,
+      Selection::Post => Some(recurring.to_transaction(date)),
+      Selection::Edit => {
+        let transaction = recurring.to_transaction(date);
+        Some(edit_occurrence(journal, currencies, transaction, date)?)
+      },
+    };
+    if let Some(recurring) = journal.recurring.get_mut(&recurring_id) {
+      recurring.advance();
+    }
+    if let Some(transaction) = transaction {
+      journal.transactions.insert(transaction.id.clone(), transaction);
+    }
+  }
+}
+
+/// Lets a single occurrence differ from its template before it is posted.
+/// The amounts are asked again until the occurrence balances.
+fn edit_occurrence(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  mut transaction: Transaction,
+  date: NaiveDate,
+) -> Result<Transaction, std::io::Error> {
+  let date: NaiveDate = input_until_parsed("Date (YYYY-MM-DD):".to_string(), date.to_string())?;
+  transaction.date = Transaction::format_date(date);
+  transaction.name = input_default("Transaction Name:".to_string(), transaction.name)?;
+  transaction.description = input_default(
+    "Transaction Description:".to_string(),
+    transaction.description
+  )?;
+  let mut changes = transaction.account_changes.values_mut().collect::<Vec<_>>();
+  changes.sort_by_cached_key(|c| (
+    c.credit_or_debit == BalanceType::Credit,
+    journal.account_full_name(&c.account_id).unwrap_or_default(),
+  ));
+  loop {
+    for change in changes.iter_mut() {
+      change.amount = input_until_number_with_validation(
+        format!(
+          "{} {} Amount:",
+          journal.account_full_name(&change.account_id).unwrap_or_default(),
+          change.credit_or_debit.as_str()
+        ),
+        change.amount.to_string(),
+        &|amount: f64| amount > 0.0
+      )?;
+    }
+    let imbalances = account_change::imbalances(changes.iter().map(|c| &**c));
+    if imbalances.is_empty() {
+      break;
+    }
+    for (id, imbalance) in &imbalances {
+      println!("  Unbalanced by {}", format_amount(currencies, id, imbalance.abs()));
+    }
+  }
+  Ok(transaction)
+}
+
+fn input_rule(current: Option<Rule>) -> Result<Rule, std::io::Error> {
+  let mut options = Frequency::all()
+    .into_iter()
+    .map(|frequency| LabeledItem::from(frequency.as_str().to_string(), frequency))
+    .collect::<Vec<_>>();
+  if let Some(current) = current {
+    options.sort_by_key(|o| o.item != current.frequency);
+  }
+  let frequency = *select_with_labels("Repeats".to_string(), &options)?;
+  let every = input_until_number_with_validation(
+    "Every how many periods:".to_string(),
+    current.map_or(1, |rule| rule.every).to_string(),
+    &|every: u32| every > 0
+  )?;
+  Ok(Rule { frequency, every })
+}
+
+fn input_end(current: Option<NaiveDate>) -> Result<Option<NaiveDate>, std::io::Error> {
+  input_optional_parsed::<NaiveDate>("End Date (YYYY-MM-DD, empty for none):".to_string(), current)
+}
+
+fn recurring_label(recurring: &RecurringTransaction) -> String {
+  match recurring.next_due() {
+    None => format!("{} ({}, ended)", recurring.name, recurring.rule.describe()),
+    Some(date) => format!("{} ({}, next {})", recurring.name, recurring.rule.describe(), date),
+  }
+}
@@ -0,0 +1,539 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+use crate::{
+  account::{AccountId, AccountType, BalanceType},
+  currency::{format_amount, Currency, CurrencyId},
+  budget::budget_report,
+  cash_flow::cash_flow,
+  general_ledger::general_ledger,
+  journal::Journal,
+  periodic::{periodic_report, Interval},
+  posting::Posting,
+  reconcile::uncleared_report,
+  utility::error_token,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+  Text(String),
+  Amount(f64, CurrencyId),
+  Empty,
+}
+
+/// Tabular output shared by every report, so each one can be printed or
+/// handed to any exporter.
+#[derive(Debug, Clone)]
+pub struct Table {
+  pub title: String,
+  pub headers: Vec<String>,
+  pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+  pub fn new(title: String, headers: &[&str]) -> Table {
+    Table {
+      title,
+      headers: headers.iter().map(|h| h.to_string()).collect(),
+      rows: Vec::new(),
+    }
+  }
+
+  /// Renders the table with aligned columns for the terminal.
+  pub fn render(&self, currencies: &HashMap<CurrencyId, Currency>) -> String {
+    let rows = self.rows
+      .iter()
+      .map(|row| row.iter().map(|cell| match cell {
+        Cell::Text(text) => text.clone(),
+        Cell::Amount(amount, currency_id) => format_amount(currencies, currency_id, *amount),
+        Cell::Empty => String::new(),
+      }).collect::<Vec<_>>())
+      .collect::<Vec<_>>();
+
+    let mut widths = self.headers.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+    for row in &rows {
+      for (i, text) in row.iter().enumerate() {
+        if i < widths.len() {
+          widths[i] = widths[i].max(text.chars().count());
+        }
+      }
+    }
+
+    let is_numeric = |i: usize| self.rows
+      .iter()
+      .any(|row| matches!(row.get(i), Some(Cell::Amount(..))));
+    let format_row = |row: &[String]| row
+      .iter()
+      .enumerate()
+      .map(|(i, text)| match is_numeric(i) {
+        true => format!("{:>width$}", text, width = widths[i]),
+        false => format!("{:<width$}", text, width = widths[i]),
+      })
+      .collect::<Vec<_>>()
+      .join("  ")
+      .trim_end()
+      .to_string();
+
+    let mut output = format!("{}\n", self.title);
+    output.push_str(&format_row(&self.headers));
+    output.push('\n');
+    output.push_str(&"-".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1)));
+    output.push('\n');
+    for row in &rows {
+      output.push_str(&format_row(row));
+      output.push('\n');
+    }
+    output
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+  pub start: Option<NaiveDate>,
+  pub end: Option<NaiveDate>,
+}
+
+impl DateRange {
+  pub fn new(start: Option<NaiveDate>, end: Option<NaiveDate>) -> DateRange {
+    DateRange { start, end }
+  }
+
+  /// Undated postings only fall in an unbounded range.
+  pub fn contains(&self, date: Option<NaiveDate>) -> bool {
+    match date {
+      None => self.start.is_none() && self.end.is_none(),
+      Some(date) => self.start.is_none_or(|start| date >= start)
+        && self.end.is_none_or(|end| date <= end),
+    }
+  }
+
+  /// Everything up to the end of this range.
+  pub fn to_end(&self) -> DateRange {
+    DateRange { start: None, end: self.end }
+  }
+
+  pub fn describe(&self) -> String {
+    match (self.start, self.end) {
+      (None, None) => "All Dates".to_string(),
+      (Some(start), None) => format!("From {}", start),
+      (None, Some(end)) => format!("To {}", end),
+      (Some(start), Some(end)) => format!("{} to {}", start, end),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+  pub range: DateRange,
+  /// Period length for periodic reports.
+  pub interval: Interval,
+  /// Periodic reports show balances at the end of each period rather than
+  /// each period's change.
+  pub cumulative: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+  ChartOfAccounts,
+  Transactions,
+  GeneralLedger,
+  TrialBalance,
+  BalanceSheet,
+  IncomeStatement,
+  CashFlow,
+  Periodic,
+  Budget,
+  Uncleared,
+}
+
+impl ReportKind {
+  pub fn all() -> Vec<ReportKind> {
+    vec![
+      ReportKind::ChartOfAccounts,
+      ReportKind::Transactions,
+      ReportKind::GeneralLedger,
+      ReportKind::TrialBalance,
+      ReportKind::BalanceSheet,
+      ReportKind::IncomeStatement,
+      ReportKind::CashFlow,
+      ReportKind::Periodic,
+      ReportKind::Budget,
+      ReportKind::Uncleared,
+    ]
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ReportKind::ChartOfAccounts => "Chart of Accounts",
+      ReportKind::Transactions => "Transactions",
+      ReportKind::GeneralLedger => "General Ledger",
+      ReportKind::TrialBalance => "Trial Balance",
+      ReportKind::BalanceSheet => "Balance Sheet",
+      ReportKind::IncomeStatement => "Income Statement",
+      ReportKind::CashFlow => "Cash Flow Statement",
+      ReportKind::Periodic => "Periodic Report",
+      ReportKind::Budget => "Budget vs Actual",
+      ReportKind::Uncleared => "Uncleared Items",
+    }
+  }
+
+  /// Name used on the command line.
+  pub fn cli_name(self) -> &'static str {
+    match self {
+      ReportKind::ChartOfAccounts => "accounts",
+      ReportKind::Transactions => "transactions",
+      ReportKind::GeneralLedger => "general-ledger",
+      ReportKind::TrialBalance => "trial-balance",
+      ReportKind::BalanceSheet => "balance-sheet",
+      ReportKind::IncomeStatement => "income-statement",
+      ReportKind::CashFlow => "cash-flow",
+      ReportKind::Periodic => "periodic",
+      ReportKind::Budget => "budget",
+      ReportKind::Uncleared => "uncleared",
+    }
+  }
+
+  pub fn from_cli_name(name: &str) -> Option<ReportKind> {
+    ReportKind::all().into_iter().find(|kind| kind.cli_name() == name)
+  }
+
+  pub fn build(
+    self,
+    journal: &Journal,
+    currencies: &HashMap<CurrencyId, Currency>,
+    options: &ReportOptions,
+  ) -> Table {
+    match self {
+      ReportKind::ChartOfAccounts => chart_of_accounts(journal),
+      ReportKind::Transactions => transactions(journal, currencies, options),
+      ReportKind::GeneralLedger => general_ledger(journal, currencies, options),
+      ReportKind::TrialBalance => trial_balance(journal, currencies, options),
+      ReportKind::BalanceSheet => balance_sheet(journal, currencies, options),
+      ReportKind::IncomeStatement => income_statement(journal, currencies, options),
+      ReportKind::CashFlow => cash_flow(journal, currencies, options),
+      ReportKind::Periodic => periodic_report(journal, currencies, options),
+      ReportKind::Budget => budget_report(journal, currencies, options),
+      ReportKind::Uncleared => uncleared_report(journal, currencies, options),
+    }
+  }
+}
+
+/// Every account by full name with its type and normal balance.
+pub fn chart_of_accounts(journal: &Journal) -> Table {
+  let mut table = Table::new(
+    format!("{} - Chart of Accounts", journal.name),
+    &["Account", "Type", "Balance Type"],
+  );
+
+  let mut accounts = journal.accounts
+    .values()
+    .map(|a| (account_label(journal, &a.id), a))
+    .collect::<Vec<_>>();
+  accounts.sort_by(|a, b| a.0.cmp(&b.0));
+  for (name, account) in accounts {
+    table.rows.push(vec![
+      Cell::Text(name),
+      Cell::Text(account.account_type.as_str().to_string()),
+      Cell::Text(account.balance_type.as_str().to_string()),
+    ]);
+  }
+  table
+}
+
+/// One row per account change in the range.
+pub fn transactions(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - Transactions ({})", journal.name, options.range.describe()),
+    &["Date", "Transaction", "Account", "Debit", "Credit", "Currency"],
+  );
+
+  for posting in journal.postings().into_iter().filter(|p| options.range.contains(p.date)) {
+    let change = posting.change;
+    let amount = Cell::Amount(change.amount, change.currency_id.clone());
+    let (debit, credit) = match change.credit_or_debit {
+      BalanceType::Debit => (amount, Cell::Empty),
+      BalanceType::Credit => (Cell::Empty, amount),
+    };
+    table.rows.push(vec![
+      Cell::Text(date_label(posting.date, &posting.transaction.date)),
+      Cell::Text(posting.transaction.name.clone()),
+      Cell::Text(account_label(journal, &change.account_id)),
+      debit,
+      credit,
+      Cell::Text(currency_label(currencies, &change.currency_id)),
+    ]);
+  }
+  table
+}
+
+/// Debit and credit balance of every account as of the end of the range.
+pub fn trial_balance(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - Trial Balance ({})", journal.name, options.range.to_end().describe()),
+    &["Account", "Currency", "Debit", "Credit"],
+  );
+
+  let mut debit_totals: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  let mut credit_totals: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for ((account_id, currency_id), total) in sorted_totals(journal, currencies, options.range.to_end()) {
+    if total.abs() < 0.005 {
+      continue;
+    }
+    let currency = currency_label(currencies, &currency_id);
+    let amount = Cell::Amount(total.abs(), currency_id.clone());
+    let (debit, credit) = match total > 0.0 {
+      true => {
+        debit_totals.entry(currency.clone()).or_insert((currency_id.clone(), 0.0)).1 += total;
+        (amount, Cell::Empty)
+      },
+      false => {
+        credit_totals.entry(currency.clone()).or_insert((currency_id.clone(), 0.0)).1 -= total;
+        (Cell::Empty, amount)
+      },
+    };
+    table.rows.push(vec![
+      Cell::Text(account_label(journal, &account_id)),
+      Cell::Text(currency),
+      debit,
+      credit,
+    ]);
+  }
+
+  let mut labels = debit_totals.keys().chain(credit_totals.keys()).cloned().collect::<Vec<_>>();
+  labels.sort();
+  labels.dedup();
+  for label in labels {
+    let currency_id = debit_totals
+      .get(&label)
+      .or_else(|| credit_totals.get(&label))
+      .map(|(id, _)| id.clone())
+      .unwrap_or_default();
+    let debit = debit_totals.get(&label).map_or(0.0, |(_, total)| *total);
+    let credit = credit_totals.get(&label).map_or(0.0, |(_, total)| *total);
+    table.rows.push(vec![
+      Cell::Text("Total".to_string()),
+      Cell::Text(label),
+      Cell::Amount(debit, currency_id.clone()),
+      Cell::Amount(credit, currency_id),
+    ]);
+  }
+  table
+}
+
+/// Assets, liabilities and equity as of the end of the range, with revenue
+/// less expenses carried into equity as retained earnings.
+pub fn balance_sheet(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let range = options.range.to_end();
+  let mut table = Table::new(
+    format!("{} - Balance Sheet ({})", journal.name, range.describe()),
+    &["Section", "Account", "Currency", "Balance"],
+  );
+
+  let totals = sorted_totals(journal, currencies, range);
+  let mut retained_earnings: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for ((account_id, currency_id), total) in &totals {
+    let account_type = journal.accounts.get(account_id).map(|a| a.account_type);
+    if let Some(AccountType::Revenue) | Some(AccountType::Expense) = account_type {
+      retained_earnings
+        .entry(currency_label(currencies, currency_id))
+        .or_insert((currency_id.clone(), 0.0))
+        .1 -= total;
+    }
+  }
+
+  let mut liabilities_and_equity: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for (section, account_type) in [
+    ("Assets", AccountType::Asset),
+    ("Liabilities", AccountType::Liability),
+    ("Equity", AccountType::Equity),
+  ] {
+    let mut section_totals = section_rows(
+      &mut table,
+      journal,
+      currencies,
+      &totals,
+      section,
+      account_type
+    );
+    if account_type == AccountType::Equity {
+      for (label, (currency_id, amount)) in &retained_earnings {
+        if amount.abs() < 0.005 {
+          continue;
+        }
+        table.rows.push(vec![
+          Cell::Text(section.to_string()),
+          Cell::Text("Retained Earnings".to_string()),
+          Cell::Text(label.clone()),
+          Cell::Amount(*amount, currency_id.clone()),
+        ]);
+        section_totals.entry(label.clone()).or_insert((currency_id.clone(), 0.0)).1 += amount;
+      }
+    }
+    total_rows(&mut table, &format!("Total {}", section), &section_totals);
+    if account_type != AccountType::Asset {
+      for (label, (currency_id, amount)) in section_totals {
+        liabilities_and_equity.entry(label).or_insert((currency_id, 0.0)).1 += amount;
+      }
+    }
+  }
+  total_rows(&mut table, "Total Liabilities and Equity", &liabilities_and_equity);
+  table
+}
+
+/// Revenue and expenses within the range and the resulting net income.
+pub fn income_statement(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Table {
+  let mut table = Table::new(
+    format!("{} - Income Statement ({})", journal.name, options.range.describe()),
+    &["Section", "Account", "Currency", "Amount"],
+  );
+
+  let totals = sorted_totals(journal, currencies, options.range);
+  let revenue = section_rows(&mut table, journal, currencies, &totals, "Revenue", AccountType::Revenue);
+  total_rows(&mut table, "Total Revenue", &revenue);
+  let expenses = section_rows(&mut table, journal, currencies, &totals, "Expenses", AccountType::Expense);
+  total_rows(&mut table, "Total Expenses", &expenses);
+
+  let mut net_income = revenue;
+  for (label, (currency_id, amount)) in expenses {
+    net_income.entry(label).or_insert((currency_id, 0.0)).1 -= amount;
+  }
+  total_rows(&mut table, "Net Income", &net_income);
+  table
+}
+
+/// A posting of one account with the account's balance after it.
+pub struct RegisterEntry<'a> {
+  pub posting: Posting<'a>,
+  /// Running balance in the posting's currency, positive on the side of the
+  /// account's balance type.
+  pub balance: f64,
+}
+
+/// Postings of `account_id` in date order with running balances kept
+/// separately for each currency.
+pub fn register<'a>(journal: &'a Journal, account_id: &AccountId) -> Vec<RegisterEntry<'a>> {
+  let balance_type = match journal.accounts.get(account_id) {
+    None => return Vec::new(),
+    Some(account) => account.balance_type,
+  };
+
+  let mut balances: HashMap<&CurrencyId, f64> = HashMap::new();
+  journal.postings()
+    .into_iter()
+    .filter(|p| &p.change.account_id == account_id)
+    .map(|posting| {
+      let balance = balances.entry(&posting.change.currency_id).or_insert(0.0);
+      *balance += posting.change.signed_amount(balance_type);
+      RegisterEntry { posting, balance: *balance }
+    })
+    .collect()
+}
+
+/// Debit positive totals per account and currency for postings in `range`.
+pub fn account_totals(
+  journal: &Journal,
+  range: DateRange,
+) -> HashMap<(AccountId, CurrencyId), f64> {
+  let mut totals: HashMap<(AccountId, CurrencyId), f64> = HashMap::new();
+  for posting in journal.postings().into_iter().filter(|p| range.contains(p.date)) {
+    let change = posting.change;
+    *totals
+      .entry((change.account_id.clone(), change.currency_id.clone()))
+      .or_insert(0.0) += change.signed_amount(BalanceType::Debit);
+  }
+  totals
+}
+
+/// Account totals ordered by account full name, then currency.
+fn sorted_totals(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  range: DateRange,
+) -> Vec<((AccountId, CurrencyId), f64)> {
+  let mut totals = account_totals(journal, range).into_iter().collect::<Vec<_>>();
+  totals.sort_by_cached_key(|((account_id, currency_id), _)| (
+    account_label(journal, account_id),
+    currency_label(currencies, currency_id),
+  ));
+  totals
+}
+
+/// Adds a row for every account of `account_type`, in the sign of the type's
+/// normal balance, returning the section totals per currency.
+fn section_rows(
+  table: &mut Table,
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  totals: &[((AccountId, CurrencyId), f64)],
+  section: &str,
+  account_type: AccountType,
+) -> BTreeMap<String, (CurrencyId, f64)> {
+  let mut section_totals: BTreeMap<String, (CurrencyId, f64)> = BTreeMap::new();
+  for ((account_id, currency_id), total) in totals {
+    if journal.accounts.get(account_id).map(|a| a.account_type) != Some(account_type) {
+      continue;
+    }
+    if total.abs() < 0.005 {
+      continue;
+    }
+    let amount = match account_type.normal_balance() {
+      BalanceType::Debit => *total,
+      BalanceType::Credit => -total,
+    };
+    let label = currency_label(currencies, currency_id);
+    table.rows.push(vec![
+      Cell::Text(section.to_string()),
+      Cell::Text(account_label(journal, account_id)),
+      Cell::Text(label.clone()),
+      Cell::Amount(amount, currency_id.clone()),
+    ]);
+    section_totals.entry(label).or_insert((currency_id.clone(), 0.0)).1 += amount;
+  }
+  section_totals
+}
+
+/// Adds a labelled total row for each currency.
+pub fn total_rows(table: &mut Table, label: &str, totals: &BTreeMap<String, (CurrencyId, f64)>) {
+  for (currency, (currency_id, amount)) in totals {
+    table.rows.push(vec![
+      Cell::Text(label.to_string()),
+      Cell::Empty,
+      Cell::Text(currency.clone()),
+      Cell::Amount(*amount, currency_id.clone()),
+    ]);
+  }
+}
+
+pub fn account_label(journal: &Journal, account_id: &AccountId) -> String {
+  journal
+    .account_full_name(account_id)
+    .unwrap_or_else(|| error_token().to_string())
+}
+
+pub fn currency_label(currencies: &HashMap<CurrencyId, Currency>, currency_id: &CurrencyId) -> String {
+  currencies
+    .get(currency_id)
+    .map(|c| c.symbol.clone())
+    .unwrap_or_else(|| error_token().to_string())
+}
+
+/// ISO date when the transaction date can be read, otherwise the raw text.
+pub fn date_label(date: Option<NaiveDate>, raw: &str) -> String {
+  date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| raw.to_string())
+}
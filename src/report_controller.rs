@@ -0,0 +1,115 @@
+use std::{collections::HashMap, fs};
+use crate::{
+  currency::{Currency, CurrencyId},
+  delimited::{to_delimited, Delimiter},
+  filter_controller::input_query,
+  journal::Journal,
+  page::Navigation,
+  periodic::Interval,
+  query::Query,
+  report::{DateRange, ReportKind, ReportOptions, Table},
+  utility::{
+    on_error,
+    on_not_found,
+    LabeledItem,
+    select_with_labels,
+    input_default,
+    input_optional_date,
+  },
+};
+
+pub fn view_reports(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  navigation: &mut Navigation,
+) {
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let kind = {
+    let mut options = vec![LabeledItem::from("[Back]".to_string(), None)];
+    options.extend(ReportKind::all()
+      .into_iter()
+      .map(|kind| LabeledItem::from(kind.as_str().to_string(), Some(kind))));
+    match select_with_labels("Select Report".to_string(), &options) {
+      Err(error) => return on_error(navigation, error),
+      Ok(None) => return navigation.back(),
+      Ok(Some(kind)) => *kind,
+    }
+  };
+
+  let mut options = match input_report_options() {
+    Err(error) => return on_error(navigation, error),
+    Ok(options) => options,
+  };
+  if kind == ReportKind::Periodic {
+    if let Err(error) = input_periodic_options(&mut options) {
+      return on_error(navigation, error);
+    }
+  }
+
+  let query = match input_query(&Query::default()) {
+    Err(error) => return on_error(navigation, error),
+    Ok(query) => query,
+  };
+
+  let table = match query.is_empty() {
+    true => kind.build(journal, currencies, &options),
+    false => kind.build(&query.select(journal, currencies), currencies, &options),
+  };
+  println!("{}", table.render(currencies));
+
+  if let Err(error) = offer_export(&table, currencies, kind.cli_name()) {
+    on_error(navigation, error)
+  }
+}
+
+pub fn input_report_options() -> Result<ReportOptions, std::io::Error> {
+  let start = input_optional_date("From (YYYY-MM-DD, empty for all):".to_string())?;
+  let end = input_optional_date("To (YYYY-MM-DD, empty for all):".to_string())?;
+  Ok(ReportOptions { range: DateRange::new(start, end), ..ReportOptions::default() })
+}
+
+fn input_periodic_options(options: &mut ReportOptions) -> Result<(), std::io::Error> {
+  let intervals = Interval::all()
+    .into_iter()
+    .map(|interval| LabeledItem::from(interval.as_str().to_string(), interval))
+    .collect::<Vec<_>>();
+  options.interval = *select_with_labels("Period".to_string(), &intervals)?;
+
+  let modes = vec![
+    LabeledItem::from("Changes per Period".to_string(), false),
+    LabeledItem::from("Balances at Period End".to_string(), true),
+  ];
+  options.cumulative = *select_with_labels("Show".to_string(), &modes)?;
+  Ok(())
+}
+
+/// Lets the user save a displayed table as CSV or TSV.
+pub fn offer_export(
+  table: &Table,
+  currencies: &HashMap<CurrencyId, Currency>,
+  default_name: &str,
+) -> Result<(), std::io::Error> {
+  let options = vec![
+    LabeledItem::from("[Done]".to_string(), None),
+    LabeledItem::from("[Export CSV]".to_string(), Some(Delimiter::Comma)),
+    LabeledItem::from("[Export TSV]".to_string(), Some(Delimiter::Tab)),
+  ];
+  let delimiter = match select_with_labels("Export".to_string(), &options)? {
+    None => return Ok(()),
+    Some(delimiter) => *delimiter,
+  };
+
+  let path = input_default(
+    "File Path:".to_string(), 
+    format!("{}.{}", default_name, delimiter.extension())
+  )?;
+  match fs::write(&path, to_delimited(table, currencies, delimiter)) {
+    Err(error) => println!("Export failed. {}", error),
+    Ok(()) => println!("Exported to {}", path),
+  }
+  Ok(())
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use crate::{
+  account::{AccountId, BalanceType},
+  account_change::AccountChange,
+  currency::CurrencyId,
+  transaction::Transaction,
+};
+
+pub type TemplateId = String;
+
+/// An account change whose amount may be left for entry time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateChange {
+  pub account_id: AccountId,
+  pub currency_id: CurrencyId,
+  pub credit_or_debit: BalanceType,
+  pub amount: Option<f64>,
+}
+
+/// A saved starting point for common transactions, e.g. "Groceries from
+/// checking" or "Payroll split".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionTemplate {
+  pub id: TemplateId,
+  /// Name the template is listed under.
+  pub label: String,
+  pub name: String,
+  pub description: String,
+  pub changes: Vec<TemplateChange>,
+}
+
+impl TransactionTemplate {
+  /// Copies the name, description and account changes of `transaction`
+  /// with their amounts. Clear a change's amount to ask for it instead.
+  pub fn from_transaction(label: String, transaction: &Transaction) -> TransactionTemplate {
+    let mut changes = transaction.account_changes
+      .values()
+      .map(|c| TemplateChange {
+        account_id: c.account_id.clone(),
+        currency_id: c.currency_id.clone(),
+        credit_or_debit: c.credit_or_debit,
+        amount: Some(c.amount),
+      })
+      .collect::<Vec<_>>();
+    // Debits first, the order entries are usually read in
+    changes.sort_by_key(|c| c.credit_or_debit == BalanceType::Credit);
+    TransactionTemplate {
+      id: Uuid::new_v4().to_string(),
+      label,
+      name: transaction.name.clone(),
+      description: transaction.description.clone(),
+      changes,
+    }
+  }
+
+  /// A new transaction dated `date`. `amounts` fills the changes without a
+  /// saved amount, in order.
+  pub fn to_transaction(&self, date: NaiveDate, amounts: &[f64]) -> Transaction {
+    let mut transaction = Transaction::new(
+      Transaction::format_date(date),
+      self.name.clone(),
+      self.description.clone(),
+    );
+    let mut amounts = amounts.iter();
+    for change in &self.changes {
+      let amount = match change.amount {
+        Some(amount) => amount,
+        None => amounts.next().copied().unwrap_or(0.0),
+      };
+      transaction.add_account_change(AccountChange::new(
+        change.account_id.clone(),
+        change.currency_id.clone(),
+        change.credit_or_debit,
+        amount,
+      ));
+    }
+    transaction
+  }
+}
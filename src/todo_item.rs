@@ -16,11 +16,11 @@ impl PartialEq for TodoItem {
 
 impl TodoItem {
   pub fn new(id: String, title: String, description: String) -> TodoItem {
-    return TodoItem {
+    TodoItem {
       id,
       title,
       description,
       status: false,
-    };
+    }
   }
 }
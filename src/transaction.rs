@@ -1,17 +1,26 @@
 use std::collections::HashMap;
+use chrono::{NaiveDate, Datelike};
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use crate::account_change::{AccountChange, AccountChangeId};
+use crate::{
+  account_change::{imbalances, AccountChange, AccountChangeId},
+  currency::CurrencyId,
+  payee::PayeeId,
+};
 
 pub type TransactionId = String;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
   pub id: TransactionId,
   pub date: String,
   pub name: String,
   pub description: String,
   pub account_changes: HashMap<AccountChangeId, AccountChange>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub payee_id: Option<PayeeId>,
 }
 
 impl Transaction {
@@ -26,6 +35,57 @@ impl Transaction {
       name, 
       description, 
       account_changes: HashMap::new(), 
+      tags: Vec::new(),
+      payee_id: None,
+    }
+  }
+
+  /// Formats a date the same way `new_transaction` stores it.
+  pub fn format_date(date: NaiveDate) -> String {
+    format!("{}/{}/{}", date.year(), date.month(), date.day())
+  }
+
+  pub fn parsed_date(&self) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&self.date, "%Y/%m/%d").ok()
+  }
+
+  pub fn add_account_change(&mut self, account_change: AccountChange) {
+    self.account_changes.insert(account_change.id.clone(), account_change);
+  }
+
+  /// A copy with new ids, dated `date` or the original date.
+  pub fn duplicate(&self, date: Option<NaiveDate>) -> Transaction {
+    let mut transaction = Transaction::new(
+      date.map_or_else(|| self.date.clone(), Transaction::format_date),
+      self.name.clone(),
+      self.description.clone(),
+    );
+    transaction.tags = self.tags.clone();
+    transaction.payee_id = self.payee_id.clone();
+    for change in self.account_changes.values() {
+      transaction.add_account_change(AccountChange::new(
+        change.account_id.clone(),
+        change.currency_id.clone(),
+        change.credit_or_debit,
+        change.amount,
+      ));
+    }
+    transaction
+  }
+
+  /// Debit positive totals of the currencies that do not balance.
+  pub fn imbalances(&self) -> Vec<(CurrencyId, f64)> {
+    imbalances(self.account_changes.values())
+  }
+
+  pub fn is_balanced(&self) -> bool {
+    self.imbalances().is_empty()
+  }
+
+  /// Adds `tag` unless the transaction already has it.
+  pub fn add_tag(&mut self, tag: &str) {
+    if !self.tags.iter().any(|t| t == tag) {
+      self.tags.push(tag.to_string());
     }
   }
 }
\ No newline at end of file
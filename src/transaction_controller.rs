@@ -1,7 +1,7 @@
 use std::{
   collections::HashMap,
 };
-use chrono::Datelike;
+use chrono::NaiveDate;
 use dialoguer::{
   theme::ColorfulTheme, 
   Confirm
@@ -11,165 +11,362 @@ use crate::{
     Transaction, 
     TransactionId
   },
-  page::Page, 
+  account::BalanceType,
+  account_change::{imbalances, AccountChange},
+  budget_controller::warn_over_budget,
+  currency::{format_amount, Currency, CurrencyId},
+  display::transaction_details,
+  filter::TransactionFilter,
+  filter_controller::save_filter,
+  journal::Journal,
+  page::{Navigation, Page},
+  payee_controller::input_payee,
+  reconcile_controller::confirm_reconciled,
+  report::date_label,
+  template::TransactionTemplate,
   utility::{
     on_error, 
     on_not_found,
     LabeledItem,
     select_with_labels, 
     input_until_parsed, 
-    input_default, input_until_number_with_validation, is_valid_month, is_valid_day
+    input_default,
+    input_until_number_with_validation,
+    input_currency,
+    confirm_default,
+    fuzzy_input_with_labels,
+    fuzzy_page_with_labels,
+    OptionalKey,
   }
 };
 
-pub fn new_transaction<'a>(
-  page: &'a mut Page,
-  transactions: Option<&'a mut HashMap<TransactionId, Transaction>>,
+/// Guided entry of a whole transaction: date, payee and description, then
+/// postings until every currency balances. The postings last used with the
+/// payee are offered first, and the amount of each new posting is
+/// pre-filled with whatever balances the transaction so far.
+pub fn new_transaction(
+  navigation: &mut Navigation,
+  currencies: &HashMap<CurrencyId, Currency>,
+  default_currency_id: &CurrencyId,
+  journal: Option<&mut Journal>,
 ) {
-  let transactions = match transactions {
-    None => return on_not_found(page),
-    Some(transactions) => transactions,
-  };
+  enum Selection {
+    AddPosting,
+    RemovePosting,
+    Save,
+    Cancel,
+  }
 
-  let name = {
-    let prompt = format!("Transaction Name:"); 
-    let default_name = format!("Transaction {}", transactions.len()); 
-    match input_default(prompt, default_name) {
-      Err(error) => return on_error(page, error),
-      Ok(name) => name,
-    }
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
   };
 
-  // Build date for default inputs
-  let current_date = chrono::Utc::now();
-  
-  let year: i32 = {
-    let prompt = format!("Enter Transaction Year:");
-    let default_year = format!("{}", current_date.year()); 
-    match input_until_parsed(prompt, default_year) {
-      Err(error) => return on_error(page, error),
-      Ok(year) => year,
-    }
-  };
-  
-  let month = {
-    let prompt = format!("Enter Transaction Month:");
-    let default_month = format!("{}", current_date.month());
-    match input_until_number_with_validation(
-      prompt, 
-      default_month,
-      &is_valid_month
-    ) {
-      Err(error) => return on_error(page, error),
-      Ok(month) => month,
+  let date = {
+    let prompt = "Transaction Date (YYYY-MM-DD):".to_string();
+    let default_date = chrono::Utc::now().date_naive().to_string();
+    match input_until_parsed::<NaiveDate>(prompt, default_date) {
+      Err(error) => return on_error(navigation, error),
+      Ok(date) => date,
     }
   };
 
-  let day = {
-    let prompt = format!("Enter Transaction Day:");
-    let default_day = format!("{}", current_date.day());
-    let validate = &|day| {
-      is_valid_day(current_date.year(), current_date.month(), day)
-    };
-    match input_until_number_with_validation(
-      prompt, 
-      default_day,
-      &validate
-    ) {
-      Err(error) => return on_error(page, error),
-      Ok(day) => day,
+  let (name, payee_id) = {
+    let default_name = format!("Transaction {}", journal.transactions.len());
+    match input_payee(journal, default_name) {
+      Err(error) => return on_error(navigation, error),
+      Ok(payee) => payee,
     }
   };
+  let payee = payee_id.as_ref().and_then(|id| journal.payees.get(id));
 
   let description = {
-    let prompt = format!("Enter Transaction Description");
-    let default_description = format!("");
+    let prompt = "Enter Transaction Description".to_string();
+    let default_description = String::new();
     match input_default(prompt, default_description) {
-      Err(error) => return on_error(page, error),
+      Err(error) => return on_error(navigation, error),
       Ok(description) => description,
     }
   };
 
-  let date = format!("{}/{}/{}", year, month, day);
-  let transaction = Transaction::new(date, name, description);
-  transactions.insert(transaction.id.clone(), transaction);
-  *page = Page::SelectTransaction
+  let mut account_options = journal.accounts
+    .keys()
+    .map(|id| LabeledItem::from(journal.account_full_name(id).unwrap_or_default(), id.clone()))
+    .collect::<Vec<_>>();
+  account_options.sort_by(|a, b| a.label.cmp(&b.label));
+  if account_options.is_empty() {
+    println!("Create an account before entering transactions");
+    return navigation.back();
+  }
+  // Offer the payee's usual account first
+  if let Some(index) = payee
+    .and_then(|p| p.default_account_id.as_ref())
+    .and_then(|id| account_options.iter().position(|o| &o.item == id))
+  {
+    let option = account_options.remove(index);
+    account_options.insert(0, option);
+  }
+
+  let mut transaction = Transaction::new(Transaction::format_date(date), name, description);
+  transaction.payee_id = payee_id.clone();
+  let mut currency_id = default_currency_id.clone();
+  let last_postings = payee
+    .and_then(|p| journal.last_transaction_with(p))
+    .map(|t| {
+      let mut changes = t.account_changes.values().collect::<Vec<_>>();
+      changes.sort_by_key(|c| (c.credit_or_debit != BalanceType::Debit, journal.account_full_name(&c.account_id)));
+      changes
+    })
+    .unwrap_or_default();
+  let mut postings = match prefill_postings(currencies, journal, &last_postings) {
+    Err(error) => return on_error(navigation, error),
+    Ok(postings) => postings,
+  };
+  if let Some(posting) = postings.last() {
+    currency_id = posting.currency_id.clone();
+  }
+  loop {
+    let imbalances = imbalances(&postings);
+    for posting in &postings {
+      println!(
+        "  {:<40} {:>6} {}",
+        journal.account_full_name(&posting.account_id).unwrap_or_default(),
+        posting.credit_or_debit.as_str(),
+        format_amount(currencies, &posting.currency_id, posting.amount),
+      );
+    }
+    for (id, imbalance) in &imbalances {
+      println!("  Unbalanced by {}", format_amount(currencies, id, imbalance.abs()));
+    }
+
+    let mut options = vec![LabeledItem::from("[Add Posting]".to_string(), Selection::AddPosting)];
+    if imbalances.is_empty() && postings.len() >= 2 {
+      options.insert(0, LabeledItem::from("[Save]".to_string(), Selection::Save));
+    }
+    if !postings.is_empty() {
+      options.push(LabeledItem::from("[Remove Last Posting]".to_string(), Selection::RemovePosting));
+    }
+    options.push(LabeledItem::from("[Cancel]".to_string(), Selection::Cancel));
+    let selection = match select_with_labels(transaction.name.clone(), &options) {
+      Err(error) => return on_error(navigation, error),
+      Ok(selection) => selection,
+    };
+
+    match selection {
+      Selection::Cancel => return navigation.back(),
+      Selection::RemovePosting => {
+        postings.pop();
+        continue;
+      },
+      Selection::Save => break,
+      Selection::AddPosting => {},
+    }
+
+    let account_id = match fuzzy_input_with_labels("Account:".to_string(), &account_options) {
+      Err(error) => return on_error(navigation, error),
+      Ok(account_id) => account_id.clone(),
+    };
+
+    currency_id = match input_currency(currencies, &currency_id) {
+      Err(error) => return on_error(navigation, error),
+      Ok(currency_id) => currency_id,
+    };
+
+    // Offer the side and amount that balance the currency, if it is off
+    let imbalance = imbalances
+      .iter()
+      .find(|(id, _)| id == &currency_id)
+      .map(|(_, imbalance)| *imbalance);
+    let credit_or_debit = {
+      let mut options = vec![
+        LabeledItem::from("Debit".to_string(), BalanceType::Debit),
+        LabeledItem::from("Credit".to_string(), BalanceType::Credit),
+      ];
+      if imbalance.is_some_and(|imbalance| imbalance > 0.0) {
+        options.reverse();
+      }
+      match select_with_labels("Credit Or Debit:".to_string(), &options) {
+        Err(error) => return on_error(navigation, error),
+        Ok(credit_or_debit) => *credit_or_debit,
+      }
+    };
+
+    let amount = {
+      let prompt = format!("Enter {} Amount:", credit_or_debit.as_str());
+      let balancing = imbalance.map(|imbalance| match credit_or_debit {
+        BalanceType::Debit => -imbalance,
+        BalanceType::Credit => imbalance,
+      });
+      let default_amount = match balancing {
+        Some(amount) if amount > 0.0 => amount_text(currencies, &currency_id, amount),
+        _ => String::new(),
+      };
+      match input_until_number_with_validation(prompt, default_amount, &|amount: f64| amount > 0.0) {
+        Err(error) => return on_error(navigation, error),
+        Ok(amount) => amount,
+      }
+    };
+
+    postings.push(AccountChange::new(account_id, currency_id.clone(), credit_or_debit, amount));
+  }
+
+  let posted = postings.clone();
+  for posting in postings {
+    transaction.add_account_change(posting);
+  }
+  let transaction_id = transaction.id.clone();
+  journal.transactions.insert(transaction.id.clone(), transaction);
+  navigation.back();
+  navigation.open_transaction(transaction_id);
+
+  warn_over_budget(currencies, journal, &posted.iter().collect::<Vec<_>>(), date);
 }
 
-pub fn select_transaction<'a>(
-  page: &'a mut Page,
-  transaction_id: &'a mut Option<TransactionId>,
-  transactions: Option<&'a mut HashMap<TransactionId, Transaction>>,
+/// Asks for the amount of each posting last used with the payee, offering
+/// the previous amount, or for the final one whatever balances. Postings
+/// given no amount are left out.
+fn prefill_postings(
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: &Journal,
+  last_postings: &[&AccountChange],
+) -> Result<Vec<AccountChange>, std::io::Error> {
+  let mut postings: Vec<AccountChange> = Vec::new();
+  for (index, change) in last_postings.iter().enumerate() {
+    if !journal.accounts.contains_key(&change.account_id) {
+      continue;
+    }
+    let balancing = imbalances(&postings)
+      .into_iter()
+      .find(|(id, _)| id == &change.currency_id)
+      .map(|(_, imbalance)| match change.credit_or_debit {
+        BalanceType::Debit => -imbalance,
+        BalanceType::Credit => imbalance,
+      })
+      .filter(|amount| *amount > 0.0 && index == last_postings.len() - 1);
+    let prompt = format!(
+      "{} {} Amount (0 to leave out):",
+      journal.account_full_name(&change.account_id).unwrap_or_default(),
+      change.credit_or_debit.as_str(),
+    );
+    let default_amount = amount_text(currencies, &change.currency_id, balancing.unwrap_or(change.amount));
+    let amount = input_until_number_with_validation(prompt, default_amount, &|amount: f64| amount >= 0.0)?;
+    if amount > 0.0 {
+      postings.push(AccountChange::new(
+        change.account_id.clone(),
+        change.currency_id.clone(),
+        change.credit_or_debit,
+        amount
+      ));
+    }
+  }
+  Ok(postings)
+}
+
+/// Plain number at the currency's precision, for pre-filled inputs.
+fn amount_text(currencies: &HashMap<CurrencyId, Currency>, currency_id: &CurrencyId, amount: f64) -> String {
+  let precision = currencies.get(currency_id).map_or(2, |c| c.precision);
+  format!("{:.precision$}", amount, precision = precision)
+}
+
+/// Transactions shown at a time in the transaction list.
+const PAGE_SIZE: usize = 15;
+
+pub fn select_transaction(
+  navigation: &mut Navigation,
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
 ) {
-  enum Selection<'a> {
-    Transaction(&'a TransactionId),
+  enum Selection {
+    Transaction(TransactionId),
+    Filter,
+    ClearFilter,
+    SaveFilter,
+    SavedFilters,
+    SelectMultiple,
     NewTransaction,
+    FromTemplate,
     Back
   }
 
-  let transactions = match transactions {
-    None => return on_not_found(page),
-    Some(transactions) => transactions,
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
   };
 
+  let filter = navigation.context().filter;
+  let transactions = filter.apply(journal, currencies);
   let options = {
-    let mut options = transactions.iter()
-      .map(|(id, t)| {
-        let label = format!("{}: {}", t.date, t.name); 
-        LabeledItem::from(
-          label,
-          Selection::Transaction(id)
-        )
-      })
-      .collect::<Vec<_>>();
-    options.sort_by(|a, b| a.label.cmp(&b.label));
+    let mut options = vec![
+      LabeledItem::from("[Back]".to_string(), Selection::Back),
+      LabeledItem::from("[Filter]".to_string(), Selection::Filter),
+    ];
+    if !filter.is_empty() {
+      options.push(LabeledItem::from("[Clear Filter]".to_string(), Selection::ClearFilter));
+      options.push(LabeledItem::from("[Save Filter]".to_string(), Selection::SaveFilter));
+    }
+    if !journal.filters.is_empty() {
+      options.push(LabeledItem::from("[Saved Filters]".to_string(), Selection::SavedFilters));
+    }
     options.extend(vec![
+      LabeledItem::from("[Select Multiple]".to_string(), Selection::SelectMultiple),
       LabeledItem::from(
         "[New Transaction]".to_string(), 
         Selection::NewTransaction
       ),
-      LabeledItem::from("[Back]".to_string(), Selection::Back)
+      LabeledItem::from("[From Template]".to_string(), Selection::FromTemplate),
     ]);
+    options.extend(transactions.iter().map(|t| {
+      let label = format!("{}: {}", date_label(t.parsed_date(), &t.date), t.name);
+      LabeledItem::from(label, Selection::Transaction(t.id.clone()))
+    }));
     options
   };
 
-  let selection = { 
-    match select_with_labels(
-      "Select Transaction".to_string(), 
-      &options
-    ) {
-      Err(error) => return on_error(page, error),
-      Ok(selection) => selection,
-    }
+  let prompt = format!(
+    "Select Transaction ({} of {}, {})",
+    transactions.len(),
+    journal.transactions.len(),
+    filter.describe(journal, currencies)
+  );
+  let selection = match fuzzy_page_with_labels(prompt, &options, PAGE_SIZE) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
   };
   
-  match *selection {
-    Selection::Transaction(id) => {
-      match transactions.get(id) {
-        None => return on_not_found(page),
-        Some(transaction) => {
-          *transaction_id = Some(transaction.id.clone());
-          *page = Page::ViewTransaction;
-        },
-      };    
+  match selection {
+    Selection::Transaction(id) => navigation.open_transaction(id.clone()),
+    Selection::Filter => navigation.go(Page::FilterTransactions),
+    Selection::ClearFilter => navigation.set_filter(TransactionFilter::default()),
+    Selection::SaveFilter => {
+      if let Err(error) = save_filter(journal, currencies, &filter) {
+        on_error(navigation, error)
+      }
     },
+    Selection::SavedFilters => navigation.go(Page::SavedFilters),
+    Selection::SelectMultiple => navigation.go(Page::BulkTransactions),
     Selection::NewTransaction => {
-      *page = Page::NewTransaction;
+      navigation.go(Page::NewTransaction);
+    },
+    Selection::FromTemplate => {
+      navigation.go(Page::NewFromTemplate);
     },
     Selection::Back => {
-      *page = Page::ViewJournal;
+      navigation.back();
     },
-  };
+  }
 }
 
 pub fn view_transaction(
-  page: &mut Page,
-  transaction: Option<&mut Transaction>,
+  navigation: &mut Navigation,
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&Journal>,
+  transaction_id: &Option<TransactionId>,
 ) {
   enum Selection {
     Back,
     Display,
     AccountChanges,
+    SaveTemplate,
     Delete,
   }
 
@@ -177,12 +374,16 @@ pub fn view_transaction(
     LabeledItem::from("[Back]".to_string(), Selection::Back),
     LabeledItem::from("[Display]".to_string(), Selection::Display),
     LabeledItem::from("[Account Changes]".to_string(), Selection::AccountChanges),
+    LabeledItem::from("[Save as Template]".to_string(), Selection::SaveTemplate),
     LabeledItem::from("[Delete]".to_string(), Selection::Delete),
   ];
 
-  let transaction = match transaction {
-    None => return on_not_found(page),
-    Some(transaction) => transaction,
+  let (journal, transaction) = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => match journal.transactions.get_optional(transaction_id.clone()) {
+      None => return on_not_found(navigation),
+      Some(transaction) => (journal, transaction),
+    },
   };
 
   let selection = { 
@@ -190,43 +391,202 @@ pub fn view_transaction(
       transaction.name.clone(), 
       &options
     ) {
-      Err(error) => return on_error(page, error),
+      Err(error) => return on_error(navigation, error),
       Ok(selection) => selection,
     }
   };
 
   match *selection {
-    Selection::Display => println!("{:#?}", transaction),
+    Selection::Display => println!("{}", transaction_details(journal, currencies, transaction)),
     Selection::Back => {
-      *page = Page::SelectTransaction
+      navigation.back()
     },
     Selection::Delete => {
-      *page = Page::DeleteTransaction
+      navigation.go(Page::DeleteTransaction)
     },
     Selection::AccountChanges => {
-      *page = Page::SelectAccountChange
+      navigation.go(Page::SelectAccountChange)
     },
+    Selection::SaveTemplate => {
+      navigation.go(Page::SaveTemplate)
+    },
+  }
 }
+
+pub fn new_from_template(
+  navigation: &mut Navigation,
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+) {
+  enum Selection<'a> {
+    Template(&'a TransactionTemplate),
+    DeleteTemplate,
+    Back,
+  }
+
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+
+  let mut options = journal.templates
+    .values()
+    .map(|t| LabeledItem::from(t.label.clone(), Selection::Template(t)))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| a.label.cmp(&b.label));
+  options.extend(vec![
+    LabeledItem::from("[Delete Template]".to_string(), Selection::DeleteTemplate),
+    LabeledItem::from("[Back]".to_string(), Selection::Back),
+  ]);
+  let selection = match fuzzy_input_with_labels("Template:".to_string(), &options) {
+    Err(error) => return on_error(navigation, error),
+    Ok(selection) => selection,
+  };
+
+  let template = match selection {
+    Selection::Back => return navigation.back(),
+    Selection::DeleteTemplate => {
+      let options = journal.templates
+        .values()
+        .map(|t| LabeledItem::from(t.label.clone(), t.id.clone()))
+        .collect::<Vec<_>>();
+      if options.is_empty() {
+        return;
+      }
+      let template_id = match select_with_labels("Delete Template".to_string(), &options) {
+        Err(error) => return on_error(navigation, error),
+        Ok(template_id) => template_id.clone(),
+      };
+      journal.templates.remove(&template_id);
+      return;
+    },
+    Selection::Template(template) => template,
+  };
+
+  let date = {
+    let prompt = "Date (YYYY-MM-DD):".to_string();
+    let default_date = chrono::Utc::now().date_naive().to_string();
+    match input_until_parsed::<NaiveDate>(prompt, default_date) {
+      Err(error) => return on_error(navigation, error),
+      Ok(date) => date,
+    }
+  };
+
+  let open = template.changes.iter().filter(|c| c.amount.is_none()).collect::<Vec<_>>();
+  let transaction = loop {
+    let mut amounts = Vec::new();
+    for (index, change) in open.iter().enumerate() {
+      let prompt = format!(
+        "{} {} Amount:",
+        journal.account_full_name(&change.account_id).unwrap_or_default(),
+        change.credit_or_debit.as_str()
+      );
+      // Offer the last open posting the amount that balances its currency
+      let balancing = match index + 1 == open.len() {
+        false => None,
+        true => template.to_transaction(date, &amounts)
+          .imbalances()
+          .into_iter()
+          .find(|(id, _)| id == &change.currency_id)
+          .map(|(_, imbalance)| match change.credit_or_debit {
+            BalanceType::Debit => -imbalance,
+            BalanceType::Credit => imbalance,
+          }),
+      };
+      let default_amount = match balancing {
+        Some(amount) if amount > 0.0 => amount_text(currencies, &change.currency_id, amount),
+        _ => String::new(),
+      };
+      match input_until_number_with_validation(prompt, default_amount, &|amount: f64| amount > 0.0) {
+        Err(error) => return on_error(navigation, error),
+        Ok(amount) => amounts.push(amount),
+      }
+    }
+
+    let transaction = template.to_transaction(date, &amounts);
+    let imbalances = transaction.imbalances();
+    if imbalances.is_empty() {
+      break transaction;
+    }
+    for (id, imbalance) in &imbalances {
+      println!("  Unbalanced by {}", format_amount(currencies, id, imbalance.abs()));
+    }
+    if open.is_empty() {
+      println!("The template's saved amounts do not balance");
+      return navigation.back();
+    }
+    match confirm_default("Enter the amounts again?".to_string()) {
+      Err(error) => return on_error(navigation, error),
+      Ok(true) => {},
+      Ok(false) => return navigation.back(),
+    }
+  };
+
+  let transaction_id = transaction.id.clone();
+  journal.transactions.insert(transaction.id.clone(), transaction);
+  navigation.back();
+  navigation.open_transaction(transaction_id);
+}
+
+pub fn save_template(
+  navigation: &mut Navigation,
+  currencies: &HashMap<CurrencyId, Currency>,
+  journal: Option<&mut Journal>,
+  transaction_id: &Option<TransactionId>,
+) {
+  let journal = match journal {
+    None => return on_not_found(navigation),
+    Some(journal) => journal,
+  };
+  let transaction = match transaction_id.as_ref().and_then(|id| journal.transactions.get(id)) {
+    None => return on_not_found(navigation),
+    Some(transaction) => transaction,
+  };
+
+  let label = match input_default("Template Name:".to_string(), transaction.name.clone()) {
+    Err(error) => return on_error(navigation, error),
+    Ok(label) => label,
+  };
+
+  let mut template = TransactionTemplate::from_transaction(label, transaction);
+  for change in &mut template.changes {
+    let prompt = format!(
+      "Keep {} {} {}?",
+      journal.account_full_name(&change.account_id).unwrap_or_default(),
+      change.credit_or_debit.as_str(),
+      format_amount(currencies, &change.currency_id, change.amount.unwrap_or_default())
+    );
+    match confirm_default(prompt) {
+      Err(error) => return on_error(navigation, error),
+      Ok(true) => {},
+      Ok(false) => change.amount = None,
+    }
+  }
+  journal.templates.insert(template.id.clone(), template);
+  navigation.back();
 }
 
 pub fn delete_transaction<'a>(
-  page: &'a mut Page,
+  navigation: &'a mut Navigation,
   transactions: Option<&'a mut HashMap<TransactionId, Transaction>>,
   transaction_id: &Option<TransactionId>,
 ) {
   let transactions = match transactions {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(transactions) => transactions,
   };
 
   let transaction_id = match transaction_id {
-    None => return on_not_found(page),
+    None => return on_not_found(navigation),
     Some(id) => id,
   };
 
-  let name = match transactions.get(transaction_id) {
-    None => return on_not_found(page),
-    Some(transaction) => transaction.name.clone(),
+  let (name, reconciled) = match transactions.get(transaction_id) {
+    None => return on_not_found(navigation),
+    Some(transaction) => (
+      transaction.name.clone(),
+      transaction.account_changes.values().filter(|c| c.is_reconciled()).count()
+    ),
   };
 
   let should_delete = match Confirm::with_theme(
@@ -237,18 +597,22 @@ pub fn delete_transaction<'a>(
     )
     .default(false)
     .interact() 
+    .and_then(|confirmed| match confirmed {
+      true => confirm_reconciled(reconciled, "Delete"),
+      false => Ok(false),
+    })
   {
-    Err(error) => return on_error(page, error),
+    Err(error) => return on_error(navigation, error),
     Ok(should_delete) => should_delete,
   };
   
   match should_delete {
     false => {
-      *page = Page::ViewTransaction
+      navigation.back()
     },
     true => {
       transactions.remove(transaction_id);
-      *page = Page::SelectTransaction
+      navigation.back_to(Page::SelectTransaction)
     },
   }
 }
\ No newline at end of file
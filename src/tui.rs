@@ -0,0 +1,788 @@
+use std::io;
+use chrono::NaiveDate;
+use ratatui::{
+  crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+  layout::{Constraint, Direction, Layout, Rect},
+  style::{Color, Modifier, Style},
+  text::Line,
+  widgets::{Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState},
+  DefaultTerminal,
+  Frame,
+};
+use crate::{
+  account::{AccountId, BalanceType},
+  account_change::{AccountChange, AccountChangeId},
+  currency::{format_amount, CurrencyId},
+  data::Data,
+  journal::{Journal, JournalId},
+  ledger::type_from_name,
+  reconcile_controller::reconciled_warning,
+  transaction::{Transaction, TransactionId},
+};
+
+/// Runs the full-screen interface until the user quits. Changes are made to
+/// `data` in place; saving is left to the caller.
+pub fn run(data: &mut Data) -> io::Result<()> {
+  let mut terminal = ratatui::init();
+  let result = App::new(data).run(&mut terminal);
+  ratatui::restore();
+  result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+  Journals,
+  Accounts,
+  Transactions,
+  Postings,
+}
+
+impl Pane {
+  fn next(self) -> Pane {
+    match self {
+      Pane::Journals => Pane::Accounts,
+      Pane::Accounts => Pane::Transactions,
+      Pane::Transactions => Pane::Postings,
+      Pane::Postings => Pane::Journals,
+    }
+  }
+
+  fn previous(self) -> Pane {
+    match self {
+      Pane::Journals => Pane::Postings,
+      Pane::Accounts => Pane::Journals,
+      Pane::Transactions => Pane::Accounts,
+      Pane::Postings => Pane::Transactions,
+    }
+  }
+}
+
+/// The field an inline edit writes to when it is committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+  NewJournal,
+  JournalName,
+  NewAccount,
+  AccountName,
+  NewTransaction,
+  TransactionName,
+  TransactionDescription,
+  TransactionDate,
+  NewPosting,
+  PostingAccount,
+  PostingAmount,
+}
+
+impl Field {
+  fn prompt(self) -> &'static str {
+    match self {
+      Field::NewJournal => "New journal name",
+      Field::JournalName => "Journal name",
+      Field::NewAccount => "New account full name (e.g. Expenses:Food)",
+      Field::AccountName => "Account name",
+      Field::NewTransaction => "New transaction name",
+      Field::TransactionName => "Transaction name",
+      Field::TransactionDescription => "Description",
+      Field::TransactionDate => "Date (YYYY-MM-DD)",
+      Field::NewPosting => "Account full name for the new posting",
+      Field::PostingAccount => "Account full name",
+      Field::PostingAmount => "Amount",
+    }
+  }
+}
+
+/// What the user asked for when leaving a transaction.
+#[derive(Debug, Clone, Copy)]
+enum Leave {
+  Quit,
+  Move(i64),
+}
+
+enum Mode {
+  Normal,
+  Editing { field: Field, input: String },
+  ConfirmDelete,
+  ConfirmLeave(Leave),
+  /// Waiting to change reconciled postings with the given key.
+  ConfirmReconciled(KeyCode),
+}
+
+struct App<'a> {
+  data: &'a mut Data,
+  focus: Pane,
+  journal: ListState,
+  account: ListState,
+  transaction: ListState,
+  posting: TableState,
+  mode: Mode,
+  message: String,
+  quit: bool,
+  /// Transaction whose postings were changed since it was selected.
+  changed: Option<TransactionId>,
+}
+
+impl<'a> App<'a> {
+  fn new(data: &'a mut Data) -> App<'a> {
+    App {
+      data,
+      focus: Pane::Journals,
+      journal: ListState::default().with_selected(Some(0)),
+      account: ListState::default().with_selected(Some(0)),
+      transaction: ListState::default().with_selected(Some(0)),
+      posting: TableState::default().with_selected(Some(0)),
+      mode: Mode::Normal,
+      message: String::new(),
+      quit: false,
+      changed: None,
+    }
+  }
+
+  fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    while !self.quit {
+      terminal.draw(|frame| self.draw(frame))?;
+      if let Event::Key(key) = event::read()? {
+        if key.kind == KeyEventKind::Press {
+          self.on_key(key);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  // Ordered ids behind each pane
+
+  fn journal_ids(&self) -> Vec<JournalId> {
+    let mut journals = self.data.journals.values().collect::<Vec<_>>();
+    journals.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    journals.into_iter().map(|j| j.id.clone()).collect()
+  }
+
+  fn current_journal(&self) -> Option<&Journal> {
+    let ids = self.journal_ids();
+    self.data.journals.get(ids.get(self.journal.selected()?)?)
+  }
+
+  fn current_journal_mut(&mut self) -> Option<&mut Journal> {
+    let ids = self.journal_ids();
+    let id = ids.get(self.journal.selected()?)?;
+    self.data.journals.get_mut(id)
+  }
+
+  /// Accounts in tree order with their depth.
+  fn account_rows(&self) -> Vec<(AccountId, usize, String)> {
+    let journal = match self.current_journal() {
+      None => return Vec::new(),
+      Some(journal) => journal,
+    };
+    let mut rows = journal.accounts
+      .keys()
+      .filter_map(|id| Some((id.clone(), journal.account_full_name(id)?)))
+      .collect::<Vec<_>>();
+    rows.sort_by(|a, b| a.1.cmp(&b.1));
+    rows
+      .into_iter()
+      .map(|(id, full_name)| {
+        let depth = full_name.matches(crate::journal::ACCOUNT_SEPARATOR).count();
+        (id, depth, full_name)
+      })
+      .collect()
+  }
+
+  fn current_account_id(&self) -> Option<AccountId> {
+    self.account_rows().get(self.account.selected()?).map(|row| row.0.clone())
+  }
+
+  fn transaction_ids(&self) -> Vec<TransactionId> {
+    let journal = match self.current_journal() {
+      None => return Vec::new(),
+      Some(journal) => journal,
+    };
+    let mut transactions = journal.transactions.values().collect::<Vec<_>>();
+    transactions.sort_by_cached_key(|t| (t.parsed_date(), t.name.clone(), t.id.clone()));
+    transactions.into_iter().map(|t| t.id.clone()).collect()
+  }
+
+  fn current_transaction(&self) -> Option<&Transaction> {
+    let ids = self.transaction_ids();
+    self.current_journal()?.transactions.get(ids.get(self.transaction.selected()?)?)
+  }
+
+  fn current_transaction_mut(&mut self) -> Option<&mut Transaction> {
+    let ids = self.transaction_ids();
+    let id = ids.get(self.transaction.selected()?)?.clone();
+    self.current_journal_mut()?.transactions.get_mut(&id)
+  }
+
+  fn posting_ids(&self) -> Vec<AccountChangeId> {
+    let (journal, transaction) = match (self.current_journal(), self.current_transaction()) {
+      (Some(journal), Some(transaction)) => (journal, transaction),
+      _ => return Vec::new(),
+    };
+    let mut changes = transaction.account_changes.values().collect::<Vec<_>>();
+    changes.sort_by_cached_key(|c| (
+      c.credit_or_debit == BalanceType::Credit,
+      journal.account_full_name(&c.account_id),
+      c.id.clone(),
+    ));
+    changes.into_iter().map(|c| c.id.clone()).collect()
+  }
+
+  fn current_posting(&self) -> Option<&AccountChange> {
+    let ids = self.posting_ids();
+    self.current_transaction()?.account_changes.get(ids.get(self.posting.selected()?)?)
+  }
+
+  /// Reconciled postings the selected item would take with it if deleted.
+  fn reconciled_in_selection(&self) -> usize {
+    let count = |t: &Transaction| t.account_changes.values().filter(|c| c.is_reconciled()).count();
+    match self.focus {
+      Pane::Journals => self.current_journal().map_or(0, |j| j.transactions.values().map(count).sum()),
+      Pane::Accounts => 0,
+      Pane::Transactions => self.current_transaction().map_or(0, count),
+      Pane::Postings => self.current_posting().map_or(0, |c| c.is_reconciled() as usize),
+    }
+  }
+
+  fn current_posting_mut(&mut self) -> Option<&mut AccountChange> {
+    let ids = self.posting_ids();
+    let id = ids.get(self.posting.selected()?)?.clone();
+    self.current_transaction_mut()?.account_changes.get_mut(&id)
+  }
+
+  fn default_currency_id(&self) -> Option<CurrencyId> {
+    let journal_currency = self.current_journal().and_then(Journal::last_used_currency);
+    journal_currency.or_else(|| Some(self.data.default_currency_id()).filter(|id| !id.is_empty()))
+  }
+
+  // Input
+
+  fn on_key(&mut self, key: KeyEvent) {
+    match std::mem::replace(&mut self.mode, Mode::Normal) {
+      Mode::Normal => self.on_normal_key(key),
+      Mode::ConfirmDelete => {
+        if let KeyCode::Char('y') = key.code {
+          self.delete_selected();
+        } else {
+          self.message = "Delete cancelled".to_string();
+        }
+      },
+      Mode::ConfirmLeave(leave) => {
+        if let KeyCode::Char('y') = key.code {
+          self.finish_leave(leave);
+        } else {
+          self.message = "Add postings until the transaction balances".to_string();
+        }
+      },
+      Mode::ConfirmReconciled(code) => {
+        if let KeyCode::Char('y') = key.code {
+          self.change(code);
+        } else {
+          self.message = "Change cancelled".to_string();
+        }
+      },
+      Mode::Editing { field, mut input } => match key.code {
+        KeyCode::Esc => self.message = "Edit cancelled".to_string(),
+        KeyCode::Enter => self.commit(field, input.trim().to_string()),
+        KeyCode::Backspace => {
+          input.pop();
+          self.mode = Mode::Editing { field, input };
+        },
+        KeyCode::Char(c) => {
+          input.push(c);
+          self.mode = Mode::Editing { field, input };
+        },
+        _ => self.mode = Mode::Editing { field, input },
+      },
+    }
+  }
+
+  fn on_normal_key(&mut self, key: KeyEvent) {
+    self.message.clear();
+    match key.code {
+      KeyCode::Char('q') | KeyCode::Esc => self.leave(Leave::Quit),
+      KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.leave(Leave::Quit),
+      KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.focus = self.focus.next(),
+      KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => self.focus = self.focus.previous(),
+      KeyCode::Down | KeyCode::Char('j') => self.leave(Leave::Move(1)),
+      KeyCode::Up | KeyCode::Char('k') => self.leave(Leave::Move(-1)),
+      KeyCode::Enter => self.focus = match self.focus {
+        Pane::Journals => Pane::Transactions,
+        Pane::Transactions => Pane::Postings,
+        other => other,
+      },
+      KeyCode::Char('a') => self.start_add(),
+      // Amounts, accounts, sides and dates of reconciled postings agree
+      // with a statement
+      KeyCode::Char('e' | 'A' | 's') if self.focus == Pane::Postings => {
+        self.confirm_reconciled(key.code, "Change")
+      },
+      KeyCode::Char('t') if self.focus == Pane::Transactions => {
+        self.confirm_reconciled(key.code, "Redate")
+      },
+      KeyCode::Char('e') => self.start_edit(),
+      KeyCode::Char('d') if self.focus == Pane::Transactions => {
+        self.start_field(Field::TransactionDescription)
+      },
+      KeyCode::Char('x') | KeyCode::Delete => {
+        self.mode = Mode::ConfirmDelete;
+        self.message = match self.reconciled_in_selection() {
+          0 => "Delete the selected item? (y/n)".to_string(),
+          count => format!("Delete the selected item and {} reconciled postings? (y/n)", count),
+        };
+      },
+      _ => {},
+    }
+  }
+
+  /// Asks before changing the selection with `code` when it touches
+  /// reconciled postings, as `reconcile_controller::confirm_reconciled`
+  /// does.
+  fn confirm_reconciled(&mut self, code: KeyCode, action: &str) {
+    match self.reconciled_in_selection() {
+      0 => self.change(code),
+      count => {
+        self.mode = Mode::ConfirmReconciled(code);
+        self.message = format!("{} (y/n)", reconciled_warning(count, action));
+      },
+    }
+  }
+
+  fn change(&mut self, code: KeyCode) {
+    match code {
+      KeyCode::Char('e') => self.start_edit(),
+      KeyCode::Char('A') => self.start_field(Field::PostingAccount),
+      KeyCode::Char('t') => self.start_field(Field::TransactionDate),
+      KeyCode::Char('s') => if let Some(posting) = self.current_posting_mut() {
+        posting.credit_or_debit = match posting.credit_or_debit {
+          BalanceType::Debit => BalanceType::Credit,
+          BalanceType::Credit => BalanceType::Debit,
+        };
+        self.mark_changed();
+      },
+      _ => {},
+    }
+  }
+
+  /// Asks before quitting or selecting another transaction while the
+  /// changed one does not balance. Moving within the other panes keeps
+  /// the transaction.
+  fn leave(&mut self, leave: Leave) {
+    let keeps_transaction = matches!(leave, Leave::Move(_))
+      && matches!(self.focus, Pane::Accounts | Pane::Postings);
+    let unbalanced = self.current_transaction()
+      .filter(|t| self.changed.as_ref() == Some(&t.id) && !t.is_balanced())
+      .map(|t| t.name.clone());
+    match (keeps_transaction, unbalanced) {
+      (false, Some(name)) => {
+        self.mode = Mode::ConfirmLeave(leave);
+        self.message = format!("\"{}\" does not balance. Leave it anyway? (y/n)", name);
+      },
+      _ => self.finish_leave(leave),
+    }
+  }
+
+  fn finish_leave(&mut self, leave: Leave) {
+    match leave {
+      Leave::Quit => self.quit = true,
+      Leave::Move(delta) => self.move_selection(delta),
+    }
+  }
+
+  fn move_selection(&mut self, delta: i64) {
+    let length = match self.focus {
+      Pane::Journals => self.journal_ids().len(),
+      Pane::Accounts => self.account_rows().len(),
+      Pane::Transactions => self.transaction_ids().len(),
+      Pane::Postings => self.posting_ids().len(),
+    };
+    let selected = match self.focus {
+      Pane::Journals => self.journal.selected(),
+      Pane::Accounts => self.account.selected(),
+      Pane::Transactions => self.transaction.selected(),
+      Pane::Postings => self.posting.selected(),
+    };
+    let next = match length {
+      0 => 0,
+      _ => (selected.unwrap_or(0) as i64 + delta).clamp(0, length as i64 - 1) as usize,
+    };
+    match self.focus {
+      Pane::Journals => {
+        self.journal.select(Some(next));
+        self.account.select(Some(0));
+        self.transaction.select(Some(0));
+        self.posting.select(Some(0));
+        self.changed = None;
+      },
+      Pane::Accounts => self.account.select(Some(next)),
+      Pane::Transactions => {
+        self.transaction.select(Some(next));
+        self.posting.select(Some(0));
+        self.changed = None;
+      },
+      Pane::Postings => self.posting.select(Some(next)),
+    }
+  }
+
+  fn start_field(&mut self, field: Field) {
+    let input = match field {
+      Field::JournalName => self.current_journal().map(|j| j.name.clone()),
+      Field::AccountName => self.current_account_id()
+        .and_then(|id| Some(self.current_journal()?.accounts.get(&id)?.name.clone())),
+      Field::TransactionName => self.current_transaction().map(|t| t.name.clone()),
+      Field::TransactionDescription => self.current_transaction().map(|t| t.description.clone()),
+      Field::TransactionDate => self.current_transaction()
+        .map(|t| t.parsed_date().map(|d| d.to_string()).unwrap_or_else(|| t.date.clone())),
+      Field::PostingAmount => self.current_posting_mut().map(|p| p.amount.to_string()),
+      Field::NewTransaction | Field::NewPosting | Field::PostingAccount | Field::NewAccount => {
+        Some(String::new())
+      },
+      Field::NewJournal => Some(String::new()),
+    };
+    match input {
+      None => self.message = "Nothing selected".to_string(),
+      Some(input) => self.mode = Mode::Editing { field, input },
+    }
+  }
+
+  fn start_add(&mut self) {
+    let field = match self.focus {
+      Pane::Journals => Field::NewJournal,
+      Pane::Accounts => Field::NewAccount,
+      Pane::Transactions => Field::NewTransaction,
+      Pane::Postings => Field::NewPosting,
+    };
+    if field != Field::NewJournal && self.current_journal().is_none() {
+      return self.message = "Add a journal first".to_string();
+    }
+    if field == Field::NewPosting && self.current_transaction().is_none() {
+      return self.message = "Select a transaction first".to_string();
+    }
+    self.start_field(field);
+  }
+
+  fn start_edit(&mut self) {
+    self.start_field(match self.focus {
+      Pane::Journals => Field::JournalName,
+      Pane::Accounts => Field::AccountName,
+      Pane::Transactions => Field::TransactionName,
+      Pane::Postings => Field::PostingAmount,
+    });
+  }
+
+  fn commit(&mut self, field: Field, input: String) {
+    if input.is_empty() && field != Field::TransactionDescription {
+      return self.message = "Nothing entered".to_string();
+    }
+    let result = match field {
+      Field::NewJournal => {
+        let journal = Journal::new(input);
+        self.data.journals.insert(journal.id.clone(), journal);
+        Ok(())
+      },
+      Field::JournalName => self.current_journal_mut()
+        .map(|j| j.name = input)
+        .ok_or("No journal selected"),
+      Field::NewAccount => self.current_journal_mut()
+        .map(|j| {
+          let account_type = type_from_name(&input);
+          j.find_or_create_account(&input, account_type);
+        })
+        .ok_or("No journal selected"),
+      Field::AccountName => match self.current_account_id() {
+        None => Err("No account selected"),
+        Some(id) => self.current_journal_mut()
+          .and_then(|j| j.accounts.get_mut(&id))
+          .map(|a| a.name = input)
+          .ok_or("No account selected"),
+      },
+      Field::NewTransaction => self.current_journal_mut()
+        .map(|j| {
+          let date = Transaction::format_date(chrono::Utc::now().date_naive());
+          let transaction = Transaction::new(date, input, String::new());
+          j.transactions.insert(transaction.id.clone(), transaction);
+        })
+        .ok_or("No journal selected"),
+      Field::TransactionName => self.current_transaction_mut()
+        .map(|t| t.name = input)
+        .ok_or("No transaction selected"),
+      Field::TransactionDescription => self.current_transaction_mut()
+        .map(|t| t.description = input)
+        .ok_or("No transaction selected"),
+      Field::TransactionDate => match input.parse::<NaiveDate>() {
+        Err(_) => Err("Dates are written YYYY-MM-DD"),
+        Ok(date) => self.current_transaction_mut()
+          .map(|t| t.date = Transaction::format_date(date))
+          .ok_or("No transaction selected"),
+      },
+      Field::NewPosting => self.add_posting(&input),
+      Field::PostingAccount => match self.current_journal().and_then(|j| j.find_account(&input)) {
+        None => Err("No account with that name"),
+        Some(account_id) => {
+          let account_id = account_id.clone();
+          self.current_posting_mut()
+            .map(|p| p.account_id = account_id)
+            .ok_or("No posting selected")
+        },
+      },
+      Field::PostingAmount => match input.parse::<f64>() {
+        Ok(amount) if amount >= 0.0 => self.current_posting_mut()
+          .map(|p| p.amount = amount)
+          .ok_or("No posting selected"),
+        _ => Err("Amounts are positive numbers"),
+      },
+    };
+    match result {
+      Err(error) => self.message = error.to_string(),
+      Ok(()) => if matches!(field, Field::NewPosting | Field::PostingAccount | Field::PostingAmount) {
+        self.mark_changed();
+      },
+    }
+  }
+
+  fn mark_changed(&mut self) {
+    self.changed = self.current_transaction().map(|t| t.id.clone());
+  }
+
+  /// Adds a posting to `account_name` for whatever balances the
+  /// transaction, on the side that balances it.
+  fn add_posting(&mut self, account_name: &str) -> Result<(), &'static str> {
+    let account_id = self.current_journal()
+      .and_then(|j| j.find_account(account_name))
+      .cloned()
+      .ok_or("No account with that name")?;
+    let transaction = self.current_transaction().ok_or("No transaction selected")?;
+    let currency_id = transaction.account_changes
+      .values()
+      .next()
+      .map(|c| c.currency_id.clone())
+      .or_else(|| self.default_currency_id())
+      .ok_or("No currencies")?;
+    let imbalance = transaction.imbalances()
+      .into_iter()
+      .find(|(id, _)| id == &currency_id)
+      .map_or(0.0, |(_, imbalance)| imbalance);
+    let side = match imbalance > 0.0 {
+      true => BalanceType::Credit,
+      false => BalanceType::Debit,
+    };
+    let change = AccountChange::new(account_id, currency_id, side, imbalance.abs());
+    self.current_transaction_mut()
+      .ok_or("No transaction selected")?
+      .add_account_change(change);
+    Ok(())
+  }
+
+  fn delete_selected(&mut self) {
+    let result = match self.focus {
+      Pane::Journals => {
+        let ids = self.journal_ids();
+        match self.journal.selected().and_then(|i| ids.get(i)) {
+          None => Err("No journal selected"),
+          Some(id) => {
+            self.data.journals.remove(id);
+            Ok(())
+          },
+        }
+      },
+      Pane::Accounts => match (self.current_account_id(), self.current_journal_mut()) {
+        (Some(id), Some(journal)) => {
+          let is_used = journal.transactions
+            .values()
+            .flat_map(|t| t.account_changes.values())
+            .any(|c| c.account_id == id)
+            || journal.accounts.values().any(|a| a.parent_id.as_ref() == Some(&id));
+          match is_used {
+            true => Err("Accounts with postings or sub-accounts cannot be deleted"),
+            false => {
+              journal.accounts.remove(&id);
+              Ok(())
+            },
+          }
+        },
+        _ => Err("No account selected"),
+      },
+      Pane::Transactions => {
+        let ids = self.transaction_ids();
+        match (self.transaction.selected().and_then(|i| ids.get(i)).cloned(), self.current_journal_mut()) {
+          (Some(id), Some(journal)) => {
+            journal.transactions.remove(&id);
+            Ok(())
+          },
+          _ => Err("No transaction selected"),
+        }
+      },
+      Pane::Postings => {
+        let ids = self.posting_ids();
+        match (self.posting.selected().and_then(|i| ids.get(i)).cloned(), self.current_transaction_mut()) {
+          (Some(id), Some(transaction)) => {
+            transaction.account_changes.remove(&id);
+            self.mark_changed();
+            Ok(())
+          },
+          _ => Err("No posting selected"),
+        }
+      },
+    };
+    match result {
+      Err(error) => self.message = error.to_string(),
+      Ok(()) => self.move_selection(0),
+    }
+  }
+
+  // Drawing
+
+  fn draw(&mut self, frame: &mut Frame) {
+    let [main, input, help] = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Min(5), Constraint::Length(3), Constraint::Length(1)])
+      .areas(frame.area());
+    let [left, right] = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+      .areas(main);
+    let [journals, accounts] = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Length(8), Constraint::Min(3)])
+      .areas(left);
+    let [transactions, postings] = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+      .areas(right);
+
+    self.draw_journals(frame, journals);
+    self.draw_accounts(frame, accounts);
+    self.draw_transactions(frame, transactions);
+    self.draw_postings(frame, postings);
+    self.draw_input(frame, input);
+    frame.render_widget(
+      Paragraph::new(help_text(self.focus)).style(Style::default().fg(Color::DarkGray)),
+      help,
+    );
+  }
+
+  fn block(&self, title: &str, pane: Pane) -> Block<'static> {
+    let style = match self.focus == pane {
+      true => Style::default().fg(Color::Cyan),
+      false => Style::default(),
+    };
+    Block::default().title(title.to_string()).borders(Borders::ALL).border_style(style)
+  }
+
+  fn draw_journals(&mut self, frame: &mut Frame, area: Rect) {
+    let items = self.journal_ids()
+      .iter()
+      .filter_map(|id| self.data.journals.get(id))
+      .map(|j| ListItem::new(j.name.clone()))
+      .collect::<Vec<_>>();
+    let list = List::new(items)
+      .block(self.block("Journals", Pane::Journals))
+      .highlight_style(highlight());
+    frame.render_stateful_widget(list, area, &mut self.journal);
+  }
+
+  fn draw_accounts(&mut self, frame: &mut Frame, area: Rect) {
+    let items = match self.current_journal() {
+      None => Vec::new(),
+      Some(journal) => self.account_rows()
+        .iter()
+        .filter_map(|(id, depth, _)| {
+          let account = journal.accounts.get(id)?;
+          Some(ListItem::new(format!(
+            "{}{} ({})",
+            "  ".repeat(*depth),
+            account.name,
+            account.account_type.as_str()
+          )))
+        })
+        .collect(),
+    };
+    let list = List::new(items)
+      .block(self.block("Accounts", Pane::Accounts))
+      .highlight_style(highlight());
+    frame.render_stateful_widget(list, area, &mut self.account);
+  }
+
+  fn draw_transactions(&mut self, frame: &mut Frame, area: Rect) {
+    let items = match self.current_journal() {
+      None => Vec::new(),
+      Some(journal) => self.transaction_ids()
+        .iter()
+        .filter_map(|id| journal.transactions.get(id))
+        .map(|t| {
+          let date = t.parsed_date().map(|d| d.to_string()).unwrap_or_else(|| t.date.clone());
+          let balanced = t.is_balanced();
+          ListItem::new(Line::from(format!(
+            "{}  {}{}",
+            date,
+            t.name,
+            if balanced { "" } else { "  [unbalanced]" }
+          )))
+        })
+        .collect(),
+    };
+    let list = List::new(items)
+      .block(self.block("Transactions", Pane::Transactions))
+      .highlight_style(highlight());
+    frame.render_stateful_widget(list, area, &mut self.transaction);
+  }
+
+  fn draw_postings(&mut self, frame: &mut Frame, area: Rect) {
+    let currencies = &self.data.currencies;
+    let mut title = "Postings".to_string();
+    let rows = match (self.current_journal(), self.current_transaction()) {
+      (Some(journal), Some(transaction)) => {
+        if !transaction.description.is_empty() {
+          title = format!("Postings - {}", transaction.description);
+        }
+        self.posting_ids()
+          .iter()
+          .filter_map(|id| transaction.account_changes.get(id))
+          .map(|c| {
+            let amount = format_amount(currencies, &c.currency_id, c.amount);
+            let (debit, credit) = match c.credit_or_debit {
+              BalanceType::Debit => (amount, String::new()),
+              BalanceType::Credit => (String::new(), amount),
+            };
+            Row::new(vec![
+              Cell::from(journal.account_full_name(&c.account_id).unwrap_or_default()),
+              Cell::from(Line::from(debit).right_aligned()),
+              Cell::from(Line::from(credit).right_aligned()),
+            ])
+          })
+          .collect::<Vec<_>>()
+      },
+      _ => Vec::new(),
+    };
+    let table = Table::new(rows, [Constraint::Min(20), Constraint::Length(14), Constraint::Length(14)])
+      .header(Row::new(vec!["Account", "Debit", "Credit"]).style(Style::default().add_modifier(Modifier::BOLD)))
+      .block(self.block(&title, Pane::Postings))
+      .row_highlight_style(highlight());
+    frame.render_stateful_widget(table, area, &mut self.posting);
+  }
+
+  fn draw_input(&self, frame: &mut Frame, area: Rect) {
+    let (title, text) = match &self.mode {
+      Mode::Editing { field, input } => (field.prompt().to_string(), format!("{}_", input)),
+      Mode::ConfirmDelete | Mode::ConfirmLeave(_) | Mode::ConfirmReconciled(_) | Mode::Normal => {
+        (String::new(), self.message.clone())
+      },
+    };
+    frame.render_widget(
+      Paragraph::new(text).block(Block::default().title(title).borders(Borders::ALL)),
+      area,
+    );
+  }
+}
+
+fn help_text(focus: Pane) -> &'static str {
+  match focus {
+    Pane::Journals => "Tab/←→ pane  ↑↓ move  Enter open  a add  e rename  x delete  q quit",
+    Pane::Accounts => "Tab/←→ pane  ↑↓ move  a add  e rename  x delete  q quit",
+    Pane::Transactions => "Tab/←→ pane  ↑↓ move  Enter postings  a add  e name  d description  t date  x delete  q quit",
+    Pane::Postings => "Tab/←→ pane  ↑↓ move  a add  e amount  A account  s debit/credit  x delete  q quit",
+  }
+}
+
+fn highlight() -> Style {
+  Style::default().add_modifier(Modifier::REVERSED)
+}
@@ -3,23 +3,27 @@ use dialoguer::{
   Input, 
   theme::ColorfulTheme, 
   Select,
-  FuzzySelect, Confirm
+  FuzzySelect, Confirm,
+  MultiSelect,
+};
+use crate::{
+  currency::{Currency, CurrencyId},
+  page::Navigation,
 };
-use crate::page::Page;
 use std::{io::Error, str::FromStr, collections::HashMap, hash::Hash};
 
 pub fn error_token() -> &'static str {
   "[ERROR]"
 }
 
-pub fn on_error(page: &mut Page, error: impl std::fmt::Display) {
+pub fn on_error(navigation: &mut Navigation, error: impl std::fmt::Display) {
   println!("{}", error);
-  *page = Page::SelectAccount;
+  navigation.back();
 }
 
-pub fn on_not_found(page: &mut Page) {
+pub fn on_not_found(navigation: &mut Navigation) {
   println!("Not Found");
-  *page = Page::SelectAccount;
+  navigation.back();
 }
 
 pub fn is_valid_month(month: u32) -> bool {
@@ -30,15 +34,35 @@ pub fn is_valid_day(year: i32, month: u32, day: u32) -> bool {
   NaiveDate::from_ymd_opt(year, month, day).is_some()
 }
 
-pub fn fuzzy_input_with_labels<'a, T>(
+pub fn fuzzy_input_with_labels<T>(
+  prompt: String,
+  items: &[LabeledItem<T>],
+) -> Result<&T, Error> {
+  let labels: Vec<_> = items.iter().map(|e| &e.label).collect();
+
+  match FuzzySelect::with_theme(&ColorfulTheme::default())
+    .with_prompt(prompt)
+    .default(0)
+    .items(&labels)
+    .interact()
+  {
+    Err(error) => Err(error),
+    Ok(i) => Ok(&items[i].item),
+  }
+}
+
+/// Fuzzy selection showing `page_size` items at a time, for long lists.
+pub fn fuzzy_page_with_labels<T>(
   prompt: String,
-  items: &'a Vec<LabeledItem<T>>,
-) -> Result<&'a T, Error> {
+  items: &[LabeledItem<T>],
+  page_size: usize,
+) -> Result<&T, Error> {
   let labels: Vec<_> = items.iter().map(|e| &e.label).collect();
 
   match FuzzySelect::with_theme(&ColorfulTheme::default())
     .with_prompt(prompt)
     .default(0)
+    .max_length(page_size)
     .items(&labels)
     .interact()
   {
@@ -47,6 +71,22 @@ pub fn fuzzy_input_with_labels<'a, T>(
   }
 }
 
+/// Checkbox selection showing `page_size` items at a time.
+pub fn multi_select_with_labels<T>(
+  prompt: String,
+  items: &[LabeledItem<T>],
+  page_size: usize,
+) -> Result<Vec<&T>, Error> {
+  let labels: Vec<_> = items.iter().map(|e| &e.label).collect();
+
+  MultiSelect::with_theme(&ColorfulTheme::default())
+    .with_prompt(prompt)
+    .max_length(page_size)
+    .items(&labels)
+    .interact()
+    .map(|indices| indices.into_iter().map(|i| &items[i].item).collect())
+}
+
 pub fn confirm_default(
   prompt: String
 ) -> Result<bool, Error> {
@@ -71,17 +111,12 @@ pub fn input_until_parsed<F> (
   default_input: String
 ) -> Result<F, Error> where F: FromStr + Copy  {
   loop {
-    let string_representation: String = match 
-      Input::with_theme(&ColorfulTheme::default())
+    let string_representation: String = Input::with_theme(&ColorfulTheme::default())
       .with_prompt(prompt.clone())
       .with_initial_text(default_input.clone())
-      .interact_text()
-    {
-      Err(error) => return Err(error),
-      Ok(string_representation) => string_representation,
-    };
+      .interact_text()?;
 
-    if let Some(number) = string_representation.parse().ok() {
+    if let Ok(number) = string_representation.parse() {
       return Ok(number);
     }
   }
@@ -93,19 +128,54 @@ pub fn input_until_number_with_validation<F>(
   validate: &dyn Fn(F)-> bool
 ) -> Result<F, Error> where F: FromStr + Copy {
   loop {
-    let number = match input_until_parsed(
+    let number = input_until_parsed(
       prompt.clone(), 
       default_input.clone()
-    ) {
-      Err(error) => return Err(error),
-      Ok(number) => number
-    };
+    )?;
 
     if validate(number) {
       return Ok(number);
     }
   }
 }
+/// Reads a `YYYY-MM-DD` date, returning `None` when left empty.
+pub fn input_optional_date(prompt: String) -> Result<Option<NaiveDate>, Error> {
+  loop {
+    let text: String = Input::with_theme(&ColorfulTheme::default())
+      .with_prompt(prompt.clone())
+      .allow_empty(true)
+      .interact_text()?;
+
+    if text.trim().is_empty() {
+      return Ok(None);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d") {
+      return Ok(Some(date));
+    }
+  }
+}
+
+/// Like `input_until_parsed`, but empty input gives `None`.
+pub fn input_optional_parsed<F>(
+  prompt: String,
+  current: Option<F>,
+) -> Result<Option<F>, Error> where F: FromStr + ToString {
+  loop {
+    let text: String = Input::with_theme(&ColorfulTheme::default())
+      .with_prompt(prompt.clone())
+      .with_initial_text(current.as_ref().map(F::to_string).unwrap_or_default())
+      .allow_empty(true)
+      .interact_text()?;
+
+    if text.trim().is_empty() {
+      return Ok(None);
+    }
+    if let Ok(value) = text.trim().parse() {
+      return Ok(Some(value));
+    }
+  }
+}
+
 pub struct LabeledItem<T> {
   pub label: String,
   pub item: T,
@@ -117,10 +187,10 @@ impl<T> LabeledItem<T> {
   }
 }
 
-pub fn select_with_labels<'a, T>(
+pub fn select_with_labels<T>(
   prompt: String,
-  items: &'a Vec<LabeledItem<T>>,
-) -> Result<&'a T, Error> {
+  items: &[LabeledItem<T>],
+) -> Result<&T, Error> {
   let labels: Vec<_> = items.iter().map(|e| &e.label).collect();
 
   match Select::with_theme(&ColorfulTheme::default())
@@ -134,6 +204,23 @@ pub fn select_with_labels<'a, T>(
   }
 }
 
+/// Asks for a currency when there is more than one, listing `default_id`
+/// first.
+pub fn input_currency(
+  currencies: &HashMap<CurrencyId, Currency>,
+  default_id: &CurrencyId,
+) -> Result<CurrencyId, Error> {
+  if currencies.len() <= 1 {
+    return Ok(default_id.clone());
+  }
+  let mut options = currencies
+    .values()
+    .map(|c| LabeledItem::from(format!("{} ({})", c.name, c.symbol), &c.id))
+    .collect::<Vec<_>>();
+  options.sort_by(|a, b| (a.item != default_id, &a.label).cmp(&(b.item != default_id, &b.label)));
+  Ok((*select_with_labels("Currency:".to_string(), &options)?).clone())
+}
+
 pub trait OptionalKey<'a, K, V> { 
   fn get_optional(&'a self, key: Option<K>) -> Option<&'a V>;
   fn get_optional_mut(&'a mut self, key: Option<K>) -> Option<&'a mut V>;
@@ -141,10 +228,10 @@ pub trait OptionalKey<'a, K, V> {
 
 impl <'a, K, V> OptionalKey<'a, K, V> for HashMap<K, V> where K: Eq + Hash + 'a {
   fn get_optional(&'a self, key: Option<K>) -> Option<&'a V> {
-    key.map(|k| self.get(&k)).flatten()
+    key.and_then(|k| self.get(&k))
   }
 
   fn get_optional_mut(&'a mut self, key: Option<K>) -> Option<&'a mut V> {
-    key.map(|k| self.get_mut(&k)).flatten()
+    key.and_then(|k| self.get_mut(&k))
   }
 }
\ No newline at end of file
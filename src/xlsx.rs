@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use crate::{
+  cash_flow::cash_flow,
+  currency::{Currency, CurrencyId},
+  general_ledger::general_ledger,
+  journal::Journal,
+  report::{
+    balance_sheet, 
+    chart_of_accounts, 
+    income_statement, 
+    trial_balance, 
+    Cell, 
+    ReportOptions, 
+    Table
+  },
+};
+
+/// Builds an XLSX workbook for `journal` with sheets for the chart of
+/// accounts, general ledger, trial balance, balance sheet, income statement
+/// and cash flow statement. Amounts use a number format built from their currency.
+pub fn export_xlsx(
+  journal: &Journal,
+  currencies: &HashMap<CurrencyId, Currency>,
+  options: &ReportOptions,
+) -> Result<Vec<u8>, XlsxError> {
+  let formats = currencies
+    .iter()
+    .map(|(id, currency)| (id.clone(), currency_format(currency)))
+    .collect::<HashMap<_, _>>();
+
+  let mut workbook = Workbook::new();
+  for (name, table) in [
+    ("Chart of Accounts", chart_of_accounts(journal)),
+    ("General Ledger", general_ledger(journal, currencies, options)),
+    ("Trial Balance", trial_balance(journal, currencies, options)),
+    ("Balance Sheet", balance_sheet(journal, currencies, options)),
+    ("Income Statement", income_statement(journal, currencies, options)),
+    ("Cash Flow", cash_flow(journal, currencies, options)),
+  ] {
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(name)?;
+    write_table(worksheet, &table, &formats)?;
+  }
+  workbook.save_to_buffer()
+}
+
+/// Excel number format such as `"$"#,##0.00` or `#,##0.000 "BTC"`.
+pub fn number_format(currency: &Currency) -> String {
+  let number = match currency.precision {
+    0 => "#,##0".to_string(),
+    precision => format!("#,##0.{}", "0".repeat(precision)),
+  };
+  let symbol = format!("\"{}\"", currency.symbol.replace('"', ""));
+  let positive = match currency.symbol.chars().count() {
+    0 => number,
+    1 => format!("{}{}", symbol, number),
+    _ => format!("{} {}", number, symbol),
+  };
+  format!("{};-{}", positive, positive)
+}
+
+fn currency_format(currency: &Currency) -> Format {
+  Format::new().set_num_format(number_format(currency))
+}
+
+fn write_table(
+  worksheet: &mut Worksheet,
+  table: &Table,
+  formats: &HashMap<CurrencyId, Format>,
+) -> Result<(), XlsxError> {
+  let bold = Format::new().set_bold();
+  worksheet.write_string_with_format(0, 0, &table.title, &bold)?;
+  for (column, header) in table.headers.iter().enumerate() {
+    worksheet.write_string_with_format(1, column as u16, header, &bold)?;
+  }
+
+  for (index, row) in table.rows.iter().enumerate() {
+    let row_number = index as u32 + 2;
+    for (column, cell) in row.iter().enumerate() {
+      let column = column as u16;
+      match cell {
+        Cell::Text(text) => {
+          worksheet.write_string(row_number, column, text)?;
+        },
+        Cell::Amount(amount, currency_id) => match formats.get(currency_id) {
+          Some(format) => {
+            worksheet.write_number_with_format(row_number, column, *amount, format)?;
+          },
+          None => {
+            worksheet.write_number(row_number, column, *amount)?;
+          },
+        },
+        Cell::Empty => {},
+      }
+    }
+  }
+
+  worksheet.set_freeze_panes(2, 0)?;
+  worksheet.autofit();
+  Ok(())
+}